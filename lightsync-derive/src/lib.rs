@@ -0,0 +1,139 @@
+//! `#[derive(LightsyncConfig)]`, which implements `lightsync::LightsyncConfig` for a struct of config options.
+//!
+//! Use it through the `derive` feature of `lightsync` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, LitStr};
+
+/// Implements `LightsyncConfig` for a struct with named fields, each of which is a config option.
+///
+/// Each field can be configured with an `#[option(...)]` attribute:
+/// - `path = "Section/Name"`: the path of the option, which defaults to the name of the field.
+/// - `label = "..."`: the label shown for the option instead of its path.
+/// - `min = ..., max = ...`: makes an `i32` field a range option between `min` and `max`.
+/// - `skip`: doesn't make the field an option, so it's always left as its default.
+///
+/// Every other field has to implement `ConfigOption`.
+#[proc_macro_derive(LightsyncConfig, attributes(option))]
+pub fn derive_lightsync_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The settings from a field's `#[option(...)]` attributes.
+#[derive(Default)]
+struct OptionAttrs {
+    path: Option<LitStr>,
+    label: Option<LitStr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    skip: bool,
+}
+
+impl OptionAttrs {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<OptionAttrs> {
+        let mut options = OptionAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("option")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("path") {
+                    options.path = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("label") {
+                    options.label = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("min") {
+                    options.min = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("max") {
+                    options.max = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else {
+                    return Err(meta.error("expected `path`, `label`, `min`, `max` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`LightsyncConfig` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`LightsyncConfig` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut labels = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let attrs = OptionAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            values.push(quote! { #ident: defaults.#ident });
+            continue;
+        }
+
+        let path = attrs
+            .path
+            .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        if let Some(label) = &attrs.label {
+            labels.push(quote! {
+                ::lightsync::LightingBackend::set_config_option_label(backend, #path, #label)?;
+            });
+        }
+        let value = match (&attrs.min, &attrs.max) {
+            (Some(min), Some(max)) => quote! {
+                ::lightsync::LightingBackend::get_config_option_range(
+                    backend,
+                    #path,
+                    defaults.#ident,
+                    #min,
+                    #max,
+                )?
+            },
+            (None, None) => quote! {
+                <#ty as ::lightsync::ConfigOption>::get(backend, #path, defaults.#ident)?
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "range options need both `min` and `max`",
+                ))
+            }
+        };
+        values.push(quote! { #ident: #value });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::lightsync::LightsyncConfig for #name #ty_generics #where_clause {
+            fn load_with_defaults<__B: ::lightsync::LightingBackend>(
+                sdk: &::lightsync::Sdk<__B>,
+                defaults: Self,
+            ) -> ::std::result::Result<Self, ::lightsync::Error> {
+                let backend = sdk.backend();
+                #(#labels)*
+                ::std::result::Result::Ok(#name {
+                    #(#values,)*
+                })
+            }
+        }
+    })
+}
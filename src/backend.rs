@@ -0,0 +1,540 @@
+//! The lighting implementations that `Sdk` can drive.
+//!
+//! `Sdk` doesn't talk to the Logitech LED SDK directly; it goes through a `LightingBackend`,
+//! which exposes the same set of functions as `raw`. `Logitech` is the backend for the real SDK,
+//! but any other type implementing the trait can be plugged in with `Sdk::with_backend()`.
+
+#[cfg(windows)]
+use super::{lighting, raw};
+use super::{Bitmap, Color, DeviceType, Error, Key};
+#[cfg(windows)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A set of functions equivalent to those of the Logitech LED SDK.
+///
+/// Colors are percentages from 0 to 100 and durations are in milliseconds.
+///
+/// Functions should return `Error::NotInitialized` if they're called before `init()` or after `shutdown()`,
+/// and `Error::Unsupported` if the backend has no equivalent to them.
+pub trait LightingBackend {
+    /// Makes necessary initializations.
+    ///
+    /// It should return `Error::AlreadyRunning` if there is already an instance running.
+    fn init(&self) -> Result<(), Error>;
+
+    /// Like `init()`, but registers the integration with the name `name`.
+    fn init_with_name(&self, name: &str) -> Result<(), Error>;
+
+    /// Retrieves the version of the SDK as `(major, minor, build)`.
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error>;
+
+    /// Sets the kinds of devices affected by future calls, as a combination of the constants in `lighting`.
+    ///
+    /// It should return `Error::InvalidArgument` if `target_device` isn't a valid combination.
+    fn set_target_device(&self, target_device: i32) -> Result<(), Error>;
+
+    /// Saves the current lighting so it can be restored with `restore_lighting()`.
+    fn save_current_lighting(&self) -> Result<(), Error>;
+
+    /// Sets the lighting of all targeted devices.
+    fn set_lighting(&self, color: Color) -> Result<(), Error>;
+
+    /// Sets the lighting of zone `zone` on all devices of type `device_type`.
+    fn set_lighting_for_target_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error>;
+
+    /// Restores the lighting saved by `save_current_lighting()`.
+    fn restore_lighting(&self) -> Result<(), Error>;
+
+    /// Plays a flashing effect on all targeted devices.
+    fn flash_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error>;
+
+    /// Plays a pulsing effect on all targeted devices.
+    fn pulse_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error>;
+
+    /// Stops any flashing or pulsing effects.
+    fn stop_effects(&self) -> Result<(), Error>;
+
+    /// Sets the lighting of per-key devices to `bitmap`, leaving cells with an alpha of 0 alone.
+    fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error>;
+
+    /// Sets a list of keys to be ignored by `set_lighting_from_bitmap()`.
+    fn exclude_keys_from_bitmap(&self, keys: &[Key]) -> Result<(), Error>;
+
+    /// Sets the color of the key with scan code `code`.
+    fn set_lighting_for_key_with_scan_code(&self, code: i32, color: Color) -> Result<(), Error>;
+
+    /// Sets the color of the key with HID code `code`.
+    fn set_lighting_for_key_with_hid_code(&self, code: i32, color: Color) -> Result<(), Error>;
+
+    /// Sets the color of the key with Quartz code `code`.
+    fn set_lighting_for_key_with_quartz_code(&self, code: i32, color: Color) -> Result<(), Error>;
+
+    /// Sets the color of `key`.
+    fn set_lighting_for_key_with_key_name(&self, key: Key, color: Color) -> Result<(), Error>;
+
+    /// Saves the current color of `key` so it can be restored with `restore_lighting_for_key()`.
+    fn save_lighting_for_key(&self, key: Key) -> Result<(), Error>;
+
+    /// Restores the color of `key` saved by `save_lighting_for_key()`.
+    fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error>;
+
+    /// Plays a flashing effect on `key`.
+    fn flash_single_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: i32,
+        interval: i32,
+    ) -> Result<(), Error>;
+
+    /// Plays a pulsing effect on `key`, fading from `start` to `end`.
+    fn pulse_single_key(
+        &self,
+        key: Key,
+        start: Color,
+        end: Color,
+        duration: i32,
+        infinite: bool,
+    ) -> Result<(), Error>;
+
+    /// Stops any effects on `key`.
+    fn stop_effects_on_key(&self, key: Key) -> Result<(), Error>;
+
+    /// Restores the lighting saved at initialization and frees any resources.
+    fn shutdown(&self);
+
+    /// Sets the label shown for the config option at `path`.
+    fn set_config_option_label(&self, path: &str, label: &str) -> Result<(), Error>;
+
+    /// Gets the number chosen for the config option at `path`, or `default`.
+    fn get_config_option_number(&self, path: &str, default: f64) -> Result<f64, Error>;
+
+    /// Gets the boolean chosen for the config option at `path`, or `default`.
+    fn get_config_option_bool(&self, path: &str, default: bool) -> Result<bool, Error>;
+
+    /// Gets the color chosen for the config option at `path`, or `default`.
+    fn get_config_option_color(&self, path: &str, default: Color) -> Result<Color, Error>;
+
+    /// Gets the number between `min` and `max` chosen for the config option at `path`, or `default`.
+    fn get_config_option_range(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+    ) -> Result<i32, Error>;
+
+    /// Gets the rectangle chosen for the config option at `path`, or `default`.
+    fn get_config_option_rect(
+        &self,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error>;
+
+    /// Gets the string chosen for the config option at `path`, or `default`.
+    fn get_config_option_string(&self, path: &str, default: &str) -> Result<String, Error>;
+
+    /// Gets the name of the key chosen for the config option at `path`, or `default`.
+    fn get_config_option_key_input(&self, path: &str, default: &str) -> Result<String, Error>;
+
+    /// Gets which of `values` was chosen for the config option at `path`, or `default`.
+    fn get_config_option_select(
+        &self,
+        path: &str,
+        default: &str,
+        values: &[&str],
+    ) -> Result<String, Error>;
+}
+
+/// The Logitech LED SDK, as provided by Logitech Gaming Software or G HUB.
+///
+/// The SDK only exists on Windows. On other platforms every call returns `Error::Unsupported`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Logitech;
+
+/// Whether the SDK is currently initialized, since it can only be initialized once per process.
+#[cfg(windows)]
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Turns the result of an SDK call into a `Result`.
+///
+/// The SDK doesn't say why a call failed, so assume the connection's been lost unless we know it hasn't been initialized.
+#[cfg(windows)]
+fn check<T>(result: Result<T, Error>) -> Result<T, Error> {
+    result.map_err(|err| match err {
+        Error::ConnectionLost if !INITIALIZED.load(Ordering::SeqCst) => Error::NotInitialized,
+        err => err,
+    })
+}
+
+/// Marks the SDK as initialized, or returns `Error::AlreadyRunning` if it already is.
+///
+/// This is done in one step so that two threads initializing at once can't both get through.
+#[cfg(windows)]
+fn claim() -> Result<(), Error> {
+    INITIALIZED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .map(|_| ())
+        .map_err(|_| Error::AlreadyRunning)
+}
+
+#[cfg(windows)]
+fn check_bool(succeeded: bool) -> Result<(), Error> {
+    check(if succeeded {
+        Ok(())
+    } else {
+        Err(Error::ConnectionLost)
+    })
+}
+
+#[cfg(windows)]
+impl LightingBackend for Logitech {
+    fn init(&self) -> Result<(), Error> {
+        claim()?;
+        if raw::init() {
+            Ok(())
+        } else {
+            INITIALIZED.store(false, Ordering::SeqCst);
+            Err(Error::ConnectionLost)
+        }
+    }
+
+    fn init_with_name(&self, name: &str) -> Result<(), Error> {
+        claim()?;
+        raw::init_with_name(name).inspect_err(|_| {
+            INITIALIZED.store(false, Ordering::SeqCst);
+        })
+    }
+
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error> {
+        check(raw::get_sdk_version().ok_or(Error::ConnectionLost))
+    }
+
+    fn set_target_device(&self, target_device: i32) -> Result<(), Error> {
+        if target_device & !lighting::ALL != 0 {
+            return Err(Error::InvalidArgument);
+        }
+        check_bool(raw::set_target_device(target_device))
+    }
+
+    fn save_current_lighting(&self) -> Result<(), Error> {
+        check_bool(raw::save_current_lighting())
+    }
+
+    fn set_lighting(&self, color: Color) -> Result<(), Error> {
+        check_bool(raw::set_lighting(color))
+    }
+
+    fn set_lighting_for_target_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error> {
+        check_bool(raw::set_lighting_for_target_zone(device_type, zone, color))
+    }
+
+    fn restore_lighting(&self) -> Result<(), Error> {
+        check_bool(raw::restore_lighting())
+    }
+
+    fn flash_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        check_bool(raw::flash_lighting(color, duration, interval))
+    }
+
+    fn pulse_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        check_bool(raw::pulse_lighting(color, duration, interval))
+    }
+
+    fn stop_effects(&self) -> Result<(), Error> {
+        check_bool(raw::stop_effects())
+    }
+
+    fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error> {
+        check_bool(raw::set_lighting_from_bitmap(
+            &mut bitmap.as_bytes().to_vec(),
+        ))
+    }
+
+    fn exclude_keys_from_bitmap(&self, keys: &[Key]) -> Result<(), Error> {
+        check_bool(raw::exclude_keys_from_bitmap(keys))
+    }
+
+    fn set_lighting_for_key_with_scan_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        check_bool(raw::set_lighting_for_key_with_scan_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_hid_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        check_bool(raw::set_lighting_for_key_with_hid_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_quartz_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        check_bool(raw::set_lighting_for_key_with_quartz_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_key_name(&self, key: Key, color: Color) -> Result<(), Error> {
+        check_bool(raw::set_lighting_for_key_with_key_name(key, color))
+    }
+
+    fn save_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        check_bool(raw::save_lighting_for_key(key))
+    }
+
+    fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        check_bool(raw::restore_lighting_for_key(key))
+    }
+
+    fn flash_single_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: i32,
+        interval: i32,
+    ) -> Result<(), Error> {
+        check_bool(raw::flash_single_key(key, color, duration, interval))
+    }
+
+    fn pulse_single_key(
+        &self,
+        key: Key,
+        start: Color,
+        end: Color,
+        duration: i32,
+        infinite: bool,
+    ) -> Result<(), Error> {
+        check_bool(raw::pulse_single_key(key, start, end, duration, infinite))
+    }
+
+    fn stop_effects_on_key(&self, key: Key) -> Result<(), Error> {
+        check_bool(raw::stop_effects_on_key(key))
+    }
+
+    fn shutdown(&self) {
+        raw::shutdown();
+        INITIALIZED.store(false, Ordering::SeqCst);
+    }
+
+    fn set_config_option_label(&self, path: &str, label: &str) -> Result<(), Error> {
+        check(raw::set_config_option_label(path, label))
+    }
+
+    fn get_config_option_number(&self, path: &str, default: f64) -> Result<f64, Error> {
+        check(raw::get_config_option_number(path, default))
+    }
+
+    fn get_config_option_bool(&self, path: &str, default: bool) -> Result<bool, Error> {
+        check(raw::get_config_option_bool(path, default))
+    }
+
+    fn get_config_option_color(&self, path: &str, default: Color) -> Result<Color, Error> {
+        check(raw::get_config_option_color(path, default))
+    }
+
+    fn get_config_option_range(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+    ) -> Result<i32, Error> {
+        check(raw::get_config_option_range(path, default, min, max))
+    }
+
+    fn get_config_option_rect(
+        &self,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        check(raw::get_config_option_rect(
+            path, default.0, default.1, default.2, default.3,
+        ))
+    }
+
+    fn get_config_option_string(&self, path: &str, default: &str) -> Result<String, Error> {
+        check(raw::get_config_option_string(path, default))
+    }
+
+    fn get_config_option_key_input(&self, path: &str, default: &str) -> Result<String, Error> {
+        check(raw::get_config_option_key_input(path, default))
+    }
+
+    fn get_config_option_select(
+        &self,
+        path: &str,
+        default: &str,
+        values: &[&str],
+    ) -> Result<String, Error> {
+        check(raw::get_config_option_select(path, default, values))
+    }
+}
+
+#[cfg(not(windows))]
+impl LightingBackend for Logitech {
+    fn init(&self) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn init_with_name(&self, _name: &str) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_target_device(&self, _target_device: i32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn save_current_lighting(&self) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting(&self, _color: Color) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_for_target_zone(
+        &self,
+        _device_type: DeviceType,
+        _zone: i32,
+        _color: Color,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn restore_lighting(&self) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn flash_lighting(&self, _color: Color, _duration: i32, _interval: i32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn pulse_lighting(&self, _color: Color, _duration: i32, _interval: i32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn stop_effects(&self) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_from_bitmap(&self, _bitmap: &Bitmap) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn exclude_keys_from_bitmap(&self, _keys: &[Key]) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_for_key_with_scan_code(&self, _code: i32, _color: Color) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_for_key_with_hid_code(&self, _code: i32, _color: Color) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_for_key_with_quartz_code(
+        &self,
+        _code: i32,
+        _color: Color,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn set_lighting_for_key_with_key_name(&self, _key: Key, _color: Color) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn save_lighting_for_key(&self, _key: Key) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn restore_lighting_for_key(&self, _key: Key) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn flash_single_key(
+        &self,
+        _key: Key,
+        _color: Color,
+        _duration: i32,
+        _interval: i32,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn pulse_single_key(
+        &self,
+        _key: Key,
+        _start: Color,
+        _end: Color,
+        _duration: i32,
+        _infinite: bool,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn stop_effects_on_key(&self, _key: Key) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn shutdown(&self) {}
+
+    fn set_config_option_label(&self, _path: &str, _label: &str) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_number(&self, _path: &str, _default: f64) -> Result<f64, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_bool(&self, _path: &str, _default: bool) -> Result<bool, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_color(&self, _path: &str, _default: Color) -> Result<Color, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_range(
+        &self,
+        _path: &str,
+        _default: i32,
+        _min: i32,
+        _max: i32,
+    ) -> Result<i32, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_rect(
+        &self,
+        _path: &str,
+        _default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_string(&self, _path: &str, _default: &str) -> Result<String, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_key_input(&self, _path: &str, _default: &str) -> Result<String, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn get_config_option_select(
+        &self,
+        _path: &str,
+        _default: &str,
+        _values: &[&str],
+    ) -> Result<String, Error> {
+        Err(Error::Unsupported)
+    }
+}
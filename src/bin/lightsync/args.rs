@@ -0,0 +1,366 @@
+//! Parsing of the command line.
+
+use lightsync::{Color, DeviceType, Key, TargetDevices};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: lightsync [OPTIONS] <COMMAND>
+
+Commands:
+  set <COLOR>                   Set the lighting of every device
+  key <KEY> <COLOR>             Set the color of a single key
+  zone <DEVICE> <ZONE> <COLOR>  Set a zone of every device of a type
+                                (keyboard, mouse, mousemat, headset or speaker)
+  flash <COLOR>                 Flash the lighting, or a single key with --key
+  pulse <COLOR>                 Pulse the lighting, or a single key with --key
+  bitmap <FILE>                 Set per-key devices to a file of raw RGBA bitmap bytes
+  run <SCENE>                   Apply a scene file and run its effects, reloading it when it changes
+  preview <SCENE>               Draw a scene file running in the terminal, without using the SDK
+  render <SCENE> <FILE>         Draw a scene file to a .png or .svg file, or animate it in a .gif
+                                or .png file with --frames, without using the SDK
+  version                       Print the version of the SDK
+
+Options:
+  --target <KIND>      Only light devices of a kind (monochrome, rgb, perkey-rgb or all);
+                       can be given more than once
+  --dry-run            Print the SDK calls instead of making them
+  --hold <SECONDS>     Keep the connection open for this long before exiting; by default it's
+                       kept open until the process is killed, or for as long as a flash or pulse,
+                       and isn't kept open at all with --dry-run
+  --key <KEY>          Flash or pulse a single key
+  --to <COLOR>         The color a single key pulses to [default: #000000]
+  --duration <SECONDS> How long to flash or pulse for, or 0 to keep going [default: 0]
+  --interval <SECONDS> How long each flash or pulse takes [default: 0.5]
+  --fps <FPS>          The frame rate to run effects at [default: 30]
+  --frames <FRAMES>    The number of frames to render [default: 1]
+  -h, --help           Print this message
+
+Colors are hex colors like #ff8800, and keys are names of `lightsync::Key` variants like Esc or NumEnter.
+The lighting set by a command is only kept while the connection to the SDK is open.";
+
+/// What to do, and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    /// The devices to target, or `None` to leave the SDK's default.
+    pub target: Option<TargetDevices>,
+    pub dry_run: bool,
+    pub hold: Option<Duration>,
+    pub frame_rate: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Set(Color),
+    Key(Key, Color),
+    Zone(DeviceType, i32, Color),
+    Flash {
+        key: Option<Key>,
+        color: Color,
+        duration: Duration,
+        interval: Duration,
+    },
+    Pulse {
+        key: Option<Key>,
+        color: Color,
+        to: Color,
+        duration: Duration,
+        interval: Duration,
+    },
+    Bitmap(PathBuf),
+    Run(PathBuf),
+    Preview(PathBuf),
+    Render {
+        scene: PathBuf,
+        output: PathBuf,
+        frames: usize,
+    },
+    Version,
+}
+
+fn color(s: &str) -> Result<Color, String> {
+    s.parse().map_err(|_| format!("invalid color `{}`", s))
+}
+
+fn key(s: &str) -> Result<Key, String> {
+    s.parse().map_err(|_| format!("unknown key name `{}`", s))
+}
+
+fn device_type(s: &str) -> Result<DeviceType, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "keyboard" => Ok(DeviceType::Keyboard),
+        "mouse" => Ok(DeviceType::Mouse),
+        "mousemat" => Ok(DeviceType::Mousemat),
+        "headset" => Ok(DeviceType::Headset),
+        "speaker" => Ok(DeviceType::Speaker),
+        _ => Err(format!("unknown device type `{}`", s)),
+    }
+}
+
+fn target(s: &str) -> Result<TargetDevices, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "monochrome" => Ok(TargetDevices::MONOCHROME),
+        "rgb" => Ok(TargetDevices::RGB),
+        "perkey-rgb" | "perkey_rgb" => Ok(TargetDevices::PERKEY_RGB),
+        "all" => Ok(TargetDevices::ALL),
+        _ => Err(format!("unknown kind of device `{}`", s)),
+    }
+}
+
+fn seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("invalid number of seconds `{}`", s))
+}
+
+impl Args {
+    /// Parses the arguments after the name of the program.
+    ///
+    /// Options can come before or after the command, either as `--name value` or `--name=value`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut target = None;
+        let mut dry_run = false;
+        let mut hold = None;
+        let mut frame_rate = 30;
+        let mut frames = 1;
+        let mut single_key = None;
+        let mut to = Color::BLACK;
+        let mut duration = Duration::from_secs(0);
+        let mut interval = Duration::from_millis(500);
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") || arg == "--" {
+                if arg == "-h" {
+                    return Ok(Args::help());
+                }
+                if arg == "--" {
+                    positional.extend(args.by_ref());
+                } else {
+                    positional.push(arg);
+                }
+                continue;
+            }
+
+            let (name, inline) = match arg.find('=') {
+                Some(index) => (&arg[..index], Some(arg[index + 1..].to_owned())),
+                None => (arg.as_str(), None),
+            };
+            match name {
+                "--help" => return Ok(Args::help()),
+                "--dry-run" => {
+                    dry_run = true;
+                    continue;
+                }
+                _ => {}
+            }
+            let value = match inline.or_else(|| args.next()) {
+                Some(value) => value,
+                None => return Err(format!("`{}` needs a value", name)),
+            };
+            match name {
+                "--target" => {
+                    *target.get_or_insert(TargetDevices::empty()) |= self::target(&value)?
+                }
+                "--hold" => hold = Some(seconds(&value)?),
+                "--key" => single_key = Some(key(&value)?),
+                "--to" => to = color(&value)?,
+                "--duration" => duration = seconds(&value)?,
+                "--interval" => interval = seconds(&value)?,
+                "--fps" => {
+                    frame_rate = value
+                        .parse()
+                        .ok()
+                        .filter(|&fps| fps > 0)
+                        .ok_or_else(|| format!("invalid frame rate `{}`", value))?
+                }
+                "--frames" => {
+                    frames = value
+                        .parse()
+                        .ok()
+                        .filter(|&frames| frames > 0)
+                        .ok_or_else(|| format!("invalid number of frames `{}`", value))?
+                }
+                _ => return Err(format!("unknown option `{}`", name)),
+            }
+        }
+
+        let positional: Vec<_> = positional.iter().map(String::as_str).collect();
+        let command = match positional[..] {
+            [] | ["help"] => Command::Help,
+            ["set", c] => Command::Set(color(c)?),
+            ["key", k, c] => Command::Key(key(k)?, color(c)?),
+            ["zone", device, zone, c] => Command::Zone(
+                device_type(device)?,
+                zone.parse()
+                    .map_err(|_| format!("invalid zone `{}`", zone))?,
+                color(c)?,
+            ),
+            ["flash", c] => Command::Flash {
+                key: single_key,
+                color: color(c)?,
+                duration,
+                interval,
+            },
+            ["pulse", c] => Command::Pulse {
+                key: single_key,
+                color: color(c)?,
+                to,
+                duration,
+                interval,
+            },
+            ["bitmap", file] => Command::Bitmap(file.into()),
+            ["run", scene] => Command::Run(scene.into()),
+            ["preview", scene] => Command::Preview(scene.into()),
+            ["render", scene, output] => Command::Render {
+                scene: scene.into(),
+                output: output.into(),
+                frames,
+            },
+            ["version"] => Command::Version,
+            [command, ..] => {
+                let known = [
+                    "set", "key", "zone", "flash", "pulse", "bitmap", "run", "preview", "render",
+                    "version",
+                ];
+                return Err(if known.contains(&command) {
+                    format!("wrong number of arguments to `{}`", command)
+                } else {
+                    format!("unknown command `{}`", command)
+                });
+            }
+        };
+
+        Ok(Args {
+            command,
+            target,
+            dry_run,
+            hold,
+            frame_rate,
+        })
+    }
+
+    fn help() -> Args {
+        Args {
+            command: Command::Help,
+            target: None,
+            dry_run: false,
+            hold: None,
+            frame_rate: 30,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            parse("set #ff0000").unwrap().command,
+            Command::Set(Color::RED)
+        );
+        assert_eq!(
+            parse("key esc 00f").unwrap().command,
+            Command::Key(Key::Esc, Color::BLUE)
+        );
+        assert_eq!(
+            parse("zone mouse 1 #00ff00").unwrap().command,
+            Command::Zone(DeviceType::Mouse, 1, Color::GREEN)
+        );
+        assert_eq!(
+            parse("pulse --key W #ffffff --duration 2").unwrap().command,
+            Command::Pulse {
+                key: Some(Key::W),
+                color: Color::WHITE,
+                to: Color::BLACK,
+                duration: Duration::from_secs(2),
+                interval: Duration::from_millis(500),
+            }
+        );
+        assert_eq!(
+            parse("run scene.toml").unwrap().command,
+            Command::Run("scene.toml".into())
+        );
+        assert_eq!(
+            parse("render --frames 10 scene.toml out.gif")
+                .unwrap()
+                .command,
+            Command::Render {
+                scene: "scene.toml".into(),
+                output: "out.gif".into(),
+                frames: 10,
+            }
+        );
+        assert_eq!(parse("").unwrap().command, Command::Help);
+        assert_eq!(parse("version --help").unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn options() {
+        let args = parse("--dry-run --target rgb set fff --target=monochrome --hold 1.5").unwrap();
+        assert!(args.dry_run);
+        assert_eq!(
+            args.target,
+            Some(TargetDevices::RGB | TargetDevices::MONOCHROME)
+        );
+        assert_eq!(args.hold, Some(Duration::from_millis(1500)));
+        assert_eq!(args.frame_rate, 30);
+
+        let args = parse("version").unwrap();
+        assert!(!args.dry_run);
+        assert_eq!(args.target, None);
+        assert_eq!(args.hold, None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("set red").unwrap_err(), "invalid color `red`");
+        assert_eq!(
+            parse("key Nope fff").unwrap_err(),
+            "unknown key name `Nope`"
+        );
+        assert_eq!(
+            parse("zone mouse one fff").unwrap_err(),
+            "invalid zone `one`"
+        );
+        assert_eq!(
+            parse("set fff --target keyboard").unwrap_err(),
+            "unknown kind of device `keyboard`"
+        );
+        assert_eq!(
+            parse("set fff --hold -1").unwrap_err(),
+            "invalid number of seconds `-1`"
+        );
+        assert_eq!(
+            parse("set fff --hold 1e30").unwrap_err(),
+            "invalid number of seconds `1e30`"
+        );
+        assert_eq!(
+            parse("set fff --hold").unwrap_err(),
+            "`--hold` needs a value"
+        );
+        assert_eq!(
+            parse("run --fps 0 a").unwrap_err(),
+            "invalid frame rate `0`"
+        );
+        assert_eq!(
+            parse("set").unwrap_err(),
+            "wrong number of arguments to `set`"
+        );
+        assert_eq!(parse("paint fff").unwrap_err(), "unknown command `paint`");
+        assert_eq!(
+            parse("--loud set fff").unwrap_err(),
+            "unknown option `--loud`"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! A backend which prints the calls it's given instead of making them.
+
+use lightsync::mock::{Call, MockBackend};
+use lightsync::{Bitmap, Color, DeviceType, Error, Key, LightingBackend, TargetDevices};
+
+/// A `LightingBackend` which prints each call like the Logitech LED SDK function it would call,
+/// so commands can be tried out without the SDK.
+///
+/// The calls go through a `MockBackend`, so they fail in the same situations as the real SDK.
+#[derive(Debug, Clone, Default)]
+pub struct DryRun {
+    mock: MockBackend,
+}
+
+impl DryRun {
+    pub fn new() -> DryRun {
+        DryRun::default()
+    }
+
+    /// Prints the calls made since the last time this was called.
+    fn print<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        for call in self.mock.calls() {
+            println!("{}", format_call(&call));
+        }
+        self.mock.clear_calls();
+        result
+    }
+}
+
+fn percent(color: Color) -> String {
+    let (red, green, blue) = color.to_percent();
+    format!("{}, {}, {}", red, green, blue)
+}
+
+/// Formats `call` like a call to the SDK function it stands for, with colors in percentages.
+fn format_call(call: &Call) -> String {
+    match call {
+        Call::Init => "LogiLedInit()".to_owned(),
+        Call::InitWithName(name) => format!("LogiLedInitWithName({:?})", name),
+        Call::GetSdkVersion => "LogiLedGetSdkVersion()".to_owned(),
+        Call::SetTargetDevice(target) => format!(
+            "LogiLedSetTargetDevice({:?})",
+            TargetDevices::from_bits_truncate(*target)
+        ),
+        Call::SaveCurrentLighting => "LogiLedSaveCurrentLighting()".to_owned(),
+        Call::SetLighting(color) => format!("LogiLedSetLighting({})", percent(*color)),
+        Call::SetLightingForTargetZone(device_type, zone, color) => format!(
+            "LogiLedSetLightingForTargetZone({:?}, {}, {})",
+            device_type,
+            zone,
+            percent(*color)
+        ),
+        Call::RestoreLighting => "LogiLedRestoreLighting()".to_owned(),
+        Call::FlashLighting(color, duration, interval) => format!(
+            "LogiLedFlashLighting({}, {}, {})",
+            percent(*color),
+            duration,
+            interval
+        ),
+        Call::PulseLighting(color, duration, interval) => format!(
+            "LogiLedPulseLighting({}, {}, {})",
+            percent(*color),
+            duration,
+            interval
+        ),
+        Call::StopEffects => "LogiLedStopEffects()".to_owned(),
+        Call::SetLightingFromBitmap(bitmap) => {
+            let cells: Vec<_> = bitmap
+                .iter()
+                .filter(|&(key, _)| bitmap.alpha(key) != Some(0))
+                .map(|(key, color)| format!("{:?}: {}", key, color))
+                .collect();
+            format!("LogiLedSetLightingFromBitmap({{{}}})", cells.join(", "))
+        }
+        Call::ExcludeKeysFromBitmap(keys) => format!("LogiLedExcludeKeysFromBitmap({:?})", keys),
+        Call::SetLightingForKeyWithScanCode(code, color) => format!(
+            "LogiLedSetLightingForKeyWithScanCode({:#x}, {})",
+            code,
+            percent(*color)
+        ),
+        Call::SetLightingForKeyWithHidCode(code, color) => format!(
+            "LogiLedSetLightingForKeyWithHidCode({:#x}, {})",
+            code,
+            percent(*color)
+        ),
+        Call::SetLightingForKeyWithQuartzCode(code, color) => format!(
+            "LogiLedSetLightingForKeyWithQuartzCode({:#x}, {})",
+            code,
+            percent(*color)
+        ),
+        Call::SetLightingForKeyWithKeyName(key, color) => format!(
+            "LogiLedSetLightingForKeyWithKeyName({:?}, {})",
+            key,
+            percent(*color)
+        ),
+        Call::SaveLightingForKey(key) => format!("LogiLedSaveLightingForKey({:?})", key),
+        Call::RestoreLightingForKey(key) => format!("LogiLedRestoreLightingForKey({:?})", key),
+        Call::FlashSingleKey(key, color, duration, interval) => format!(
+            "LogiLedFlashSingleKey({:?}, {}, {}, {})",
+            key,
+            percent(*color),
+            duration,
+            interval
+        ),
+        Call::PulseSingleKey(key, start, end, duration, infinite) => format!(
+            "LogiLedPulseSingleKey({:?}, {}, {}, {}, {})",
+            key,
+            percent(*start),
+            percent(*end),
+            duration,
+            infinite
+        ),
+        Call::StopEffectsOnKey(key) => format!("LogiLedStopEffectsOnKey({:?})", key),
+        Call::Shutdown => "LogiLedShutdown()".to_owned(),
+        Call::SetConfigOptionLabel(path, label) => {
+            format!("LogiLedSetConfigOptionLabel({:?}, {:?})", path, label)
+        }
+        Call::GetConfigOptionNumber(path) => format!("LogiLedGetConfigOptionNumber({:?})", path),
+        Call::GetConfigOptionBool(path) => format!("LogiLedGetConfigOptionBool({:?})", path),
+        Call::GetConfigOptionColor(path) => format!("LogiLedGetConfigOptionColor({:?})", path),
+        Call::GetConfigOptionRange(path) => format!("LogiLedGetConfigOptionRange({:?})", path),
+        Call::GetConfigOptionRect(path) => format!("LogiLedGetConfigOptionRect({:?})", path),
+        Call::GetConfigOptionString(path) => format!("LogiLedGetConfigOptionString({:?})", path),
+        Call::GetConfigOptionKeyInput(path) => {
+            format!("LogiLedGetConfigOptionKeyInput({:?})", path)
+        }
+        Call::GetConfigOptionSelect(path, values) => {
+            format!("LogiLedGetConfigOptionSelect({:?}, {:?})", path, values)
+        }
+    }
+}
+
+impl LightingBackend for DryRun {
+    fn init(&self) -> Result<(), Error> {
+        self.print(self.mock.init())
+    }
+
+    fn init_with_name(&self, name: &str) -> Result<(), Error> {
+        self.print(self.mock.init_with_name(name))
+    }
+
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error> {
+        self.print(self.mock.get_sdk_version())
+    }
+
+    fn set_target_device(&self, target_device: i32) -> Result<(), Error> {
+        self.print(self.mock.set_target_device(target_device))
+    }
+
+    fn save_current_lighting(&self) -> Result<(), Error> {
+        self.print(self.mock.save_current_lighting())
+    }
+
+    fn set_lighting(&self, color: Color) -> Result<(), Error> {
+        self.print(self.mock.set_lighting(color))
+    }
+
+    fn set_lighting_for_target_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error> {
+        self.print(
+            self.mock
+                .set_lighting_for_target_zone(device_type, zone, color),
+        )
+    }
+
+    fn restore_lighting(&self) -> Result<(), Error> {
+        self.print(self.mock.restore_lighting())
+    }
+
+    fn flash_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.print(self.mock.flash_lighting(color, duration, interval))
+    }
+
+    fn pulse_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.print(self.mock.pulse_lighting(color, duration, interval))
+    }
+
+    fn stop_effects(&self) -> Result<(), Error> {
+        self.print(self.mock.stop_effects())
+    }
+
+    fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error> {
+        self.print(self.mock.set_lighting_from_bitmap(bitmap))
+    }
+
+    fn exclude_keys_from_bitmap(&self, keys: &[Key]) -> Result<(), Error> {
+        self.print(self.mock.exclude_keys_from_bitmap(keys))
+    }
+
+    fn set_lighting_for_key_with_scan_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.print(self.mock.set_lighting_for_key_with_scan_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_hid_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.print(self.mock.set_lighting_for_key_with_hid_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_quartz_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.print(self.mock.set_lighting_for_key_with_quartz_code(code, color))
+    }
+
+    fn set_lighting_for_key_with_key_name(&self, key: Key, color: Color) -> Result<(), Error> {
+        self.print(self.mock.set_lighting_for_key_with_key_name(key, color))
+    }
+
+    fn save_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.print(self.mock.save_lighting_for_key(key))
+    }
+
+    fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.print(self.mock.restore_lighting_for_key(key))
+    }
+
+    fn flash_single_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: i32,
+        interval: i32,
+    ) -> Result<(), Error> {
+        self.print(self.mock.flash_single_key(key, color, duration, interval))
+    }
+
+    fn pulse_single_key(
+        &self,
+        key: Key,
+        start: Color,
+        end: Color,
+        duration: i32,
+        infinite: bool,
+    ) -> Result<(), Error> {
+        self.print(
+            self.mock
+                .pulse_single_key(key, start, end, duration, infinite),
+        )
+    }
+
+    fn stop_effects_on_key(&self, key: Key) -> Result<(), Error> {
+        self.print(self.mock.stop_effects_on_key(key))
+    }
+
+    fn shutdown(&self) {
+        self.mock.shutdown();
+        let _ = self.print(Ok(()));
+    }
+
+    fn set_config_option_label(&self, path: &str, label: &str) -> Result<(), Error> {
+        self.print(self.mock.set_config_option_label(path, label))
+    }
+
+    fn get_config_option_number(&self, path: &str, default: f64) -> Result<f64, Error> {
+        self.print(self.mock.get_config_option_number(path, default))
+    }
+
+    fn get_config_option_bool(&self, path: &str, default: bool) -> Result<bool, Error> {
+        self.print(self.mock.get_config_option_bool(path, default))
+    }
+
+    fn get_config_option_color(&self, path: &str, default: Color) -> Result<Color, Error> {
+        self.print(self.mock.get_config_option_color(path, default))
+    }
+
+    fn get_config_option_range(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+    ) -> Result<i32, Error> {
+        self.print(self.mock.get_config_option_range(path, default, min, max))
+    }
+
+    fn get_config_option_rect(
+        &self,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        self.print(self.mock.get_config_option_rect(path, default))
+    }
+
+    fn get_config_option_string(&self, path: &str, default: &str) -> Result<String, Error> {
+        self.print(self.mock.get_config_option_string(path, default))
+    }
+
+    fn get_config_option_key_input(&self, path: &str, default: &str) -> Result<String, Error> {
+        self.print(self.mock.get_config_option_key_input(path, default))
+    }
+
+    fn get_config_option_select(
+        &self,
+        path: &str,
+        default: &str,
+        values: &[&str],
+    ) -> Result<String, Error> {
+        self.print(self.mock.get_config_option_select(path, default, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting() {
+        assert_eq!(
+            format_call(&Call::SetLighting(Color::RED)),
+            "LogiLedSetLighting(100, 0, 0)"
+        );
+        assert_eq!(
+            format_call(&Call::SetLightingForTargetZone(
+                DeviceType::Mouse,
+                1,
+                Color::GREEN
+            )),
+            "LogiLedSetLightingForTargetZone(Mouse, 1, 0, 100, 0)"
+        );
+        assert_eq!(
+            format_call(&Call::SetTargetDevice(0b011)),
+            "LogiLedSetTargetDevice(MONOCHROME | RGB)"
+        );
+
+        let mut bitmap = Bitmap::new();
+        bitmap.set(Key::W, Color::BLUE);
+        assert_eq!(
+            format_call(&Call::SetLightingFromBitmap(Box::new(bitmap))),
+            "LogiLedSetLightingFromBitmap({W: #0000ff})"
+        );
+
+        assert_eq!(
+            format_call(&Call::GetConfigOptionColor("Colors/Background".to_owned())),
+            "LogiLedGetConfigOptionColor(\"Colors/Background\")"
+        );
+        assert_eq!(
+            format_call(&Call::GetConfigOptionSelect(
+                "Difficulty".to_owned(),
+                vec!["Easy".to_owned(), "Hard".to_owned()]
+            )),
+            "LogiLedGetConfigOptionSelect(\"Difficulty\", [\"Easy\", \"Hard\"])"
+        );
+    }
+}
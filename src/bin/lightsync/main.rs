@@ -0,0 +1,238 @@
+//! `lightsync`, a command-line tool for setting the lighting of Logitech devices from the shell.
+//!
+//! Run `lightsync --help` for usage.
+
+mod args;
+mod dry_run;
+
+use args::{Args, Command, USAGE};
+use dry_run::DryRun;
+use lightsync::mock::MockBackend;
+use lightsync::{
+    Bitmap, Engine, HeadlessEngine, KeyboardImage, LightingBackend, Logitech, Scene, SceneWatcher,
+    Sdk, TerminalPreview,
+};
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often `run` checks whether the scene file has changed.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("lightsync: {}\nRun `lightsync --help` for usage.", message);
+            process::exit(2);
+        }
+    };
+    if args.command == Command::Help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let result = if let Command::Preview(path) = &args.command {
+        preview(path, &args)
+    } else if let Command::Render {
+        scene,
+        output,
+        frames,
+    } = &args.command
+    {
+        render(scene, output, *frames, &args)
+    } else if args.dry_run {
+        Sdk::with_backend(DryRun::new())
+            .map_err(Box::from)
+            .and_then(|sdk| run(Arc::new(sdk), &args))
+    } else {
+        Sdk::with_backend_and_name(Logitech, "lightsync")
+            .map_err(Box::from)
+            .and_then(|sdk| run(Arc::new(sdk), &args))
+    };
+    if let Err(err) = result {
+        eprintln!("lightsync: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Loads the scene at `path` onto a `MockBackend`, ready to run its effects.
+fn simulate(path: &Path, args: &Args) -> Result<HeadlessEngine<MockBackend>, Box<dyn Error>> {
+    let scene = Scene::load(path)?;
+    let sdk = Sdk::with_backend(MockBackend::new())?;
+    if let Some(target) = args.target {
+        sdk.set_target_devices(target)?;
+    }
+    scene.apply(&sdk)?;
+
+    let mut engine = HeadlessEngine::new(Arc::new(sdk), args.frame_rate);
+    if let Some(effect) = scene.effect() {
+        engine.set_effect(effect);
+    }
+    Ok(engine)
+}
+
+/// Draws the scene at `path` in the terminal.
+fn preview(path: &Path, args: &Args) -> Result<(), Box<dyn Error>> {
+    let mut engine = simulate(path, args)?;
+    TerminalPreview::new().animate(&mut engine, args.hold, io::stdout())?;
+    Ok(())
+}
+
+/// Draws `frames` frames of the scene at `path` to an image at `output`, whose format is decided by its extension.
+fn render(path: &Path, output: &Path, frames: usize, args: &Args) -> Result<(), Box<dyn Error>> {
+    let mut engine = simulate(path, args)?;
+    let image = KeyboardImage::new();
+    match output.extension().and_then(OsStr::to_str) {
+        Some("svg") if frames > 1 => Err("SVGs can't be animated; use a .gif or .png file".into()),
+        Some("svg") => {
+            engine.start()?;
+            fs::write(output, image.to_svg(&engine.sdk().snapshot()))?;
+            Ok(())
+        }
+        Some("png") if frames == 1 => {
+            engine.start()?;
+            image.write_png(&engine.sdk().snapshot(), create(output)?)?;
+            Ok(())
+        }
+        Some("png") => Ok(image.write_apng(&mut engine, frames, create(output)?)?),
+        Some("gif") => Ok(image.write_gif(&mut engine, frames, create(output)?)?),
+        _ => Err("images must have a `.png`, `.svg` or `.gif` extension".into()),
+    }
+}
+
+fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    File::create(path).map(BufWriter::new)
+}
+
+/// Waits until `deadline`, or forever if there isn't one.
+fn wait(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => thread::sleep(deadline.saturating_duration_since(Instant::now())),
+        None => loop {
+            thread::park();
+        },
+    }
+}
+
+fn run<B>(sdk: Arc<Sdk<B>>, args: &Args) -> Result<(), Box<dyn Error>>
+where
+    B: LightingBackend + Send + Sync + 'static,
+{
+    let start = Instant::now();
+    // The SDK restores the lighting when the connection closes, so keep it open
+    // unless we're only printing the calls.
+    let hold = match args.hold {
+        Some(hold) => Some(hold),
+        None if args.dry_run => Some(Duration::from_secs(0)),
+        None => None,
+    };
+    let deadline = hold.map(|hold| start + hold);
+
+    if let Some(target) = args.target {
+        sdk.set_target_devices(target)?;
+    }
+
+    match &args.command {
+        Command::Help | Command::Preview(_) | Command::Render { .. } => {}
+        Command::Set(color) => sdk.set_lighting(*color)?,
+        Command::Key(key, color) => sdk.set_lighting_for_key(*key, *color)?,
+        Command::Zone(device_type, zone, color) => {
+            sdk.set_lighting_for_zone(*device_type, *zone, *color)?
+        }
+        &Command::Flash {
+            key,
+            color,
+            duration,
+            interval,
+        } => {
+            match key {
+                Some(key) => sdk.flash_key(key, color, duration, interval)?,
+                None => sdk.flash_lighting(color, duration, interval)?,
+            }
+            if args.hold.is_none() && !args.dry_run && duration > Duration::from_secs(0) {
+                thread::sleep(duration);
+                return Ok(());
+            }
+        }
+        &Command::Pulse {
+            key,
+            color,
+            to,
+            duration,
+            interval,
+        } => {
+            let forever = duration == Duration::from_secs(0);
+            match key {
+                // A key pulses once over its duration, so pulse forever with the interval as the duration.
+                Some(key) if forever => sdk.pulse_key(key, color, to, interval, true)?,
+                Some(key) => sdk.pulse_key(key, color, to, duration, false)?,
+                None => sdk.pulse_lighting(color, duration, interval)?,
+            }
+            if args.hold.is_none() && !args.dry_run && !forever {
+                thread::sleep(duration);
+                return Ok(());
+            }
+        }
+        Command::Bitmap(path) => {
+            let bytes = fs::read(path)?;
+            let bitmap = Bitmap::from_bytes(&bytes).ok_or_else(|| {
+                format!(
+                    "`{}` is {} bytes long, but bitmaps are {} bytes long",
+                    path.display(),
+                    bytes.len(),
+                    Bitmap::new().as_bytes().len()
+                )
+            })?;
+            sdk.set_lighting_from_bitmap(&bitmap)?;
+        }
+        Command::Run(path) => {
+            let mut engine = Engine::new(Arc::clone(&sdk), args.frame_rate);
+            let mut watcher = SceneWatcher::new(path);
+            let mut loaded = false;
+            loop {
+                match watcher.poll() {
+                    Some(Ok(scene)) => {
+                        engine.stop();
+                        scene.apply(&sdk)?;
+                        if let Some(effect) = scene.effect() {
+                            engine.set_effect(effect);
+                            engine.start();
+                        }
+                        loaded = true;
+                    }
+                    // Keep showing the last scene which loaded while the file's being edited.
+                    Some(Err(err)) if loaded => eprintln!("lightsync: {}", err),
+                    Some(Err(err)) => return Err(err.into()),
+                    None => {}
+                }
+                if let Some(err) = engine.last_error() {
+                    return Err(err.into());
+                }
+
+                let now = Instant::now();
+                match deadline {
+                    Some(deadline) if now >= deadline => break,
+                    Some(deadline) => thread::sleep(POLL_INTERVAL.min(deadline - now)),
+                    None => thread::sleep(POLL_INTERVAL),
+                }
+            }
+            engine.stop();
+            return Ok(());
+        }
+        Command::Version => {
+            let (major, minor, build) = sdk.version()?;
+            println!("{}.{}.{}", major, minor, build);
+            return Ok(());
+        }
+    }
+
+    wait(deadline);
+    Ok(())
+}
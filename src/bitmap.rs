@@ -0,0 +1,316 @@
+use super::{Color, Key, BITMAP_HEIGHT, BITMAP_LAYOUT, BITMAP_SIZE, BITMAP_WIDTH, BYTES_PER_KEY};
+use std::fmt;
+
+/// A grid of colors to display on per-key devices with `Sdk::set_lighting_from_bitmap()`.
+///
+/// Each cell has a color and an alpha. Cells with an alpha of 0 are left alone when the bitmap is displayed,
+/// which allows updating only part of the keyboard. A new bitmap is completely transparent.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Bitmap, Color, Key};
+///
+/// let sdk = lightsync::Sdk::init()?;
+///
+/// let mut bitmap = Bitmap::new();
+/// bitmap.fill_row(1, Color::new(0, 0, 100));
+/// bitmap.set(Key::W, Color::new(100, 0, 0));
+/// sdk.set_lighting_from_bitmap(&bitmap)?;
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+///
+/// # Layout
+/// The grid is arranged like so:
+///
+/// |       | **0**       | **1**       | **2**   | **3** | **4** | **5** | **6** | **7** | **8** | **9**  | **10**       | **11**      | **12**       | **13**            | **14**         | **15**     | **16**    | **17**     | **18**   | **19**      | **20**   |
+/// |-------|-------------|-------------|---------|-------|-------|-------|-------|-------|-------|--------|--------------|-------------|--------------|-------------------|----------------|------------|-----------|------------|----------|-------------|----------|
+/// | **0** | Esc         | F1          | F2      | F3    | F4    | F5    | F6    | F7    | F8    | F9     | F10          | F11         | F12          | PrintScreen       | ScrollLock     | PauseBreak |           |            |          |             |          |
+/// | **1** | Tilde       | One         | Two     | Three | Four  | Five  | Six   | Seven | Eight | Nine   | Zero         | Minus       | Equals       | Backspace         | Insert         | Home       | PageUp    | NumLock    | NumSlash | NumAsterisk | NumMinus |
+/// | **2** | Tab         | Q           | W       | E     | R     | T     | Y     | U     | I     | O      | P            | OpenBracket | CloseBracket | Backslash         | KeyboardDelete | End        | PageDown  | NumSeven   | NumEight | NumNine     | NumPlus  |
+/// | **3** | CapsLock    | A           | S       | D     | F     | G     | H     | J     | K     | L      | Semicolon    | Apostrophe  |              | Enter             |                |            |           | NumFour    | NumFive  | NumSix      |          |
+/// | **4** | LeftShift   | Z           | X       | C     | V     | B     | N     | M     | Comma | Period | ForwardSlash |             |              | RightShift        |                | ArrowUp    |           | NumOne     | NumTwo   | NumThree    | NumEnter |
+/// | **5** | LeftControl | LeftWindows | LeftAlt |       |       | Space |       |       |       |        |              | RightAlt    | RightWindows | ApplicationSelect | RightControl   | ArrowLeft  | ArrowDown | ArrowRight | NumZero  | NumPeriod   |          |
+///
+/// Keys which aren't in the grid, like the G keys, can't be set with a bitmap.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    /// The RGBA bytes of each cell, from the top left to the bottom right.
+    bytes: [u8; BITMAP_SIZE as usize],
+}
+
+impl Bitmap {
+    /// The number of columns in the grid.
+    pub const WIDTH: usize = BITMAP_WIDTH as usize;
+    /// The number of rows in the grid.
+    pub const HEIGHT: usize = BITMAP_HEIGHT as usize;
+
+    /// Creates a completely transparent bitmap.
+    pub const fn new() -> Bitmap {
+        Bitmap {
+            bytes: [0; BITMAP_SIZE as usize],
+        }
+    }
+
+    /// Creates a bitmap with every cell set to `color`.
+    pub fn filled(color: Color) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        bitmap.fill(color);
+        bitmap
+    }
+
+    /// Creates a bitmap from an array of `BITMAP_SIZE` RGBA bytes, like the one taken by `raw::set_lighting_from_bitmap()`.
+    ///
+    /// Returns `None` if `bytes` isn't `BITMAP_SIZE` bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Bitmap> {
+        let mut bitmap = Bitmap::new();
+        if bytes.len() != bitmap.bytes.len() {
+            return None;
+        }
+        bitmap.bytes.copy_from_slice(bytes);
+        Some(bitmap)
+    }
+
+    /// Returns the RGBA bytes of each cell, from the top left to the bottom right.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the index into `bytes` of the cell at `row` and `col`.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    fn index(row: usize, col: usize) -> usize {
+        assert!(
+            row < Bitmap::HEIGHT && col < Bitmap::WIDTH,
+            "cell ({}, {}) is outside of the bitmap",
+            row,
+            col
+        );
+        (row * Bitmap::WIDTH + col) * BYTES_PER_KEY as usize
+    }
+
+    /// Returns the color of the cell at `row` and `col`.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn cell(&self, row: usize, col: usize) -> Color {
+        let i = Bitmap::index(row, col);
+        Color::from_rgb8(self.bytes[i], self.bytes[i + 1], self.bytes[i + 2])
+    }
+
+    /// Returns the alpha of the cell at `row` and `col`.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn cell_alpha(&self, row: usize, col: usize) -> u8 {
+        self.bytes[Bitmap::index(row, col) + 3]
+    }
+
+    /// Sets the color of the cell at `row` and `col`, and makes it opaque.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn set_cell(&mut self, row: usize, col: usize, color: Color) {
+        self.set_cell_with_alpha(row, col, color, u8::MAX);
+    }
+
+    /// Sets the color and alpha of the cell at `row` and `col`.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn set_cell_with_alpha(&mut self, row: usize, col: usize, color: Color, alpha: u8) {
+        let i = Bitmap::index(row, col);
+        self.bytes[i..i + 4].copy_from_slice(&[color.red, color.green, color.blue, alpha]);
+    }
+
+    /// Sets the alpha of the cell at `row` and `col`, keeping its color.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is out of bounds.
+    pub fn set_cell_alpha(&mut self, row: usize, col: usize, alpha: u8) {
+        self.bytes[Bitmap::index(row, col) + 3] = alpha;
+    }
+
+    /// Returns the color of `key`, or `None` if it isn't in the grid.
+    pub fn get(&self, key: Key) -> Option<Color> {
+        key.bitmap_position().map(|(row, col)| self.cell(row, col))
+    }
+
+    /// Returns the alpha of `key`, or `None` if it isn't in the grid.
+    pub fn alpha(&self, key: Key) -> Option<u8> {
+        key.bitmap_position()
+            .map(|(row, col)| self.cell_alpha(row, col))
+    }
+
+    /// Sets the color of `key`, and makes it opaque.
+    ///
+    /// Keys which aren't in the grid are ignored.
+    pub fn set(&mut self, key: Key, color: Color) {
+        self.set_with_alpha(key, color, u8::MAX);
+    }
+
+    /// Sets the color and alpha of `key`.
+    ///
+    /// Keys which aren't in the grid are ignored.
+    pub fn set_with_alpha(&mut self, key: Key, color: Color, alpha: u8) {
+        if let Some((row, col)) = key.bitmap_position() {
+            self.set_cell_with_alpha(row, col, color, alpha);
+        }
+    }
+
+    /// Sets the alpha of `key`, keeping its color.
+    ///
+    /// Keys which aren't in the grid are ignored.
+    pub fn set_alpha(&mut self, key: Key, alpha: u8) {
+        if let Some((row, col)) = key.bitmap_position() {
+            self.set_cell_alpha(row, col, alpha);
+        }
+    }
+
+    /// Sets every cell to `color`, and makes them opaque.
+    pub fn fill(&mut self, color: Color) {
+        for row in 0..Bitmap::HEIGHT {
+            self.fill_row(row, color);
+        }
+    }
+
+    /// Sets every cell in row `row` to `color`, and makes them opaque.
+    ///
+    /// # Panics
+    /// Panics if `row` is out of bounds.
+    pub fn fill_row(&mut self, row: usize, color: Color) {
+        for col in 0..Bitmap::WIDTH {
+            self.set_cell(row, col, color);
+        }
+    }
+
+    /// Makes every cell transparent.
+    pub fn clear(&mut self) {
+        *self = Bitmap::new();
+    }
+
+    /// Returns an iterator over the keys in the grid and their colors, from the top left to the bottom right.
+    pub fn iter(&self) -> impl Iterator<Item = (Key, Color)> + '_ {
+        self.cells()
+            .filter_map(|((row, col), color)| BITMAP_LAYOUT[row][col].map(|key| (key, color)))
+    }
+
+    /// Returns an iterator over the positions and colors of every cell, including ones without a key,
+    /// from the top left to the bottom right.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), Color)> + '_ {
+        (0..Bitmap::HEIGHT)
+            .flat_map(|row| (0..Bitmap::WIDTH).map(move |col| (row, col)))
+            .map(move |(row, col)| ((row, col), self.cell(row, col)))
+    }
+}
+
+impl Default for Bitmap {
+    fn default() -> Bitmap {
+        Bitmap::new()
+    }
+}
+
+impl fmt::Debug for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<Vec<_>> = (0..Bitmap::HEIGHT)
+            .map(|row| {
+                (0..Bitmap::WIDTH)
+                    .map(|col| (self.cell(row, col), self.cell_alpha(row, col)))
+                    .collect()
+            })
+            .collect();
+        f.debug_struct("Bitmap").field("cells", &rows).finish()
+    }
+}
+
+impl From<[[[u8; 4]; 21]; 6]> for Bitmap {
+    /// Creates a bitmap from an array of RGBA bytes indexed by row and then column.
+    fn from(cells: [[[u8; 4]; 21]; 6]) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        for (row, cells) in cells.iter().enumerate() {
+            for (col, &[red, green, blue, alpha]) in cells.iter().enumerate() {
+                bitmap.set_cell_with_alpha(row, col, Color::from_rgb8(red, green, blue), alpha);
+            }
+        }
+        bitmap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_and_cells() {
+        let mut bitmap = Bitmap::new();
+        assert_eq!(bitmap.get(Key::W), Some(Color::BLACK));
+        assert_eq!(bitmap.alpha(Key::W), Some(0));
+
+        bitmap.set(Key::W, Color::RED);
+        assert_eq!(bitmap.cell(2, 2), Color::RED);
+        assert_eq!(bitmap.cell_alpha(2, 2), 255);
+        assert_eq!(
+            &bitmap.as_bytes()[(2 * 21 + 2) * 4..][..4],
+            &[255, 0, 0, 255]
+        );
+
+        bitmap.set_cell_with_alpha(5, 5, Color::BLUE, 128);
+        assert_eq!(bitmap.get(Key::Space), Some(Color::BLUE));
+        assert_eq!(bitmap.alpha(Key::Space), Some(128));
+
+        bitmap.set_alpha(Key::W, 0);
+        assert_eq!(bitmap.get(Key::W), Some(Color::RED));
+        assert_eq!(bitmap.alpha(Key::W), Some(0));
+    }
+
+    #[test]
+    fn keys_outside_grid() {
+        let mut bitmap = Bitmap::new();
+        bitmap.set(Key::G1, Color::RED);
+        assert_eq!(bitmap.get(Key::G1), None);
+        assert_eq!(bitmap.alpha(Key::G1), None);
+        assert_eq!(bitmap, Bitmap::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_out_of_bounds() {
+        Bitmap::new().cell(0, 21);
+    }
+
+    #[test]
+    fn fill() {
+        let mut bitmap = Bitmap::new();
+        bitmap.fill_row(0, Color::GREEN);
+        assert!((0..21).all(|col| bitmap.cell(0, col) == Color::GREEN));
+        assert!((0..21).all(|col| bitmap.cell_alpha(1, col) == 0));
+
+        bitmap.fill(Color::WHITE);
+        assert_eq!(bitmap, Bitmap::filled(Color::WHITE));
+        assert!(bitmap.cells().all(|(_, color)| color == Color::WHITE));
+
+        bitmap.clear();
+        assert_eq!(bitmap, Bitmap::new());
+    }
+
+    #[test]
+    fn iter() {
+        let bitmap = Bitmap::filled(Color::RED);
+        let keys: Vec<Key> = bitmap.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys.len(), 104);
+        assert_eq!(&keys[..3], &[Key::Esc, Key::F1, Key::F2]);
+        assert_eq!(keys.last(), Some(&Key::NumPeriod));
+        assert_eq!(bitmap.cells().count(), 21 * 6);
+    }
+
+    #[test]
+    fn from_arrays_and_bytes() {
+        let mut cells = [[[0; 4]; 21]; 6];
+        cells[3][1] = [1, 2, 3, 4];
+        let bitmap = Bitmap::from(cells);
+        assert_eq!(bitmap.get(Key::A), Some(Color::from_rgb8(1, 2, 3)));
+        assert_eq!(bitmap.alpha(Key::A), Some(4));
+        assert_eq!(Bitmap::from_bytes(bitmap.as_bytes()), Some(bitmap));
+        assert_eq!(Bitmap::from_bytes(&[0; 4]), None);
+    }
+}
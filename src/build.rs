@@ -1,6 +1,12 @@
 use std::env;
 
 fn main() {
+    // The Logitech LED SDK is only available on Windows,
+    // and the `Logitech` backend doesn't call into it anywhere else.
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() != "windows" {
+        return;
+    }
+
     let proj_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 
     println!(
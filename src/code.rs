@@ -0,0 +1,204 @@
+use super::Key;
+
+/// A PC keyboard scan code, as passed to `raw::set_lighting_for_key_with_scan_code()`.
+///
+/// Codes are set 1 make codes, and keys which send an `0xE0` prefix have `0x100` added,
+/// so the right control key is `0x11D`. This is the same numbering as `Key`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScanCode(pub u16);
+
+/// A USB HID usage ID from the keyboard usage page, as passed to `raw::set_lighting_for_key_with_hid_code()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HidCode(pub u16);
+
+/// A macOS virtual key code (`kVK_*`), as passed to `raw::set_lighting_for_key_with_quartz_code()`.
+///
+/// Keys which aren't on Mac keyboards use the codes of their equivalents on Apple extended keyboards,
+/// so `Insert` is `kVK_Help` and `PrintScreen`, `ScrollLock` and `PauseBreak` are F13 to F15.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QuartzCode(pub u16);
+
+/// A key code in any of the code spaces supported by the SDK.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Scan(ScanCode),
+    Hid(HidCode),
+    Quartz(QuartzCode),
+}
+
+impl From<ScanCode> for KeyCode {
+    fn from(code: ScanCode) -> KeyCode {
+        KeyCode::Scan(code)
+    }
+}
+
+impl From<HidCode> for KeyCode {
+    fn from(code: HidCode) -> KeyCode {
+        KeyCode::Hid(code)
+    }
+}
+
+impl From<QuartzCode> for KeyCode {
+    fn from(code: QuartzCode) -> KeyCode {
+        KeyCode::Quartz(code)
+    }
+}
+
+/// The scan code, HID code and Quartz code of every key which has them.
+///
+/// The G keys, `GLogo` and `GBadge` don't have codes, so they're missing.
+#[rustfmt::skip]
+pub(crate) const KEY_CODES: [(Key, ScanCode, HidCode, QuartzCode); 104] = [
+    (Key::Esc, ScanCode(0x01), HidCode(0x29), QuartzCode(0x35)),
+    (Key::One, ScanCode(0x02), HidCode(0x1E), QuartzCode(0x12)),
+    (Key::Two, ScanCode(0x03), HidCode(0x1F), QuartzCode(0x13)),
+    (Key::Three, ScanCode(0x04), HidCode(0x20), QuartzCode(0x14)),
+    (Key::Four, ScanCode(0x05), HidCode(0x21), QuartzCode(0x15)),
+    (Key::Five, ScanCode(0x06), HidCode(0x22), QuartzCode(0x17)),
+    (Key::Six, ScanCode(0x07), HidCode(0x23), QuartzCode(0x16)),
+    (Key::Seven, ScanCode(0x08), HidCode(0x24), QuartzCode(0x1A)),
+    (Key::Eight, ScanCode(0x09), HidCode(0x25), QuartzCode(0x1C)),
+    (Key::Nine, ScanCode(0x0A), HidCode(0x26), QuartzCode(0x19)),
+    (Key::Zero, ScanCode(0x0B), HidCode(0x27), QuartzCode(0x1D)),
+    (Key::Minus, ScanCode(0x0C), HidCode(0x2D), QuartzCode(0x1B)),
+    (Key::Equals, ScanCode(0x0D), HidCode(0x2E), QuartzCode(0x18)),
+    (Key::Backspace, ScanCode(0x0E), HidCode(0x2A), QuartzCode(0x33)),
+    (Key::Tab, ScanCode(0x0F), HidCode(0x2B), QuartzCode(0x30)),
+    (Key::Q, ScanCode(0x10), HidCode(0x14), QuartzCode(0x0C)),
+    (Key::W, ScanCode(0x11), HidCode(0x1A), QuartzCode(0x0D)),
+    (Key::E, ScanCode(0x12), HidCode(0x08), QuartzCode(0x0E)),
+    (Key::R, ScanCode(0x13), HidCode(0x15), QuartzCode(0x0F)),
+    (Key::T, ScanCode(0x14), HidCode(0x17), QuartzCode(0x11)),
+    (Key::Y, ScanCode(0x15), HidCode(0x1C), QuartzCode(0x10)),
+    (Key::U, ScanCode(0x16), HidCode(0x18), QuartzCode(0x20)),
+    (Key::I, ScanCode(0x17), HidCode(0x0C), QuartzCode(0x22)),
+    (Key::O, ScanCode(0x18), HidCode(0x12), QuartzCode(0x1F)),
+    (Key::P, ScanCode(0x19), HidCode(0x13), QuartzCode(0x23)),
+    (Key::OpenBracket, ScanCode(0x1A), HidCode(0x2F), QuartzCode(0x21)),
+    (Key::CloseBracket, ScanCode(0x1B), HidCode(0x30), QuartzCode(0x1E)),
+    (Key::Enter, ScanCode(0x1C), HidCode(0x28), QuartzCode(0x24)),
+    (Key::LeftControl, ScanCode(0x1D), HidCode(0xE0), QuartzCode(0x3B)),
+    (Key::A, ScanCode(0x1E), HidCode(0x04), QuartzCode(0x00)),
+    (Key::S, ScanCode(0x1F), HidCode(0x16), QuartzCode(0x01)),
+    (Key::D, ScanCode(0x20), HidCode(0x07), QuartzCode(0x02)),
+    (Key::F, ScanCode(0x21), HidCode(0x09), QuartzCode(0x03)),
+    (Key::G, ScanCode(0x22), HidCode(0x0A), QuartzCode(0x05)),
+    (Key::H, ScanCode(0x23), HidCode(0x0B), QuartzCode(0x04)),
+    (Key::J, ScanCode(0x24), HidCode(0x0D), QuartzCode(0x26)),
+    (Key::K, ScanCode(0x25), HidCode(0x0E), QuartzCode(0x28)),
+    (Key::L, ScanCode(0x26), HidCode(0x0F), QuartzCode(0x25)),
+    (Key::Semicolon, ScanCode(0x27), HidCode(0x33), QuartzCode(0x29)),
+    (Key::Apostrophe, ScanCode(0x28), HidCode(0x34), QuartzCode(0x27)),
+    (Key::Tilde, ScanCode(0x29), HidCode(0x35), QuartzCode(0x32)),
+    (Key::LeftShift, ScanCode(0x2A), HidCode(0xE1), QuartzCode(0x38)),
+    (Key::Backslash, ScanCode(0x2B), HidCode(0x31), QuartzCode(0x2A)),
+    (Key::Z, ScanCode(0x2C), HidCode(0x1D), QuartzCode(0x06)),
+    (Key::X, ScanCode(0x2D), HidCode(0x1B), QuartzCode(0x07)),
+    (Key::C, ScanCode(0x2E), HidCode(0x06), QuartzCode(0x08)),
+    (Key::V, ScanCode(0x2F), HidCode(0x19), QuartzCode(0x09)),
+    (Key::B, ScanCode(0x30), HidCode(0x05), QuartzCode(0x0B)),
+    (Key::N, ScanCode(0x31), HidCode(0x11), QuartzCode(0x2D)),
+    (Key::M, ScanCode(0x32), HidCode(0x10), QuartzCode(0x2E)),
+    (Key::Comma, ScanCode(0x33), HidCode(0x36), QuartzCode(0x2B)),
+    (Key::Period, ScanCode(0x34), HidCode(0x37), QuartzCode(0x2F)),
+    (Key::ForwardSlash, ScanCode(0x35), HidCode(0x38), QuartzCode(0x2C)),
+    (Key::RightShift, ScanCode(0x36), HidCode(0xE5), QuartzCode(0x3C)),
+    (Key::NumAsterisk, ScanCode(0x37), HidCode(0x55), QuartzCode(0x43)),
+    (Key::LeftAlt, ScanCode(0x38), HidCode(0xE2), QuartzCode(0x3A)),
+    (Key::Space, ScanCode(0x39), HidCode(0x2C), QuartzCode(0x31)),
+    (Key::CapsLock, ScanCode(0x3A), HidCode(0x39), QuartzCode(0x39)),
+    (Key::F1, ScanCode(0x3B), HidCode(0x3A), QuartzCode(0x7A)),
+    (Key::F2, ScanCode(0x3C), HidCode(0x3B), QuartzCode(0x78)),
+    (Key::F3, ScanCode(0x3D), HidCode(0x3C), QuartzCode(0x63)),
+    (Key::F4, ScanCode(0x3E), HidCode(0x3D), QuartzCode(0x76)),
+    (Key::F5, ScanCode(0x3F), HidCode(0x3E), QuartzCode(0x60)),
+    (Key::F6, ScanCode(0x40), HidCode(0x3F), QuartzCode(0x61)),
+    (Key::F7, ScanCode(0x41), HidCode(0x40), QuartzCode(0x62)),
+    (Key::F8, ScanCode(0x42), HidCode(0x41), QuartzCode(0x64)),
+    (Key::F9, ScanCode(0x43), HidCode(0x42), QuartzCode(0x65)),
+    (Key::F10, ScanCode(0x44), HidCode(0x43), QuartzCode(0x6D)),
+    (Key::NumLock, ScanCode(0x45), HidCode(0x53), QuartzCode(0x47)),
+    (Key::ScrollLock, ScanCode(0x46), HidCode(0x47), QuartzCode(0x6B)),
+    (Key::NumSeven, ScanCode(0x47), HidCode(0x5F), QuartzCode(0x59)),
+    (Key::NumEight, ScanCode(0x48), HidCode(0x60), QuartzCode(0x5B)),
+    (Key::NumNine, ScanCode(0x49), HidCode(0x61), QuartzCode(0x5C)),
+    (Key::NumMinus, ScanCode(0x4A), HidCode(0x56), QuartzCode(0x4E)),
+    (Key::NumFour, ScanCode(0x4B), HidCode(0x5C), QuartzCode(0x56)),
+    (Key::NumFive, ScanCode(0x4C), HidCode(0x5D), QuartzCode(0x57)),
+    (Key::NumSix, ScanCode(0x4D), HidCode(0x5E), QuartzCode(0x58)),
+    (Key::NumPlus, ScanCode(0x4E), HidCode(0x57), QuartzCode(0x45)),
+    (Key::NumOne, ScanCode(0x4F), HidCode(0x59), QuartzCode(0x53)),
+    (Key::NumTwo, ScanCode(0x50), HidCode(0x5A), QuartzCode(0x54)),
+    (Key::NumThree, ScanCode(0x51), HidCode(0x5B), QuartzCode(0x55)),
+    (Key::NumZero, ScanCode(0x52), HidCode(0x62), QuartzCode(0x52)),
+    (Key::NumPeriod, ScanCode(0x53), HidCode(0x63), QuartzCode(0x41)),
+    (Key::F11, ScanCode(0x57), HidCode(0x44), QuartzCode(0x67)),
+    (Key::F12, ScanCode(0x58), HidCode(0x45), QuartzCode(0x6F)),
+    (Key::NumEnter, ScanCode(0x11C), HidCode(0x58), QuartzCode(0x4C)),
+    (Key::RightControl, ScanCode(0x11D), HidCode(0xE4), QuartzCode(0x3E)),
+    (Key::NumSlash, ScanCode(0x135), HidCode(0x54), QuartzCode(0x4B)),
+    (Key::PrintScreen, ScanCode(0x137), HidCode(0x46), QuartzCode(0x69)),
+    (Key::RightAlt, ScanCode(0x138), HidCode(0xE6), QuartzCode(0x3D)),
+    (Key::PauseBreak, ScanCode(0x145), HidCode(0x48), QuartzCode(0x71)),
+    (Key::Home, ScanCode(0x147), HidCode(0x4A), QuartzCode(0x73)),
+    (Key::ArrowUp, ScanCode(0x148), HidCode(0x52), QuartzCode(0x7E)),
+    (Key::PageUp, ScanCode(0x149), HidCode(0x4B), QuartzCode(0x74)),
+    (Key::ArrowLeft, ScanCode(0x14B), HidCode(0x50), QuartzCode(0x7B)),
+    (Key::ArrowRight, ScanCode(0x14D), HidCode(0x4F), QuartzCode(0x7C)),
+    (Key::End, ScanCode(0x14F), HidCode(0x4D), QuartzCode(0x77)),
+    (Key::ArrowDown, ScanCode(0x150), HidCode(0x51), QuartzCode(0x7D)),
+    (Key::PageDown, ScanCode(0x151), HidCode(0x4E), QuartzCode(0x79)),
+    (Key::Insert, ScanCode(0x152), HidCode(0x49), QuartzCode(0x72)),
+    (Key::KeyboardDelete, ScanCode(0x153), HidCode(0x4C), QuartzCode(0x75)),
+    (Key::LeftWindows, ScanCode(0x15B), HidCode(0xE3), QuartzCode(0x37)),
+    (Key::RightWindows, ScanCode(0x15C), HidCode(0xE7), QuartzCode(0x36)),
+    (Key::ApplicationSelect, ScanCode(0x15D), HidCode(0x65), QuartzCode(0x6E)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn scan_codes_match_key_names() {
+        for &(key, scan_code, _, _) in KEY_CODES.iter() {
+            assert_eq!(i32::from(key), i32::from(scan_code.0), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let keys: HashSet<_> = KEY_CODES.iter().map(|entry| entry.0).collect();
+        let scan_codes: HashSet<_> = KEY_CODES.iter().map(|entry| entry.1).collect();
+        let hid_codes: HashSet<_> = KEY_CODES.iter().map(|entry| entry.2).collect();
+        let quartz_codes: HashSet<_> = KEY_CODES.iter().map(|entry| entry.3).collect();
+        assert_eq!(keys.len(), KEY_CODES.len());
+        assert_eq!(scan_codes.len(), KEY_CODES.len());
+        assert_eq!(hid_codes.len(), KEY_CODES.len());
+        assert_eq!(quartz_codes.len(), KEY_CODES.len());
+    }
+
+    #[test]
+    fn round_trip() {
+        for &(key, scan_code, hid_code, quartz_code) in KEY_CODES.iter() {
+            assert_eq!(key.scan_code(), Some(scan_code));
+            assert_eq!(key.hid_code(), Some(hid_code));
+            assert_eq!(key.quartz_code(), Some(quartz_code));
+            assert_eq!(Key::from_scan_code(scan_code), Some(key));
+            assert_eq!(Key::from_hid_code(hid_code), Some(key));
+            assert_eq!(Key::from_quartz_code(quartz_code), Some(key));
+            assert_eq!(Key::from_code(hid_code), Some(key));
+        }
+    }
+
+    #[test]
+    fn keys_without_codes() {
+        assert_eq!(Key::G1.scan_code(), None);
+        assert_eq!(Key::GLogo.hid_code(), None);
+        assert_eq!(Key::GBadge.quartz_code(), None);
+        assert_eq!(Key::from_scan_code(ScanCode(0x54)), None);
+        assert_eq!(Key::from_hid_code(HidCode(0)), None);
+        assert_eq!(Key::from_quartz_code(QuartzCode(0x0A)), None);
+    }
+}
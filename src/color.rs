@@ -0,0 +1,265 @@
+use super::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// An RGB color.
+///
+/// Colors are stored with 8 bits per channel, like in the bitmap passed to `Sdk::set_lighting_from_bitmap()`.
+/// The rest of the SDK takes percentages from 0 to 100, which are rounded to the nearest byte,
+/// so converting a percentage to a `Color` and back always gives the same percentage.
+///
+/// # Example
+/// ```
+/// use lightsync::Color;
+///
+/// let orange: Color = "#ff8800".parse()?;
+/// assert_eq!(orange, Color::from_rgb8(255, 136, 0));
+/// assert_eq!(orange.to_percent(), (100, 53, 0));
+/// assert_eq!(Color::new(100, 53, 0).to_percent(), (100, 53, 0));
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+/// Converts a percentage to a byte, rounding to the nearest one.
+const fn percent_to_byte(percent: i32) -> u8 {
+    let percent = if percent < 0 {
+        0
+    } else if percent > 100 {
+        100
+    } else {
+        percent
+    };
+    ((percent * 255 + 50) / 100) as u8
+}
+
+/// Converts a byte to a percentage, rounding to the nearest one.
+const fn byte_to_percent(byte: u8) -> i32 {
+    (byte as i32 * 100 + 127) / 255
+}
+
+impl Color {
+    pub const BLACK: Color = Color::from_rgb8(0, 0, 0);
+    pub const WHITE: Color = Color::from_rgb8(255, 255, 255);
+    pub const RED: Color = Color::from_rgb8(255, 0, 0);
+    pub const GREEN: Color = Color::from_rgb8(0, 255, 0);
+    pub const BLUE: Color = Color::from_rgb8(0, 0, 255);
+
+    /// Creates a color from percentages of red, green and blue, like the SDK takes.
+    ///
+    /// Percentages outside of 0 to 100 are clamped; use `from_percent()` to reject them instead.
+    pub const fn new(red: i32, green: i32, blue: i32) -> Color {
+        Color {
+            red: percent_to_byte(red),
+            green: percent_to_byte(green),
+            blue: percent_to_byte(blue),
+        }
+    }
+
+    /// Creates a color from percentages of red, green and blue, like the SDK takes.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if any percentage is outside of 0 to 100.
+    pub fn from_percent(red: i32, green: i32, blue: i32) -> Result<Color, Error> {
+        if [red, green, blue]
+            .iter()
+            .all(|percent| (0..=100).contains(percent))
+        {
+            Ok(Color::new(red, green, blue))
+        } else {
+            Err(Error::InvalidArgument)
+        }
+    }
+
+    /// Creates a color from bytes of red, green and blue, like the bitmap passed to `Sdk::set_lighting_from_bitmap()`.
+    pub const fn from_rgb8(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    /// Parses a hex color like `#ff8800`, `ff8800` or `#f80`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `hex` isn't a valid hex color.
+    pub fn from_hex(hex: &str) -> Result<Color, Error> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidArgument);
+        }
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).unwrap();
+        match hex.len() {
+            3 => Ok(Color::from_rgb8(
+                channel(&hex[0..1]) * 0x11,
+                channel(&hex[1..2]) * 0x11,
+                channel(&hex[2..3]) * 0x11,
+            )),
+            6 => Ok(Color::from_rgb8(
+                channel(&hex[0..2]),
+                channel(&hex[2..4]),
+                channel(&hex[4..6]),
+            )),
+            _ => Err(Error::InvalidArgument),
+        }
+    }
+
+    /// Creates a color from a hue in degrees, and a saturation and value from 0 to 1.
+    ///
+    /// Hues outside of 0 to 360 wrap around, and saturations and values outside of 0 to 1 are clamped.
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Color {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        Color::from_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Creates a color from a hue in degrees, and a saturation and lightness from 0 to 1.
+    ///
+    /// Hues outside of 0 to 360 wrap around, and saturations and lightnesses outside of 0 to 1 are clamped.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// The part of the HSV and HSL conversions which is the same for both.
+    fn from_chroma(hue: f64, chroma: f64, min: f64) -> Color {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let byte = |channel: f64| ((channel + min) * 255.0).round() as u8;
+        Color::from_rgb8(byte(red), byte(green), byte(blue))
+    }
+
+    /// Returns the percentages of red, green and blue in this color, like the SDK takes.
+    pub const fn to_percent(self) -> (i32, i32, i32) {
+        (
+            byte_to_percent(self.red),
+            byte_to_percent(self.green),
+            byte_to_percent(self.blue),
+        )
+    }
+
+    /// Returns the bytes of red, green and blue in this color, like the bitmap passed to `Sdk::set_lighting_from_bitmap()`.
+    pub const fn to_rgb8(self) -> (u8, u8, u8) {
+        (self.red, self.green, self.blue)
+    }
+
+    /// Mixes this color with `other`, returning this color when `t` is 0 and `other` when `t` is 1.
+    ///
+    /// `t` is clamped to 0 to 1.
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel =
+            |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+        Color::from_rgb8(
+            channel(self.red, other.red),
+            channel(self.green, other.green),
+            channel(self.blue, other.blue),
+        )
+    }
+
+    /// Returns this color as a hex color like `#ff8800`.
+    pub fn to_hex(self) -> String {
+        self.to_string()
+    }
+
+    /// Returns whether black text is easier to read than white text on this color.
+    pub(crate) fn is_light(self) -> bool {
+        299 * self.red as u32 + 587 * self.green as u32 + 114 * self.blue as u32 > 128_000
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Color, Error> {
+        Color::from_hex(s)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_round_trip() {
+        for percent in 0..=100 {
+            assert_eq!(
+                Color::new(percent, 0, 100 - percent).to_percent(),
+                (percent, 0, 100 - percent)
+            );
+        }
+    }
+
+    #[test]
+    fn percent_range() {
+        assert_eq!(Color::new(-5, 50, 150), Color::new(0, 50, 100));
+        assert_eq!(Color::from_percent(0, 100, 50), Ok(Color::new(0, 100, 50)));
+        assert_eq!(Color::from_percent(0, 101, 50), Err(Error::InvalidArgument));
+        assert_eq!(Color::from_percent(-1, 0, 0), Err(Error::InvalidArgument));
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(
+            Color::from_hex("#ff8800"),
+            Ok(Color::from_rgb8(255, 136, 0))
+        );
+        assert_eq!(Color::from_hex("FF8800"), Ok(Color::from_rgb8(255, 136, 0)));
+        assert_eq!(Color::from_hex("#f80"), Ok(Color::from_rgb8(255, 136, 0)));
+        assert_eq!(Color::from_hex("#ff880"), Err(Error::InvalidArgument));
+        assert_eq!(Color::from_hex("#gg8800"), Err(Error::InvalidArgument));
+        assert_eq!(Color::from_hex("#+f8800"), Err(Error::InvalidArgument));
+        assert_eq!(Color::from_rgb8(255, 136, 0).to_hex(), "#ff8800");
+    }
+
+    #[test]
+    fn hsv_and_hsl() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::BLUE);
+        assert_eq!(
+            Color::from_hsv(30.0, 1.0, 1.0),
+            Color::from_rgb8(255, 128, 0)
+        );
+        assert_eq!(
+            Color::from_hsv(200.0, 0.0, 0.5),
+            Color::from_rgb8(128, 128, 128)
+        );
+
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5), Color::BLUE);
+        assert_eq!(
+            Color::from_hsl(60.0, 1.0, 0.25),
+            Color::from_rgb8(128, 128, 0)
+        );
+        assert_eq!(Color::from_hsl(0.0, 1.0, 1.0), Color::WHITE);
+    }
+
+    #[test]
+    fn lerp() {
+        assert_eq!(Color::RED.lerp(Color::BLUE, 0.0), Color::RED);
+        assert_eq!(Color::RED.lerp(Color::BLUE, 1.0), Color::BLUE);
+        assert_eq!(
+            Color::BLACK.lerp(Color::WHITE, 0.5),
+            Color::from_rgb8(128, 128, 128)
+        );
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 2.0), Color::WHITE);
+    }
+}
@@ -0,0 +1,601 @@
+//! Typed config options, which can be loaded together with `#[derive(LightsyncConfig)]`
+//! and watched for changes with `ConfigWatcher`.
+
+use super::{Color, Error, LightingBackend, Logitech, Sdk};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// A type which can be the value of a config option.
+pub trait ConfigOption: Sized {
+    /// Gets the value chosen for the config option at `path` from `backend`, or `default` if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with.
+    fn get<B: LightingBackend>(backend: &B, path: &str, default: Self) -> Result<Self, Error>;
+}
+
+impl ConfigOption for Color {
+    fn get<B: LightingBackend>(backend: &B, path: &str, default: Color) -> Result<Color, Error> {
+        backend.get_config_option_color(path, default)
+    }
+}
+
+impl ConfigOption for bool {
+    fn get<B: LightingBackend>(backend: &B, path: &str, default: bool) -> Result<bool, Error> {
+        backend.get_config_option_bool(path, default)
+    }
+}
+
+impl ConfigOption for f64 {
+    fn get<B: LightingBackend>(backend: &B, path: &str, default: f64) -> Result<f64, Error> {
+        backend.get_config_option_number(path, default)
+    }
+}
+
+impl ConfigOption for String {
+    fn get<B: LightingBackend>(backend: &B, path: &str, default: String) -> Result<String, Error> {
+        backend.get_config_option_string(path, &default)
+    }
+}
+
+/// A rectangle, as `(x, y, width, height)`.
+impl ConfigOption for (i32, i32, i32, i32) {
+    fn get<B: LightingBackend>(
+        backend: &B,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        backend.get_config_option_rect(path, default)
+    }
+}
+
+/// A struct of config options which are loaded together.
+///
+/// This is usually implemented with `#[derive(LightsyncConfig)]`, using the `derive` feature.
+/// Each field of the struct is an option, whose path defaults to the name of the field,
+/// and can be configured with an `#[option(...)]` attribute:
+/// - `path = "Section/Name"` sets the path of the option.
+/// - `label = "..."` sets the label shown for the option instead of its path.
+/// - `min = ..., max = ...` makes an `i32` field a range option between `min` and `max`.
+/// - `skip` leaves the field as its default, instead of making it an option.
+///
+/// Every other field has to implement `ConfigOption`.
+#[cfg_attr(
+    feature = "derive",
+    doc = r##"
+# Example
+```no_run
+use lightsync::{Color, LightsyncConfig, Sdk};
+
+#[derive(LightsyncConfig)]
+struct Config {
+    #[option(path = "Colors/Terrorist", label = "T color")]
+    terrorist: Color,
+    #[option(path = "Colors/CounterTerrorist", label = "CT color")]
+    counter_terrorist: Color,
+    #[option(path = "Flash/Speed", min = 1, max = 10)]
+    flash_speed: i32,
+    #[option(label = "Flash when hit")]
+    flash: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            terrorist: Color::RED,
+            counter_terrorist: Color::BLUE,
+            flash_speed: 5,
+            flash: true,
+        }
+    }
+}
+
+let sdk = Sdk::init_with_name("Counter-Strike")?;
+let config = Config::load(&sdk)?;
+sdk.set_lighting(config.terrorist)?;
+# Ok::<(), lightsync::Error>(())
+```
+"##
+)]
+pub trait LightsyncConfig: Sized {
+    /// Sets the labels of the options, and then gets the value chosen for each,
+    /// or the value of its field in `defaults` if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with, stopping at the first option which fails.
+    fn load_with_defaults<B: LightingBackend>(sdk: &Sdk<B>, defaults: Self) -> Result<Self, Error>;
+
+    /// Sets the labels of the options, and then gets the value chosen for each, or its default if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with, stopping at the first option which fails.
+    fn load<B: LightingBackend>(sdk: &Sdk<B>) -> Result<Self, Error>
+    where
+        Self: Default,
+    {
+        Self::load_with_defaults(sdk, Self::default())
+    }
+}
+
+/// Gets a value of any `ConfigOption` type chosen by the user, or `default` if not chosen.
+///
+/// Path is the identifier for the option,
+/// which can be either just a name (e.g. "Terrorist")
+/// or a path in a two level tree (e.g. "Colors/Terrorist.")
+///
+/// If the path is two levels deep, it will be placed inside a section
+/// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
+///
+/// You can also specify a label, if you want it to be different to the path.
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
+pub fn get_option<T: ConfigOption>(
+    path: &str,
+    default: T,
+    label: Option<&str>,
+) -> Result<T, Error> {
+    labelled(&Logitech, path, label, |backend| {
+        T::get(backend, path, default)
+    })
+}
+
+/// Sets the label of the option at `path` on `backend`, if there is one, and then gets the option with `get`.
+pub(crate) fn labelled<B: LightingBackend, T>(
+    backend: &B,
+    path: &str,
+    label: Option<&str>,
+    get: impl FnOnce(&B) -> Result<T, Error>,
+) -> Result<T, Error> {
+    if let Some(label) = label {
+        backend.set_config_option_label(path, label)?;
+    }
+    get(backend)
+}
+
+/// Reads the option it stands for again, calling its callback and returning `true` if the value has changed.
+type Watch<B> = Box<dyn FnMut(&Sdk<B>) -> Result<bool, Error> + Send>;
+
+/// Re-reads config options every so often, and calls callbacks when they change.
+///
+/// The options panel of Logitech Gaming Software or G HUB can be used while an integration is running, so
+/// this lets it react to the user's changes without restarting. Options are read again by `poll()`,
+/// or every `interval` on a background thread after `start()`.
+///
+/// Callbacks are called with the watcher locked, so they mustn't call the watcher themselves.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Color, ConfigWatcher, Sdk};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let sdk = Arc::new(Sdk::init_with_name("foo")?);
+/// let mut watcher = ConfigWatcher::new(Arc::clone(&sdk));
+///
+/// let background = {
+///     let sdk = Arc::clone(&sdk);
+///     watcher.watch("Colors/Background", Color::BLUE, move |&color| {
+///         let _ = sdk.set_lighting(color);
+///     })?
+/// };
+/// sdk.set_lighting(background)?;
+///
+/// let (_, speed) = watcher.subscribe("Speed", 1.0)?;
+/// watcher.start(Duration::from_secs(1));
+/// for speed in speed {
+///     println!("The speed is now {}", speed);
+/// }
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+pub struct ConfigWatcher<B: LightingBackend> {
+    shared: Arc<Shared<B>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared<B: LightingBackend> {
+    sdk: Arc<Sdk<B>>,
+    state: Mutex<WatchState<B>>,
+    /// Notified when the watcher is stopped, to wake up the background thread.
+    stopped: Condvar,
+}
+
+struct WatchState<B: LightingBackend> {
+    watches: Vec<Watch<B>>,
+    running: bool,
+    last_error: Option<Error>,
+}
+
+impl<B: LightingBackend> Shared<B> {
+    fn state(&self) -> MutexGuard<'_, WatchState<B>> {
+        // A callback panicking doesn't leave the watches inconsistent.
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<B: LightingBackend> WatchState<B> {
+    fn poll(&mut self, sdk: &Sdk<B>) -> Result<usize, Error> {
+        let mut changed = 0;
+        for watch in &mut self.watches {
+            if watch(sdk)? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+impl<B: LightingBackend> ConfigWatcher<B> {
+    /// Creates a watcher for options read through `sdk`, which isn't watching any yet.
+    pub fn new(sdk: Arc<Sdk<B>>) -> ConfigWatcher<B> {
+        ConfigWatcher {
+            shared: Arc::new(Shared {
+                sdk,
+                state: Mutex::new(WatchState {
+                    watches: Vec::new(),
+                    running: false,
+                    last_error: None,
+                }),
+                stopped: Condvar::new(),
+            }),
+            thread: None,
+        }
+    }
+
+    /// Adds a watch which reads a value with `read`, calling `on_change` whenever it's different from the last one.
+    ///
+    /// Returns the value it has now.
+    fn add<T>(
+        &self,
+        mut read: impl FnMut(&Sdk<B>) -> Result<T, Error> + Send + 'static,
+        mut on_change: impl FnMut(&T) + Send + 'static,
+    ) -> Result<T, Error>
+    where
+        T: Clone + PartialEq + Send + 'static,
+    {
+        let mut last = read(&self.shared.sdk)?;
+        let value = last.clone();
+        self.shared.state().watches.push(Box::new(move |sdk| {
+            let value = read(sdk)?;
+            if value == last {
+                return Ok(false);
+            }
+            on_change(&value);
+            last = value;
+            Ok(true)
+        }));
+        Ok(value)
+    }
+
+    /// Watches the option at `path`, calling `on_change` with its new value whenever it changes.
+    ///
+    /// Returns the value chosen for the option now, or `default` if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to read the option with.
+    pub fn watch<T>(
+        &self,
+        path: &str,
+        default: T,
+        on_change: impl FnMut(&T) + Send + 'static,
+    ) -> Result<T, Error>
+    where
+        T: ConfigOption + Clone + PartialEq + Send + 'static,
+    {
+        let path = path.to_string();
+        self.add(
+            move |sdk| T::get(sdk.backend(), &path, default.clone()),
+            on_change,
+        )
+    }
+
+    /// Watches the range option between `min` and `max` at `path`, calling `on_change` with its new value whenever
+    /// it changes.
+    ///
+    /// Returns the value chosen for the option now, or `default` if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to read the option with.
+    pub fn watch_range(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+        on_change: impl FnMut(&i32) + Send + 'static,
+    ) -> Result<i32, Error> {
+        let path = path.to_string();
+        self.add(
+            move |sdk| {
+                sdk.backend()
+                    .get_config_option_range(&path, default, min, max)
+            },
+            on_change,
+        )
+    }
+
+    /// Watches a whole struct of options, calling `on_change` with all of them whenever any of them change.
+    ///
+    /// Returns the values chosen for the options now, or the values in `defaults` if not chosen.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to read the options with.
+    pub fn watch_config<C>(
+        &self,
+        defaults: C,
+        on_change: impl FnMut(&C) + Send + 'static,
+    ) -> Result<C, Error>
+    where
+        C: LightsyncConfig + Clone + PartialEq + Send + 'static,
+    {
+        self.add(
+            move |sdk| C::load_with_defaults(sdk, defaults.clone()),
+            on_change,
+        )
+    }
+
+    /// Watches the option at `path`, sending its new value on the returned channel whenever it changes.
+    ///
+    /// Returns the value chosen for the option now, or `default` if not chosen, along with the channel.
+    /// Values stop being sent once the receiver is dropped.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to read the option with.
+    pub fn subscribe<T>(&self, path: &str, default: T) -> Result<(T, Receiver<T>), Error>
+    where
+        T: ConfigOption + Clone + PartialEq + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let value = self.watch(path, default, move |value| {
+            let _ = sender.send(value.clone());
+        })?;
+        Ok((value, receiver))
+    }
+
+    /// Reads every watched option again, calling the callbacks of those which have changed.
+    ///
+    /// Returns the number of options which changed.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to read an option with, without reading the ones after it.
+    pub fn poll(&self) -> Result<usize, Error> {
+        self.shared.state().poll(&self.shared.sdk)
+    }
+
+    /// Stops reading options on the background thread.
+    pub fn stop(&mut self) {
+        self.shared.state().running = false;
+        self.shared.stopped.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Returns the last error from reading options on the background thread, if there's been one.
+    pub fn last_error(&self) -> Option<Error> {
+        self.shared.state().last_error
+    }
+}
+
+impl<B: LightingBackend + Send + Sync + 'static> ConfigWatcher<B> {
+    /// Starts reading the watched options every `interval` on a background thread, until `stop()` is called
+    /// or the watcher is dropped.
+    ///
+    /// Errors are recorded in `last_error()`, and the options are read again on the next interval.
+    /// Intervals shorter than a millisecond are rounded up to one, so the SDK isn't polled in a busy loop.
+    pub fn start(&mut self, interval: Duration) {
+        self.stop();
+        self.shared.state().running = true;
+
+        let interval = interval.max(Duration::from_millis(1));
+        let shared = Arc::clone(&self.shared);
+        self.thread = Some(thread::spawn(move || {
+            let mut next_poll = Instant::now() + interval;
+            loop {
+                let mut state = shared.state();
+                let now = Instant::now();
+                if now < next_poll {
+                    state = shared
+                        .stopped
+                        .wait_timeout(state, next_poll - now)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0;
+                }
+                if !state.running {
+                    break;
+                }
+                if Instant::now() >= next_poll {
+                    if let Err(err) = state.poll(&shared.sdk) {
+                        state.last_error = Some(err);
+                    }
+                    next_poll = Instant::now() + interval;
+                }
+                // Let `stop` and the other methods in between polls.
+                drop(state);
+                thread::yield_now();
+            }
+        }));
+    }
+}
+
+impl<B: LightingBackend> Drop for ConfigWatcher<B> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl<B: LightingBackend> fmt::Debug for ConfigWatcher<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.shared.state();
+        f.debug_struct("ConfigWatcher")
+            .field("watches", &state.watches.len())
+            .field("running", &state.running)
+            .field("last_error", &state.last_error)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Call, MockBackend};
+
+    #[cfg(feature = "derive")]
+    #[derive(Debug, PartialEq, crate::LightsyncConfig)]
+    struct Config {
+        #[option(path = "Colors/Terrorist", label = "T color")]
+        terrorist: Color,
+        #[option(path = "Speed", min = -5, max = 5)]
+        speed: i32,
+        enabled: bool,
+        #[option(skip)]
+        count: usize,
+    }
+
+    #[cfg(feature = "derive")]
+    impl Default for Config {
+        fn default() -> Config {
+            Config {
+                terrorist: Color::RED,
+                speed: 2,
+                enabled: true,
+                count: 3,
+            }
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        assert_eq!(Config::load(&sdk), Ok(Config::default()));
+        assert_eq!(
+            mock.calls()[1..],
+            [
+                Call::SetConfigOptionLabel("Colors/Terrorist".to_string(), "T color".to_string()),
+                Call::GetConfigOptionColor("Colors/Terrorist".to_string()),
+                Call::GetConfigOptionRange("Speed".to_string()),
+                Call::GetConfigOptionBool("enabled".to_string()),
+            ]
+        );
+
+        mock.disconnect_after(0);
+        assert_eq!(Config::load(&sdk), Err(Error::ConnectionLost));
+    }
+
+    #[test]
+    fn config_option() {
+        let mock = MockBackend::new();
+        let _sdk = Sdk::with_backend(mock.clone()).unwrap();
+        assert_eq!(f64::get(&mock, "Number", 1.5), Ok(1.5));
+        assert_eq!(
+            String::get(&mock, "Name", "Steve".to_string()),
+            Ok("Steve".to_string())
+        );
+        assert_eq!(
+            <(i32, i32, i32, i32)>::get(&mock, "Rect", (1, 2, 3, 4)),
+            Ok((1, 2, 3, 4))
+        );
+        assert_eq!(
+            mock.calls()[1..],
+            [
+                Call::GetConfigOptionNumber("Number".to_string()),
+                Call::GetConfigOptionString("Name".to_string()),
+                Call::GetConfigOptionRect("Rect".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn watcher_errors() {
+        let mock = MockBackend::new();
+        let sdk = Arc::new(Sdk::with_backend(mock.clone()).unwrap());
+        let mut watcher = ConfigWatcher::new(sdk);
+        assert_eq!(watcher.watch("Flash", true, |_| panic!()), Ok(true));
+        assert_eq!(watcher.poll(), Ok(0));
+
+        mock.disconnect_after(0);
+        assert_eq!(watcher.poll(), Err(Error::ConnectionLost));
+        assert_eq!(
+            watcher.watch_range("Speed", 1, 0, 5, |_| {}),
+            Err(Error::ConnectionLost)
+        );
+
+        watcher.start(Duration::from_millis(1));
+        let start = Instant::now();
+        while watcher.last_error().is_none() && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        watcher.stop();
+        assert_eq!(watcher.last_error(), Some(Error::ConnectionLost));
+    }
+
+    #[test]
+    fn watcher_zero_interval() {
+        let sdk = Arc::new(Sdk::with_backend(MockBackend::new()).unwrap());
+        let mut watcher = ConfigWatcher::new(sdk);
+        watcher.watch("Flash", true, |_| {}).unwrap();
+        watcher.start(Duration::ZERO);
+        thread::sleep(Duration::from_millis(20));
+        // The background thread has to let go of the watcher for these to return.
+        assert_eq!(watcher.poll(), Ok(0));
+        watcher.stop();
+        assert_eq!(watcher.last_error(), None);
+    }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn watcher() {
+        use crate::test_util;
+        use crate::ConfigFile;
+        use std::fs;
+
+        let dir = test_util::temp_dir("watcher");
+        let path = dir.join("options.toml");
+        fs::write(&path, "Speed = 2\n[Colors]\nBackground = \"#ff0000\"\n").unwrap();
+        let write = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            test_util::touch_later(&path);
+        };
+
+        let config = ConfigFile::open(MockBackend::new(), &path).unwrap();
+        let mut watcher = ConfigWatcher::new(Arc::new(Sdk::with_backend(config).unwrap()));
+        let colors = Arc::new(Mutex::new(Vec::new()));
+        let background = {
+            let colors = Arc::clone(&colors);
+            watcher
+                .watch("Colors/Background", Color::BLUE, move |&color| {
+                    colors.lock().unwrap().push(color)
+                })
+                .unwrap()
+        };
+        assert_eq!(background, Color::RED);
+        let (speed, speeds) = watcher.subscribe("Speed", 1.0).unwrap();
+        assert_eq!(speed, 2.0);
+        assert_eq!(watcher.poll(), Ok(0));
+
+        write("Speed = 3\n[Colors]\nBackground = \"#00ff00\"\n");
+        assert_eq!(watcher.poll(), Ok(2));
+        assert_eq!(watcher.poll(), Ok(0));
+        assert_eq!(*colors.lock().unwrap(), [Color::GREEN]);
+        assert_eq!(speeds.try_iter().collect::<Vec<_>>(), [3.0]);
+
+        watcher.start(Duration::from_millis(5));
+        write("Speed = 4\n[Colors]\nBackground = \"#00ff00\"\n");
+        assert_eq!(speeds.recv_timeout(Duration::from_secs(5)), Ok(4.0));
+        watcher.stop();
+        assert_eq!(*colors.lock().unwrap(), [Color::GREEN]);
+        assert_eq!(watcher.last_error(), None);
+
+        drop(watcher);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
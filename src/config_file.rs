@@ -0,0 +1,806 @@
+//! A backend which keeps config options in a TOML file, for when Logitech Gaming Software isn't there to show them.
+
+use super::{Bitmap, Color, DeviceType, Error, Key, LightingBackend, Logitech};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+use toml::{Table, Value};
+
+/// A `LightingBackend` which passes lighting calls through to another backend,
+/// but reads and writes config options in a TOML file instead.
+///
+/// Options use the same paths as the SDK: an option at `Section/Name` is the key `Name` in the table `[Section]`,
+/// and an option at `Name` is a key at the top of the file. Colors are hex strings like `"#ff8800"`,
+/// rectangles are arrays of `[x, y, width, height]`, and other options are plain TOML values.
+/// Labels are written as comments above their options.
+///
+/// An option missing from the file is added with its default value when it's read, and written to the end of its
+/// section when the backend is shut down, so that users have something to edit. The rest of the file is left as the
+/// user wrote it, comments and all. The file is read again whenever it changes,
+/// so edits made while the integration is running are picked up by the next read of an option.
+/// Values of the wrong type are ignored in favour of the default, and range options are clamped to their range.
+///
+/// Free functions like `get_color_option()` always go to the Logitech SDK, so read options through the `Sdk`
+/// instead, with `Sdk::get_option()` and the other `Sdk` methods for options, or with `LightsyncConfig`.
+/// Code written that way works the same whichever backend the `Sdk` was created with.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Color, ConfigFile, Logitech, Sdk};
+///
+/// let sdk = Sdk::with_backend_and_name(ConfigFile::open(Logitech, "options.toml")?, "foo")?;
+/// let color = sdk.get_option("Colors/Background", Color::BLUE, Some("Background color"))?;
+/// sdk.set_lighting(color)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct ConfigFile<B: LightingBackend = Logitech> {
+    backend: B,
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    options: Table,
+    /// The labels set for options and sections, by path.
+    labels: BTreeMap<String, String>,
+    /// The modification time of the file when it was last read or written.
+    modified: Option<SystemTime>,
+    /// Whether `options` or `labels` have changed since the file was last written.
+    dirty: bool,
+}
+
+/// Reads the file at `path`, or returns `None` if it doesn't exist.
+fn read(path: &Path) -> io::Result<Option<(Table, Option<SystemTime>)>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let options = text
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    Ok(Some((options, modified)))
+}
+
+/// Splits `path` into its section, if it has one, and name.
+fn split(path: &str) -> (Option<&str>, &str) {
+    match path.find('/') {
+        Some(index) => (Some(&path[..index]), &path[index + 1..]),
+        None => (None, path),
+    }
+}
+
+/// Returns `Error::NulInName` if `s` couldn't be passed to the SDK, so that this backend fails in the same cases.
+fn check(s: &str) -> Result<(), Error> {
+    if s.contains('\0') {
+        Err(Error::NulInName)
+    } else {
+        Ok(())
+    }
+}
+
+/// Formats `key` as a TOML key, quoting it if it isn't a bare key.
+fn key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// Returns the index of the line after the last option in the lines of a file from `start`, up to the next section.
+///
+/// Blank lines and comments before the next section are left after the index, since they usually belong to it.
+fn end_of_options(lines: &[String], start: usize) -> usize {
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |index| start + index);
+    (start..end)
+        .rev()
+        .find(|&index| {
+            let line = lines[index].trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map_or(start, |index| index + 1)
+}
+
+fn comment(out: &mut String, label: Option<&String>) {
+    if let Some(label) = label {
+        for line in label.lines() {
+            let _ = writeln!(out, "# {}", line);
+        }
+    }
+}
+
+impl<B: LightingBackend> ConfigFile<B> {
+    /// Creates a `ConfigFile` which keeps options in the file at `path`, and passes lighting calls to `backend`.
+    ///
+    /// The file doesn't have to exist yet.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but can't be read, or an error of kind `InvalidData` if it isn't valid TOML.
+    pub fn open(backend: B, path: impl Into<PathBuf>) -> io::Result<ConfigFile<B>> {
+        let path = path.into();
+        let mut state = State::default();
+        if let Some((options, modified)) = read(&path)? {
+            state.options = options;
+            state.modified = modified;
+        }
+        Ok(ConfigFile {
+            backend,
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the backend lighting calls are passed to.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Writes the options read so far, with their labels, to the file.
+    ///
+    /// If the file already exists, only the options missing from it are added, so any changes the user has made
+    /// to it since it was last read are kept. This happens automatically when the backend is shut down,
+    /// if any options were added.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or written, or an error of kind `InvalidData` if it exists
+    /// but isn't valid TOML.
+    pub fn save(&self) -> io::Result<()> {
+        let mut state = self.state();
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => {
+                let file = text
+                    .parse()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                state.add_missing(file, &text)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => state.to_toml(),
+            Err(err) => return Err(err),
+        };
+        fs::write(&self.path, text)?;
+        state.modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        state.dirty = false;
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Gets the value of the option at `path`, adding `default` to the file if there isn't one.
+    fn option<T>(
+        &self,
+        path: &str,
+        default: T,
+        to_value: impl FnOnce(&T) -> Value,
+        from_value: impl FnOnce(&Value) -> Option<T>,
+    ) -> Result<T, Error> {
+        check(path)?;
+        let mut state = self.state();
+        let state = &mut *state;
+        state.reload(&self.path);
+
+        let (section, name) = split(path);
+        let table = match section {
+            Some(section) => match state
+                .options
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()))
+            {
+                Value::Table(table) => table,
+                // The section's been given a value, so the option can't be added to it.
+                _ => return Ok(default),
+            },
+            None => &mut state.options,
+        };
+        match table.get(name) {
+            Some(value) => Ok(from_value(value).unwrap_or(default)),
+            None => {
+                table.insert(name.to_string(), to_value(&default));
+                state.dirty = true;
+                Ok(default)
+            }
+        }
+    }
+}
+
+impl State {
+    /// Reads the file again if it's changed since it was last read or written.
+    ///
+    /// If it can't be read, the options from the last time it was read are kept.
+    fn reload(&mut self, path: &Path) {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        if let Ok(Some((options, modified))) = read(path) {
+            self.options = options;
+            self.modified = modified;
+        }
+    }
+
+    /// Adds the options missing from `text`, the contents of the file which parsed as `file`, to the end of their
+    /// sections, and takes the result as the options.
+    ///
+    /// Returns the new contents of the file, which are only formatted from scratch if the options can't be added
+    /// to `text` without changing what it means.
+    fn add_missing(&mut self, mut file: Table, text: &str) -> String {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let mut top = String::new();
+        let mut sections = String::new();
+        for (name, value) in &self.options {
+            match (value, file.get_mut(name)) {
+                (Value::Table(table), Some(Value::Table(existing))) => {
+                    let mut added = String::new();
+                    for (option, value) in table {
+                        if !existing.contains_key(option) {
+                            comment(&mut added, self.labels.get(&format!("{}/{}", name, option)));
+                            let _ = writeln!(added, "{} = {}", key(option), value);
+                            existing.insert(option.clone(), value.clone());
+                        }
+                    }
+                    let header = format!("[{}]", key(name));
+                    let start = lines.iter().position(|line| line.trim() == header);
+                    if let (false, Some(start)) = (added.is_empty(), start) {
+                        let end = end_of_options(&lines, start + 1);
+                        lines.splice(end..end, added.lines().map(str::to_string));
+                    }
+                }
+                // The user's value is kept, even if it's of the wrong type.
+                (_, Some(_)) => {}
+                (Value::Table(_), None) => {
+                    if !sections.is_empty() {
+                        sections.push('\n');
+                    }
+                    comment(&mut sections, self.labels.get(name));
+                    let _ = writeln!(sections, "[{}]", key(name));
+                    if let Value::Table(table) = value {
+                        for (option, value) in table {
+                            comment(
+                                &mut sections,
+                                self.labels.get(&format!("{}/{}", name, option)),
+                            );
+                            let _ = writeln!(sections, "{} = {}", key(option), value);
+                        }
+                    }
+                    file.insert(name.clone(), value.clone());
+                }
+                (value, None) => {
+                    comment(&mut top, self.labels.get(name));
+                    let _ = writeln!(top, "{} = {}", key(name), value);
+                    file.insert(name.clone(), value.clone());
+                }
+            }
+        }
+
+        // Options outside of a section have to come before the first one.
+        let end = end_of_options(&lines, 0);
+        lines.splice(end..end, top.lines().map(str::to_string));
+        let mut out = String::new();
+        for line in &lines {
+            let _ = writeln!(out, "{}", line);
+        }
+        if !sections.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&sections);
+        }
+
+        self.options = file;
+        // Lines that only look like headers, like those in multi-line strings, could put options in the wrong place.
+        if out.parse::<Table>().as_ref() != Ok(&self.options) {
+            out = self.to_toml();
+        }
+        out
+    }
+
+    /// Formats the options as TOML, with their labels as comments.
+    fn to_toml(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.options {
+            if !value.is_table() {
+                comment(&mut out, self.labels.get(name));
+                let _ = writeln!(out, "{} = {}", key(name), value);
+            }
+        }
+        for (section, value) in &self.options {
+            if let Value::Table(table) = value {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                comment(&mut out, self.labels.get(section));
+                let _ = writeln!(out, "[{}]", key(section));
+                for (name, value) in table {
+                    comment(&mut out, self.labels.get(&format!("{}/{}", section, name)));
+                    let _ = writeln!(out, "{} = {}", key(name), value);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<B: LightingBackend> LightingBackend for ConfigFile<B> {
+    fn init(&self) -> Result<(), Error> {
+        self.backend.init()
+    }
+
+    fn init_with_name(&self, name: &str) -> Result<(), Error> {
+        self.backend.init_with_name(name)
+    }
+
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error> {
+        self.backend.get_sdk_version()
+    }
+
+    fn set_target_device(&self, target_device: i32) -> Result<(), Error> {
+        self.backend.set_target_device(target_device)
+    }
+
+    fn save_current_lighting(&self) -> Result<(), Error> {
+        self.backend.save_current_lighting()
+    }
+
+    fn set_lighting(&self, color: Color) -> Result<(), Error> {
+        self.backend.set_lighting(color)
+    }
+
+    fn set_lighting_for_target_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error> {
+        self.backend
+            .set_lighting_for_target_zone(device_type, zone, color)
+    }
+
+    fn restore_lighting(&self) -> Result<(), Error> {
+        self.backend.restore_lighting()
+    }
+
+    fn flash_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.backend.flash_lighting(color, duration, interval)
+    }
+
+    fn pulse_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.backend.pulse_lighting(color, duration, interval)
+    }
+
+    fn stop_effects(&self) -> Result<(), Error> {
+        self.backend.stop_effects()
+    }
+
+    fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error> {
+        self.backend.set_lighting_from_bitmap(bitmap)
+    }
+
+    fn exclude_keys_from_bitmap(&self, keys: &[Key]) -> Result<(), Error> {
+        self.backend.exclude_keys_from_bitmap(keys)
+    }
+
+    fn set_lighting_for_key_with_scan_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.backend
+            .set_lighting_for_key_with_scan_code(code, color)
+    }
+
+    fn set_lighting_for_key_with_hid_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.backend.set_lighting_for_key_with_hid_code(code, color)
+    }
+
+    fn set_lighting_for_key_with_quartz_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        self.backend
+            .set_lighting_for_key_with_quartz_code(code, color)
+    }
+
+    fn set_lighting_for_key_with_key_name(&self, key: Key, color: Color) -> Result<(), Error> {
+        self.backend.set_lighting_for_key_with_key_name(key, color)
+    }
+
+    fn save_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.save_lighting_for_key(key)
+    }
+
+    fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.restore_lighting_for_key(key)
+    }
+
+    fn flash_single_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: i32,
+        interval: i32,
+    ) -> Result<(), Error> {
+        self.backend
+            .flash_single_key(key, color, duration, interval)
+    }
+
+    fn pulse_single_key(
+        &self,
+        key: Key,
+        start: Color,
+        end: Color,
+        duration: i32,
+        infinite: bool,
+    ) -> Result<(), Error> {
+        self.backend
+            .pulse_single_key(key, start, end, duration, infinite)
+    }
+
+    fn stop_effects_on_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.stop_effects_on_key(key)
+    }
+
+    fn shutdown(&self) {
+        // There's nowhere to report an error to, and the options will be added again next time anyway.
+        if self.state().dirty {
+            let _ = self.save();
+        }
+        self.backend.shutdown();
+    }
+
+    fn set_config_option_label(&self, path: &str, label: &str) -> Result<(), Error> {
+        check(path)?;
+        check(label)?;
+        let mut state = self.state();
+        if state.labels.get(path).map(String::as_str) != Some(label) {
+            state.labels.insert(path.to_string(), label.to_string());
+            state.dirty = true;
+        }
+        Ok(())
+    }
+
+    fn get_config_option_number(&self, path: &str, default: f64) -> Result<f64, Error> {
+        self.option(
+            path,
+            default,
+            |&default| Value::Float(default),
+            |value| match *value {
+                Value::Float(value) => Some(value),
+                Value::Integer(value) => Some(value as f64),
+                _ => None,
+            },
+        )
+    }
+
+    fn get_config_option_bool(&self, path: &str, default: bool) -> Result<bool, Error> {
+        self.option(
+            path,
+            default,
+            |&default| Value::Boolean(default),
+            Value::as_bool,
+        )
+    }
+
+    fn get_config_option_color(&self, path: &str, default: Color) -> Result<Color, Error> {
+        self.option(
+            path,
+            default,
+            |default| Value::String(default.to_hex()),
+            |value| value.as_str().and_then(|hex| hex.parse().ok()),
+        )
+    }
+
+    fn get_config_option_range(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+    ) -> Result<i32, Error> {
+        self.option(
+            path,
+            default,
+            |&default| Value::Integer(default.into()),
+            |value| {
+                let value = value.as_integer()?;
+                Some(value.max(min.into()).min(max.into()) as i32)
+            },
+        )
+    }
+
+    fn get_config_option_rect(
+        &self,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        self.option(
+            path,
+            default,
+            |&(x, y, width, height)| {
+                Value::Array(
+                    [x, y, width, height]
+                        .iter()
+                        .map(|&n| Value::Integer(n.into()))
+                        .collect(),
+                )
+            },
+            |value| match value.as_array()?.as_slice() {
+                [x, y, width, height] => Some((
+                    x.as_integer()? as i32,
+                    y.as_integer()? as i32,
+                    width.as_integer()? as i32,
+                    height.as_integer()? as i32,
+                )),
+                _ => None,
+            },
+        )
+    }
+
+    fn get_config_option_string(&self, path: &str, default: &str) -> Result<String, Error> {
+        check(default)?;
+        self.option(
+            path,
+            default.to_string(),
+            |default| Value::String(default.clone()),
+            |value| value.as_str().map(str::to_string),
+        )
+    }
+
+    fn get_config_option_key_input(&self, path: &str, default: &str) -> Result<String, Error> {
+        self.get_config_option_string(path, default)
+    }
+
+    fn get_config_option_select(
+        &self,
+        path: &str,
+        default: &str,
+        values: &[&str],
+    ) -> Result<String, Error> {
+        check(default)?;
+        for value in values {
+            check(value)?;
+        }
+        self.option(
+            path,
+            default.to_string(),
+            |default| Value::String(default.clone()),
+            |value| {
+                value
+                    .as_str()
+                    .filter(|value| values.contains(value))
+                    .map(str::to_string)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Call, MockBackend};
+    use crate::test_util;
+    use crate::{ConfigOption, Sdk};
+
+    fn temp_path(name: &str) -> PathBuf {
+        test_util::temp_dir("config").join(name)
+    }
+
+    #[test]
+    fn writes_defaults() {
+        let path = temp_path("defaults.toml");
+        let _ = fs::remove_file(&path);
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(ConfigFile::open(mock.clone(), &path).unwrap()).unwrap();
+        let backend = sdk.backend();
+
+        backend
+            .set_config_option_label("Colors/Terrorist", "T color")
+            .unwrap();
+        assert_eq!(
+            backend.get_config_option_color("Colors/Terrorist", Color::RED),
+            Ok(Color::RED)
+        );
+        assert_eq!(
+            backend.get_config_option_range("Colors/Brightness", 50, 0, 100),
+            Ok(50)
+        );
+        assert_eq!(backend.get_config_option_bool("Flash", true), Ok(true));
+        assert_eq!(
+            backend.get_config_option_rect("Area Shown", (0, 0, 10, 5)),
+            Ok((0, 0, 10, 5))
+        );
+        assert_eq!(
+            backend.get_config_option_select("Difficulty", "Easy", &["Easy", "Hard"]),
+            Ok("Easy".to_string())
+        );
+        assert_eq!(
+            backend.get_config_option_number("Bad\0Path", 1.0),
+            Err(Error::NulInName)
+        );
+        sdk.set_lighting(Color::BLUE).unwrap();
+        assert!(!path.exists());
+
+        drop(sdk);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\"Area Shown\" = [0, 0, 10, 5]\n\
+             Difficulty = \"Easy\"\n\
+             Flash = true\n\
+             \n\
+             [Colors]\n\
+             Brightness = 50\n\
+             # T color\n\
+             Terrorist = \"#ff0000\"\n"
+        );
+        assert_eq!(mock.calls().last(), Some(&Call::Shutdown));
+        assert!(!mock
+            .calls()
+            .iter()
+            .any(|call| matches!(call, Call::GetConfigOptionColor(_))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sdk_options() {
+        let path = temp_path("sdk.toml");
+        fs::write(&path, "Difficulty = \"Hard\"\nSpeed = 20\n").unwrap();
+        let sdk = Sdk::with_backend(ConfigFile::open(MockBackend::new(), &path).unwrap()).unwrap();
+        assert_eq!(
+            sdk.get_option("Colors/Background", Color::BLUE, Some("Background")),
+            Ok(Color::BLUE)
+        );
+        assert_eq!(sdk.get_range_option("Speed", 5, 1, 10, None), Ok(10));
+        assert_eq!(
+            sdk.get_select_option("Difficulty", 0, &["Easy", "Hard"], None),
+            Ok(&"Hard")
+        );
+        assert_eq!(
+            sdk.get_key_input_option("Keys/Toggle", "F5", None),
+            Ok("F5".to_string())
+        );
+        assert_eq!(
+            sdk.get_select_option("Difficulty", 2, &["Easy", "Hard"], None),
+            Err(Error::InvalidArgument)
+        );
+
+        drop(sdk);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Difficulty = \"Hard\"\n\
+             Speed = 20\n\
+             \n\
+             [Colors]\n\
+             # Background\n\
+             Background = \"#0000ff\"\n\
+             \n\
+             [Keys]\n\
+             Toggle = \"F5\"\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keeps_user_edits() {
+        let path = temp_path("edits.toml");
+        fs::write(
+            &path,
+            "# Made by hand\nName = \"Steve\"\n\n[Colors]\nTerrorist = \"#00ff00\"\n",
+        )
+        .unwrap();
+        let sdk = Sdk::with_backend(ConfigFile::open(MockBackend::new(), &path).unwrap()).unwrap();
+        let backend = sdk.backend();
+        assert_eq!(
+            backend.get_config_option_color("Colors/Terrorist", Color::RED),
+            Ok(Color::GREEN)
+        );
+        backend
+            .set_config_option_label("Colors/CounterTerrorist", "CT color")
+            .unwrap();
+        assert_eq!(
+            backend.get_config_option_color("Colors/CounterTerrorist", Color::BLUE),
+            Ok(Color::BLUE)
+        );
+        assert_eq!(backend.get_config_option_bool("Flash", true), Ok(true));
+        assert_eq!(
+            backend.get_config_option_range("Flash Speed/Hit", 5, 1, 10),
+            Ok(5)
+        );
+
+        // The user edits the file while the integration is still running.
+        fs::write(
+            &path,
+            "# Made by hand\nName = \"Bob\"\n\n# Team colors\n[Colors]\nTerrorist = \"#ffff00\" # yellow\n",
+        )
+        .unwrap();
+        drop(sdk);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Made by hand\n\
+             Name = \"Bob\"\n\
+             Flash = true\n\
+             \n\
+             # Team colors\n\
+             [Colors]\n\
+             Terrorist = \"#ffff00\" # yellow\n\
+             # CT color\n\
+             CounterTerrorist = \"#0000ff\"\n\
+             \n\
+             [\"Flash Speed\"]\n\
+             Hit = 5\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_values() {
+        let path = temp_path("values.toml");
+        fs::write(
+            &path,
+            r##"
+                Difficulty = "Nightmare"
+                Name = "Steve"
+
+                [Colors]
+                Terrorist = "#00ff00"
+                Brightness = 150
+                Speed = "fast"
+            "##,
+        )
+        .unwrap();
+        let config = ConfigFile::open(MockBackend::new(), &path).unwrap();
+        assert_eq!(
+            Color::get(&config, "Colors/Terrorist", Color::RED),
+            Ok(Color::GREEN)
+        );
+        assert_eq!(
+            config.get_config_option_range("Colors/Brightness", 50, 0, 100),
+            Ok(100)
+        );
+        assert_eq!(
+            config.get_config_option_number("Colors/Speed", 1.5),
+            Ok(1.5)
+        );
+        assert_eq!(
+            config.get_config_option_select("Difficulty", "Easy", &["Easy", "Hard"]),
+            Ok("Easy".to_string())
+        );
+        assert_eq!(
+            String::get(&config, "Name", String::new()),
+            Ok("Steve".to_string())
+        );
+
+        fs::write(&path, "[Colors]\nTerrorist = \"#0000ff\"\n").unwrap();
+        test_util::touch_later(&path);
+        assert_eq!(
+            Color::get(&config, "Colors/Terrorist", Color::RED),
+            Ok(Color::BLUE)
+        );
+
+        fs::write(&path, "not toml").unwrap();
+        assert_eq!(
+            ConfigFile::open(MockBackend::new(), &path)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
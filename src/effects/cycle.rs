@@ -0,0 +1,156 @@
+use super::{dim, speed, Palette};
+use crate::{Bitmap, Color, Effect};
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// The whole keyboard smoothly going through a palette of colors.
+///
+/// By default, it goes around the rainbow every ten seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectrumCycle {
+    speed: f64,
+    palette: Palette,
+}
+
+impl SpectrumCycle {
+    /// Creates a spectrum cycle with the default settings.
+    pub fn new() -> SpectrumCycle {
+        SpectrumCycle {
+            speed: 1.0,
+            palette: Palette::rainbow(),
+        }
+    }
+
+    /// Multiplies how fast the colors change.
+    pub fn with_speed(mut self, speed: f64) -> SpectrumCycle {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors to go through, instead of a rainbow.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> SpectrumCycle {
+        self.palette = palette.into();
+        self
+    }
+}
+
+impl Default for SpectrumCycle {
+    fn default() -> SpectrumCycle {
+        SpectrumCycle::new()
+    }
+}
+
+impl Effect for SpectrumCycle {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let t = time.as_secs_f64() * speed(self.speed) * 0.1;
+        frame.fill(self.palette.cyclic(t));
+    }
+}
+
+/// The whole keyboard holding each color of a palette in turn, and fading into the next one.
+///
+/// By default, it goes through red, green and blue, spending two seconds on each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorCycle {
+    speed: f64,
+    palette: Palette,
+    fade: f64,
+}
+
+impl ColorCycle {
+    /// Creates a color cycle with the default settings.
+    pub fn new() -> ColorCycle {
+        ColorCycle {
+            speed: 1.0,
+            palette: Palette::new(vec![Color::RED, Color::GREEN, Color::BLUE]),
+            fade: 0.25,
+        }
+    }
+
+    /// Multiplies how fast the colors change.
+    pub fn with_speed(mut self, speed: f64) -> ColorCycle {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors to go through.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> ColorCycle {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the fraction of each color's time spent fading into the next one, from 0 to 1. The default is 0.25.
+    pub fn with_fade(mut self, fade: f64) -> ColorCycle {
+        self.fade = fade.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Default for ColorCycle {
+    fn default() -> ColorCycle {
+        ColorCycle::new()
+    }
+}
+
+impl Effect for ColorCycle {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let colors = self.palette.colors();
+        let position = time.as_secs_f64() * speed(self.speed) * 0.5;
+        let index = position as usize % colors.len();
+        let next = colors[(index + 1) % colors.len()];
+        let hold = 1.0 - self.fade;
+        let color = if position.fract() < hold {
+            colors[index]
+        } else {
+            colors[index].lerp(next, (position.fract() - hold) / self.fade)
+        };
+        frame.fill(color);
+    }
+}
+
+/// The whole keyboard fading in and out, going through a palette of colors one breath at a time.
+///
+/// By default, each breath takes four seconds, and the keyboard breathes in white.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breathing {
+    speed: f64,
+    palette: Palette,
+}
+
+impl Breathing {
+    /// Creates a breathing effect in `color`.
+    pub fn new(color: Color) -> Breathing {
+        Breathing {
+            speed: 1.0,
+            palette: Palette::new(vec![color]),
+        }
+    }
+
+    /// Multiplies how fast the keyboard breathes.
+    pub fn with_speed(mut self, speed: f64) -> Breathing {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors of each breath, in turn.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Breathing {
+        self.palette = palette.into();
+        self
+    }
+}
+
+impl Default for Breathing {
+    fn default() -> Breathing {
+        Breathing::new(Color::WHITE)
+    }
+}
+
+impl Effect for Breathing {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let breaths = time.as_secs_f64() * speed(self.speed) * 0.25;
+        let colors = self.palette.colors();
+        let color = colors[breaths as usize % colors.len()];
+        let brightness = (1.0 - (breaths.fract() * 2.0 * PI).cos()) / 2.0;
+        frame.fill(dim(color, brightness));
+    }
+}
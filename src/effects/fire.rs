@@ -0,0 +1,111 @@
+use super::rng::Rng;
+use super::{speed, Palette, Stepper};
+use crate::{Bitmap, Effect};
+use std::time::Duration;
+
+/// The number of times the flames are updated each second, at a speed of 1.
+const STEPS_PER_SECOND: f64 = 20.0;
+
+/// Flames flickering up from the bottom of the keyboard.
+///
+/// Each cell has a heat from 0 to 1, which rises and cools as it goes up, and is colored by
+/// picking it from a gradient. By default, the gradient goes from black through red and yellow to white.
+#[derive(Debug, Clone)]
+pub struct Fire {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    cooling: f64,
+    rng: Rng,
+    stepper: Stepper,
+    heat: [[f64; Bitmap::WIDTH]; Bitmap::HEIGHT],
+}
+
+impl Fire {
+    /// Creates a fire effect with the default settings.
+    pub fn new() -> Fire {
+        Fire {
+            speed: 1.0,
+            palette: Palette::fire(),
+            seed: 0,
+            cooling: 0.15,
+            rng: Rng::new(0),
+            stepper: Stepper::default(),
+            heat: [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT],
+        }
+    }
+
+    /// Multiplies how fast the flames flicker.
+    pub fn with_speed(mut self, speed: f64) -> Fire {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the gradient heat is picked from, from cold to hot.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Fire {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides how the flames flicker.
+    pub fn with_seed(mut self, seed: u64) -> Fire {
+        self.seed = seed;
+        self.reset();
+        self
+    }
+
+    /// Sets the most heat lost going up each row, from 0 to 1. The default is 0.15,
+    /// and higher values give smaller flames.
+    pub fn with_cooling(mut self, cooling: f64) -> Fire {
+        self.cooling = cooling.clamp(0.0, 1.0);
+        self
+    }
+
+    fn reset(&mut self) {
+        self.rng = Rng::new(self.seed);
+        self.heat = [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT];
+    }
+
+    fn step(&mut self) {
+        // Going from the top down means each row rises from the row below's heat before it's updated.
+        for row in 0..Bitmap::HEIGHT - 1 {
+            for col in 0..Bitmap::WIDTH {
+                let below = &self.heat[row + 1];
+                let left = below[col.saturating_sub(1)];
+                let right = below[(col + 1).min(Bitmap::WIDTH - 1)];
+                let rising = (left + below[col] * 2.0 + right) / 4.0;
+                let cooling = self.cooling * self.rng.next_f64();
+                self.heat[row][col] = (rising - cooling).max(0.0);
+            }
+        }
+        for heat in &mut self.heat[Bitmap::HEIGHT - 1] {
+            *heat = 0.6 + 0.4 * self.rng.next_f64();
+        }
+    }
+}
+
+impl Default for Fire {
+    fn default() -> Fire {
+        Fire::new()
+    }
+}
+
+impl Effect for Fire {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let (restart, steps) = self
+            .stepper
+            .advance(time, STEPS_PER_SECOND * speed(self.speed));
+        if restart {
+            self.reset();
+        }
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (row, heats) in self.heat.iter().enumerate() {
+            for (col, &heat) in heats.iter().enumerate() {
+                frame.set_cell(row, col, self.palette.gradient(heat));
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use super::rng::Rng;
+use super::{speed, Palette, Stepper};
+use crate::{Bitmap, Color, Effect};
+use std::time::Duration;
+
+/// The number of generations each second, at a speed of 1.
+const STEPS_PER_SECOND: f64 = 4.0;
+/// The age at which cells reach the end of the palette.
+const OLD_AGE: u32 = 8;
+
+type Grid = [[Option<u32>; Bitmap::WIDTH]; Bitmap::HEIGHT];
+
+/// Conway's Game of Life, played on the bitmap grid with the edges wrapping around.
+///
+/// Live cells are colored by how many generations they've been alive for, and dead cells are left transparent.
+/// When the cells die out or stop changing, the grid is filled with new random cells.
+#[derive(Debug, Clone)]
+pub struct GameOfLife {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    density: f64,
+    rng: Rng,
+    stepper: Stepper,
+    /// The age of each live cell.
+    cells: Grid,
+    /// The generation before `cells`, to spot cells flipping between two states forever.
+    previous: Grid,
+}
+
+impl GameOfLife {
+    /// Creates a Game of Life with the default settings.
+    pub fn new() -> GameOfLife {
+        let mut life = GameOfLife {
+            speed: 1.0,
+            palette: Palette::new(vec![Color::WHITE, Color::GREEN, Color::BLUE]),
+            seed: 0,
+            density: 0.35,
+            rng: Rng::new(0),
+            stepper: Stepper::default(),
+            cells: [[None; Bitmap::WIDTH]; Bitmap::HEIGHT],
+            previous: [[None; Bitmap::WIDTH]; Bitmap::HEIGHT],
+        };
+        life.reset();
+        life
+    }
+
+    /// Multiplies the number of generations each second.
+    pub fn with_speed(mut self, speed: f64) -> GameOfLife {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the gradient cells are colored by, from newborn to old.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> GameOfLife {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides where cells start.
+    pub fn with_seed(mut self, seed: u64) -> GameOfLife {
+        self.seed = seed;
+        self.reset();
+        self
+    }
+
+    /// Sets the fraction of cells which start alive, from 0 to 1. The default is 0.35.
+    pub fn with_density(mut self, density: f64) -> GameOfLife {
+        self.density = density.clamp(0.0, 1.0);
+        self.reset();
+        self
+    }
+
+    fn reset(&mut self) {
+        self.rng = Rng::new(self.seed);
+        self.populate();
+    }
+
+    /// Fills the grid with new random cells.
+    fn populate(&mut self) {
+        for cell in self.cells.iter_mut().flatten() {
+            *cell = if self.rng.chance(self.density) {
+                Some(0)
+            } else {
+                None
+            };
+        }
+        self.previous = [[None; Bitmap::WIDTH]; Bitmap::HEIGHT];
+    }
+
+    /// Returns the number of live cells next to the cell at `row` and `col`.
+    fn neighbours(&self, row: usize, col: usize) -> usize {
+        let mut neighbours = 0;
+        for dr in [Bitmap::HEIGHT - 1, 0, 1] {
+            for dc in [Bitmap::WIDTH - 1, 0, 1] {
+                let (r, c) = ((row + dr) % Bitmap::HEIGHT, (col + dc) % Bitmap::WIDTH);
+                if (dr, dc) != (0, 0) && self.cells[r][c].is_some() {
+                    neighbours += 1;
+                }
+            }
+        }
+        neighbours
+    }
+
+    fn step(&mut self) {
+        let mut next = [[None; Bitmap::WIDTH]; Bitmap::HEIGHT];
+        for (row, cells) in next.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                *cell = match (self.cells[row][col], self.neighbours(row, col)) {
+                    (Some(age), 2) | (Some(age), 3) => Some(age + 1),
+                    (None, 3) => Some(0),
+                    _ => None,
+                };
+            }
+        }
+
+        let alive = |grid: &Grid| {
+            grid.iter()
+                .flatten()
+                .map(Option::is_some)
+                .collect::<Vec<_>>()
+        };
+        let stuck = alive(&next) == alive(&self.cells) || alive(&next) == alive(&self.previous);
+        if next.iter().flatten().all(Option::is_none) || stuck {
+            self.populate();
+        } else {
+            self.previous = self.cells;
+            self.cells = next;
+        }
+    }
+}
+
+impl Default for GameOfLife {
+    fn default() -> GameOfLife {
+        GameOfLife::new()
+    }
+}
+
+impl Effect for GameOfLife {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let (restart, steps) = self
+            .stepper
+            .advance(time, STEPS_PER_SECOND * speed(self.speed));
+        if restart {
+            self.reset();
+        }
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if let Some(age) = cell {
+                    let age = (*age).min(OLD_AGE) as f64 / OLD_AGE as f64;
+                    frame.set_cell(row, col, self.palette.gradient(age));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blinker() {
+        let mut life = GameOfLife::new().with_density(0.0);
+        for col in 4..7 {
+            life.cells[2][col] = Some(0);
+        }
+        life.step();
+        let alive: Vec<_> = (0..Bitmap::HEIGHT)
+            .flat_map(|row| (0..Bitmap::WIDTH).map(move |col| (row, col)))
+            .filter(|&(row, col)| life.cells[row][col].is_some())
+            .collect();
+        assert_eq!(alive, vec![(1, 5), (2, 5), (3, 5)]);
+        assert_eq!(life.cells[2][5], Some(1));
+        assert_eq!(life.cells[1][5], Some(0));
+
+        // It flips back to the first state next, so it's replaced with random cells,
+        // which are all dead at a density of 0.
+        life.step();
+        assert!(life.cells.iter().flatten().all(Option::is_none));
+    }
+}
@@ -0,0 +1,135 @@
+use super::rng::Rng;
+use super::{speed, Palette, Stepper};
+use crate::{Bitmap, Color, Effect};
+use std::time::Duration;
+
+/// The number of times the rain is updated each second, at a speed of 1.
+const STEPS_PER_SECOND: f64 = 30.0;
+
+/// A drop falling down a column.
+#[derive(Debug, Copy, Clone)]
+struct Drop {
+    /// The row the head of the drop is on, which goes past the bottom of the keyboard before it's removed.
+    row: f64,
+    /// How many rows the drop falls each step.
+    speed: f64,
+}
+
+/// Drops of green falling down the columns of the keyboard, like the Matrix's digital rain.
+///
+/// Keys without a drop or its trail on them are left transparent. By default, a drop starts in each
+/// column about once a second, and its trail goes from dark green to a white head.
+#[derive(Debug, Clone)]
+pub struct MatrixRain {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    density: f64,
+    rng: Rng,
+    stepper: Stepper,
+    drops: [Option<Drop>; Bitmap::WIDTH],
+    trails: [[f64; Bitmap::WIDTH]; Bitmap::HEIGHT],
+}
+
+impl MatrixRain {
+    /// Creates a Matrix rain effect with the default settings.
+    pub fn new() -> MatrixRain {
+        MatrixRain {
+            speed: 1.0,
+            palette: Palette::new(vec![
+                Color::from_rgb8(0, 48, 0),
+                Color::from_rgb8(0, 160, 0),
+                Color::GREEN,
+                Color::from_rgb8(200, 255, 200),
+            ]),
+            seed: 0,
+            density: 1.0,
+            rng: Rng::new(0),
+            stepper: Stepper::default(),
+            drops: [None; Bitmap::WIDTH],
+            trails: [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT],
+        }
+    }
+
+    /// Multiplies how fast the drops fall.
+    pub fn with_speed(mut self, speed: f64) -> MatrixRain {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the gradient trails are colored by, from their faded end to the head of the drop.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> MatrixRain {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides when drops start, and how fast they fall.
+    pub fn with_seed(mut self, seed: u64) -> MatrixRain {
+        self.seed = seed;
+        self.reset();
+        self
+    }
+
+    /// Sets the average number of drops which start in each empty column each second.
+    pub fn with_density(mut self, density: f64) -> MatrixRain {
+        self.density = density.max(0.0);
+        self
+    }
+
+    fn reset(&mut self) {
+        self.rng = Rng::new(self.seed);
+        self.drops = [None; Bitmap::WIDTH];
+        self.trails = [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT];
+    }
+
+    fn step(&mut self) {
+        for trail in self.trails.iter_mut().flatten() {
+            *trail *= 0.85;
+        }
+        for (col, drop) in self.drops.iter_mut().enumerate() {
+            if drop.is_none() && self.rng.chance(self.density / STEPS_PER_SECOND) {
+                *drop = Some(Drop {
+                    row: 0.0,
+                    speed: (6.0 + 8.0 * self.rng.next_f64()) / STEPS_PER_SECOND,
+                });
+            }
+            if let Some(falling) = drop {
+                let row = falling.row as usize;
+                if row < Bitmap::HEIGHT {
+                    self.trails[row][col] = 1.0;
+                    falling.row += falling.speed;
+                } else {
+                    *drop = None;
+                }
+            }
+        }
+    }
+}
+
+impl Default for MatrixRain {
+    fn default() -> MatrixRain {
+        MatrixRain::new()
+    }
+}
+
+impl Effect for MatrixRain {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let (restart, steps) = self
+            .stepper
+            .advance(time, STEPS_PER_SECOND * speed(self.speed));
+        if restart {
+            self.reset();
+        }
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (row, trails) in self.trails.iter().enumerate() {
+            for (col, &trail) in trails.iter().enumerate() {
+                if trail > 0.05 {
+                    frame.set_cell(row, col, self.palette.gradient(trail));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Ready-made effects to run with an `Engine` or a `LayerStack`.
+//!
+//! Every effect is deterministic: rendering the same effect with the same settings at the same time
+//! always gives the same frame, whatever frame rate it's run at. Effects with randomness take a seed,
+//! and effects which simulate something step it at a fixed rate.
+//!
+//! Effects which fill the whole keyboard draw on every cell, while effects which only light some keys,
+//! like `Starlight` and `Ripple`, leave the rest transparent so they can be layered over another effect.
+//!
+//! `TypingRipple`, `FadeTrail` and `Heatmap` react to the key events sent to them with `Engine::key_event()`.
+//!
+//! # Example
+//! ```no_run
+//! use lightsync::effects::{Direction, RainbowWave};
+//! use lightsync::{Engine, Sdk};
+//! use std::sync::Arc;
+//!
+//! let mut engine = Engine::new(Arc::new(Sdk::init()?), 30);
+//! engine.set_effect(
+//!     RainbowWave::new()
+//!         .with_speed(2.0)
+//!         .with_direction(Direction::Left),
+//! );
+//! engine.start();
+//! # Ok::<(), lightsync::Error>(())
+//! ```
+
+mod cycle;
+mod fire;
+mod life;
+mod matrix;
+mod reactive;
+mod ripple;
+mod rng;
+mod starlight;
+mod wave;
+
+pub use self::cycle::{Breathing, ColorCycle, SpectrumCycle};
+pub use self::fire::Fire;
+pub use self::life::GameOfLife;
+pub use self::matrix::MatrixRain;
+pub use self::reactive::{FadeTrail, Heatmap, TypingRipple};
+pub use self::ripple::Ripple;
+pub use self::starlight::Starlight;
+pub use self::wave::{GradientSweep, RainbowWave};
+
+use crate::{Bitmap, Color};
+use std::time::Duration;
+
+/// The direction an effect moves across the keyboard in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Direction {
+    Left,
+    #[default]
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// Returns how far along the keyboard the cell at `row` and `col` is in this direction,
+    /// from 0 at the edge the effect starts from to 1 at the edge it moves towards.
+    pub fn progress(self, row: usize, col: usize) -> f64 {
+        let across = col as f64 / (Bitmap::WIDTH - 1) as f64;
+        let down = row as f64 / (Bitmap::HEIGHT - 1) as f64;
+        match self {
+            Direction::Right => across,
+            Direction::Left => 1.0 - across,
+            Direction::Down => down,
+            Direction::Up => 1.0 - down,
+        }
+    }
+}
+
+/// A list of colors which effects blend between.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    /// Creates a palette from `colors`, in the order they're blended between.
+    ///
+    /// # Panics
+    /// Panics if `colors` is empty.
+    pub fn new(colors: Vec<Color>) -> Palette {
+        assert!(!colors.is_empty(), "palette must have at least one color");
+        Palette { colors }
+    }
+
+    /// Red, yellow, green, cyan, blue and magenta, which blend around the color wheel.
+    pub fn rainbow() -> Palette {
+        Palette::new(
+            (0..6)
+                .map(|i| Color::from_hsv(i as f64 * 60.0, 1.0, 1.0))
+                .collect(),
+        )
+    }
+
+    /// Black through red, orange and yellow to white, for heat.
+    pub fn fire() -> Palette {
+        Palette::new(vec![
+            Color::BLACK,
+            Color::from_rgb8(128, 0, 0),
+            Color::RED,
+            Color::from_rgb8(255, 128, 0),
+            Color::from_rgb8(255, 220, 0),
+            Color::WHITE,
+        ])
+    }
+
+    /// Shades of blue and cyan.
+    pub fn ocean() -> Palette {
+        Palette::new(vec![
+            Color::from_rgb8(0, 16, 64),
+            Color::BLUE,
+            Color::from_rgb8(0, 160, 255),
+            Color::from_rgb8(0, 255, 200),
+        ])
+    }
+
+    /// Returns the colors in the palette.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Returns the color `t` of the way around the palette, blending from the last color back to the first.
+    ///
+    /// `t` wraps around, so 0 and 1 are both the first color.
+    pub fn cyclic(&self, t: f64) -> Color {
+        let position = t.rem_euclid(1.0) * self.colors.len() as f64;
+        let index = position as usize % self.colors.len();
+        let next = (index + 1) % self.colors.len();
+        self.colors[index].lerp(self.colors[next], position.fract())
+    }
+
+    /// Returns the color `t` of the way from the first color to the last.
+    ///
+    /// `t` is clamped to 0 to 1.
+    pub fn gradient(&self, t: f64) -> Color {
+        let position = t.clamp(0.0, 1.0) * (self.colors.len() - 1) as f64;
+        let index = (position as usize).min(self.colors.len() - 1);
+        let next = (index + 1).min(self.colors.len() - 1);
+        self.colors[index].lerp(self.colors[next], position - index as f64)
+    }
+}
+
+impl From<Vec<Color>> for Palette {
+    /// Creates a palette from `colors`, like `Palette::new()`.
+    fn from(colors: Vec<Color>) -> Palette {
+        Palette::new(colors)
+    }
+}
+
+/// Scales a speed multiplier, treating negative and non-finite speeds as 0.
+fn speed(speed: f64) -> f64 {
+    if speed.is_finite() {
+        speed.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Runs a simulation in fixed steps, so it looks the same at any frame rate.
+#[derive(Debug, Clone, Default)]
+struct Stepper {
+    steps: u64,
+}
+
+impl Stepper {
+    /// Returns how many steps to run to catch up to `time` at `rate` steps a second, and whether
+    /// `time` has gone backwards, so the simulation needs to start again before running them.
+    fn advance(&mut self, time: Duration, rate: f64) -> (bool, u64) {
+        let target = (time.as_secs_f64() * rate).floor() as u64;
+        let restart = target < self.steps;
+        let steps = if restart { target } else { target - self.steps };
+        self.steps = target;
+        (restart, steps)
+    }
+}
+
+/// Scales `color` by `brightness` from 0 to 1.
+fn dim(color: Color, brightness: f64) -> Color {
+    Color::BLACK.lerp(color, brightness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Effect;
+
+    #[test]
+    fn palettes() {
+        let palette = Palette::new(vec![Color::BLACK, Color::WHITE]);
+        assert_eq!(palette.gradient(0.0), Color::BLACK);
+        assert_eq!(palette.gradient(0.5), Color::from_rgb8(128, 128, 128));
+        assert_eq!(palette.gradient(1.5), Color::WHITE);
+        assert_eq!(palette.cyclic(0.0), Color::BLACK);
+        assert_eq!(palette.cyclic(0.5), Color::WHITE);
+        assert_eq!(palette.cyclic(0.75), Color::from_rgb8(128, 128, 128));
+        assert_eq!(palette.cyclic(-0.5), Color::WHITE);
+
+        let single = Palette::new(vec![Color::RED]);
+        assert_eq!(single.cyclic(0.3), Color::RED);
+        assert_eq!(single.gradient(0.3), Color::RED);
+
+        assert_eq!(Palette::rainbow().cyclic(1.0 / 3.0), Color::GREEN);
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Right.progress(0, 0), 0.0);
+        assert_eq!(Direction::Right.progress(0, 20), 1.0);
+        assert_eq!(Direction::Left.progress(3, 20), 0.0);
+        assert_eq!(Direction::Up.progress(5, 7), 0.0);
+        assert_eq!(Direction::Down.progress(5, 7), 1.0);
+    }
+
+    fn effects() -> Vec<Box<dyn Effect>> {
+        vec![
+            Box::new(RainbowWave::new()),
+            Box::new(SpectrumCycle::new()),
+            Box::new(Breathing::new(Color::RED)),
+            Box::new(Starlight::new().with_seed(7)),
+            Box::new(Ripple::new().with_seed(7)),
+            Box::new(ColorCycle::new()),
+            Box::new(Fire::new().with_seed(7)),
+            Box::new(MatrixRain::new().with_seed(7)),
+            Box::new(GradientSweep::new()),
+            Box::new(GameOfLife::new().with_seed(7)),
+        ]
+    }
+
+    fn render(effect: &mut dyn Effect, time: Duration) -> Bitmap {
+        let mut frame = Bitmap::new();
+        effect.render(time, &mut frame);
+        frame
+    }
+
+    /// Rendering at different frame rates, or jumping back to the start, gives the same frames.
+    #[test]
+    fn deterministic() {
+        let end = Duration::from_secs(3);
+        for (mut slow, mut fast) in effects().into_iter().zip(effects()) {
+            let mut time = Duration::from_secs(0);
+            while time < end {
+                render(&mut *fast, time);
+                time += Duration::from_millis(10);
+            }
+            for &time in &[Duration::from_millis(1500), end] {
+                render(&mut *slow, time);
+            }
+            assert_eq!(render(&mut *slow, end), render(&mut *fast, end));
+
+            let start = render(&mut *slow, Duration::from_secs(0));
+            assert_eq!(render(&mut *fast, Duration::from_secs(0)), start);
+        }
+    }
+
+    /// Every effect draws something at some point.
+    #[test]
+    fn draws() {
+        for mut effect in effects() {
+            let drawn = (0..40)
+                .map(|tenths| render(&mut *effect, Duration::from_millis(tenths * 100)))
+                .any(|frame| frame.cells().any(|(_, color)| color != Color::BLACK));
+            assert!(drawn, "{:?}", render(&mut *effect, Duration::from_secs(0)));
+        }
+    }
+
+    #[test]
+    fn seeds() {
+        let time = Duration::from_secs(2);
+        let frame = |seed| render(&mut Starlight::new().with_seed(seed), time);
+        assert_eq!(frame(1), frame(1));
+        assert_ne!(frame(1), frame(2));
+    }
+}
@@ -0,0 +1,364 @@
+use super::ripple::{draw_ring, CELLS_PER_SECOND, MAX_RADIUS};
+use super::rng::Rng;
+use super::{dim, speed, Palette};
+use crate::{Bitmap, Color, Effect, Key, KeyEvent};
+use std::time::Duration;
+
+/// Rings of color which spread out from each key as it's pressed.
+///
+/// Keys without a ring on them are left transparent. By default, each ring is a random color of the rainbow.
+#[derive(Debug, Clone)]
+pub struct TypingRipple {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    /// The time, position and color of each press whose ring hasn't faded out yet.
+    presses: Vec<(Duration, (usize, usize), Color)>,
+    count: u64,
+    last_render: Duration,
+}
+
+impl TypingRipple {
+    /// Creates a typing ripple effect with the default settings.
+    pub fn new() -> TypingRipple {
+        TypingRipple {
+            speed: 1.0,
+            palette: Palette::rainbow(),
+            seed: 0,
+            presses: Vec::new(),
+            count: 0,
+            last_render: Duration::from_secs(0),
+        }
+    }
+
+    /// Multiplies how fast the rings spread.
+    pub fn with_speed(mut self, speed: f64) -> TypingRipple {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors rings are randomly picked from.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> TypingRipple {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides the color of each ring.
+    pub fn with_seed(mut self, seed: u64) -> TypingRipple {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Default for TypingRipple {
+    fn default() -> TypingRipple {
+        TypingRipple::new()
+    }
+}
+
+impl Effect for TypingRipple {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        if time < self.last_render {
+            self.presses.clear();
+        }
+        self.last_render = time;
+
+        let speed = speed(self.speed);
+        self.presses.retain(|&(pressed, _, _)| {
+            time.saturating_sub(pressed).as_secs_f64() * speed * CELLS_PER_SECOND < MAX_RADIUS
+        });
+        for &(pressed, origin, color) in &self.presses {
+            if let Some(since) = time.checked_sub(pressed) {
+                draw_ring(
+                    frame,
+                    origin,
+                    since.as_secs_f64() * speed * CELLS_PER_SECOND,
+                    color,
+                );
+            }
+        }
+    }
+
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        if let (true, Some(origin)) = (event.is_press(), event.key.bitmap_position()) {
+            // Each press gets its own generator, so colors don't depend on when frames were rendered.
+            let mut rng = Rng::new(self.seed ^ self.count.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            self.count += 1;
+            self.presses
+                .push((time, origin, self.palette.cyclic(rng.next_f64())));
+        }
+    }
+}
+
+/// A key's place in a `FadeTrail`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Trail {
+    Held(Color),
+    Released(Color, Duration),
+}
+
+/// Keys which light up while they're held, and fade out after they're released.
+///
+/// Keys which haven't been pressed recently are left transparent. By default, keys light up in white
+/// and take a second to fade out.
+#[derive(Debug, Clone)]
+pub struct FadeTrail {
+    palette: Palette,
+    fade: Duration,
+    trails: [[Option<Trail>; Bitmap::WIDTH]; Bitmap::HEIGHT],
+    count: usize,
+    last_render: Duration,
+}
+
+impl FadeTrail {
+    /// Creates a fading trail effect with the default settings.
+    pub fn new() -> FadeTrail {
+        FadeTrail {
+            palette: Palette::new(vec![Color::WHITE]),
+            fade: Duration::from_secs(1),
+            trails: [[None; Bitmap::WIDTH]; Bitmap::HEIGHT],
+            count: 0,
+            last_render: Duration::from_secs(0),
+        }
+    }
+
+    /// Sets the colors keys light up in, taking the next one for each press.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> FadeTrail {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets how long keys take to fade out after they're released.
+    pub fn with_fade(mut self, fade: Duration) -> FadeTrail {
+        self.fade = fade;
+        self
+    }
+}
+
+impl Default for FadeTrail {
+    fn default() -> FadeTrail {
+        FadeTrail::new()
+    }
+}
+
+impl Effect for FadeTrail {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        if time < self.last_render {
+            self.trails = [[None; Bitmap::WIDTH]; Bitmap::HEIGHT];
+        }
+        self.last_render = time;
+
+        for (row, trails) in self.trails.iter_mut().enumerate() {
+            for (col, trail) in trails.iter_mut().enumerate() {
+                match *trail {
+                    Some(Trail::Held(color)) => frame.set_cell(row, col, color),
+                    Some(Trail::Released(color, released)) => {
+                        let since = time.saturating_sub(released);
+                        if since < self.fade {
+                            let brightness = 1.0 - since.as_secs_f64() / self.fade.as_secs_f64();
+                            frame.set_cell(row, col, dim(color, brightness));
+                        } else {
+                            *trail = None;
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        let (row, col) = match event.key.bitmap_position() {
+            Some(position) => position,
+            None => return,
+        };
+        let trail = &mut self.trails[row][col];
+        if event.is_press() {
+            let colors = self.palette.colors();
+            *trail = Some(Trail::Held(colors[self.count % colors.len()]));
+            self.count += 1;
+        } else if let Some(Trail::Held(color)) = *trail {
+            *trail = Some(Trail::Released(color, time));
+        }
+    }
+}
+
+/// A heatmap of the keys which have been pressed the most.
+///
+/// Each key is colored by picking how often it's been pressed, compared to the most pressed key, from a gradient.
+/// Keys which haven't been pressed are left transparent. By default, the gradient goes from blue through green
+/// and yellow to red, and presses are never forgotten.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    palette: Palette,
+    half_life: Option<Duration>,
+    heat: [[f64; Bitmap::WIDTH]; Bitmap::HEIGHT],
+    /// The time the heat was last cooled down to.
+    cooled: Duration,
+}
+
+impl Heatmap {
+    /// Creates a heatmap with the default settings.
+    pub fn new() -> Heatmap {
+        Heatmap {
+            palette: Palette::new(vec![
+                Color::BLUE,
+                Color::GREEN,
+                Color::from_rgb8(255, 255, 0),
+                Color::RED,
+            ]),
+            half_life: None,
+            heat: [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT],
+            cooled: Duration::from_secs(0),
+        }
+    }
+
+    /// Sets the gradient keys are colored by, from the least pressed to the most pressed.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Heatmap {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Makes presses count for half as much after `half_life`, so the heatmap follows recent typing.
+    ///
+    /// # Panics
+    /// Panics if `half_life` is 0.
+    pub fn with_half_life(mut self, half_life: Duration) -> Heatmap {
+        assert!(half_life > Duration::ZERO, "half life must be more than 0");
+        self.half_life = Some(half_life);
+        self
+    }
+
+    /// Returns how many times `key` has been pressed, after cooling down.
+    pub fn heat(&self, key: Key) -> f64 {
+        key.bitmap_position()
+            .map_or(0.0, |(row, col)| self.heat[row][col])
+    }
+
+    /// Cools the heat down to `time`.
+    fn cool(&mut self, time: Duration) {
+        if time < self.cooled {
+            self.heat = [[0.0; Bitmap::WIDTH]; Bitmap::HEIGHT];
+        } else if let Some(half_life) = self.half_life {
+            let halvings = (time - self.cooled).as_secs_f64() / half_life.as_secs_f64();
+            let factor = 0.5f64.powf(halvings);
+            for heat in self.heat.iter_mut().flatten() {
+                *heat *= factor;
+            }
+        }
+        self.cooled = time;
+    }
+}
+
+impl Default for Heatmap {
+    fn default() -> Heatmap {
+        Heatmap::new()
+    }
+}
+
+impl Effect for Heatmap {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        self.cool(time);
+        let hottest = self.heat.iter().flatten().cloned().fold(0.0, f64::max);
+        for (row, heats) in self.heat.iter().enumerate() {
+            for (col, &heat) in heats.iter().enumerate() {
+                if heat > 0.01 {
+                    frame.set_cell(row, col, self.palette.gradient(heat / hottest));
+                }
+            }
+        }
+    }
+
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        if let (true, Some((row, col))) = (event.is_press(), event.key.bitmap_position()) {
+            self.cool(time.max(self.cooled));
+            self.heat[row][col] += 1.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+    use crate::{HeadlessEngine, Recording, Sdk};
+    use std::sync::Arc;
+
+    fn engine(effect: impl Effect + 'static) -> (MockBackend, HeadlessEngine<MockBackend>) {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        let mut engine = HeadlessEngine::new(Arc::new(sdk), 100);
+        engine.set_effect(effect);
+        engine.start().unwrap();
+        (mock, engine)
+    }
+
+    #[test]
+    fn typing_ripple() {
+        let (mock, mut engine) = engine(TypingRipple::new().with_palette(vec![Color::RED]));
+        engine.key_event(KeyEvent::press(Key::G));
+        engine.advance(Duration::from_millis(10)).unwrap();
+        assert_ne!(mock.key_color(Key::G), Color::BLACK);
+        assert_eq!(mock.key_color(Key::Esc), Color::BLACK);
+
+        // The ring spreads out from G, two columns every 1/6 of a second.
+        engine.advance(Duration::from_millis(160)).unwrap();
+        assert_eq!(mock.key_color(Key::J).green, 0);
+        assert!(mock.key_color(Key::J).red > mock.key_color(Key::G).red);
+
+        assert_eq!(engine.frames(), 18);
+
+        // Releasing keys and keys without a cell don't start ripples.
+        let mut ripple = TypingRipple::new();
+        ripple.key_event(Duration::from_secs(0), KeyEvent::release(Key::A));
+        ripple.key_event(Duration::from_secs(0), KeyEvent::press(Key::G1));
+        assert!(ripple.presses.is_empty());
+        assert_eq!(ripple.count, 0);
+        let mut frame = Bitmap::new();
+        ripple.render(Duration::from_millis(100), &mut frame);
+        assert_eq!(frame, Bitmap::new());
+    }
+
+    #[test]
+    fn fade_trail() {
+        let (mock, mut engine) = engine(FadeTrail::new());
+        let recording: Recording = "
+            0.0 press A
+            0.5 release A
+        "
+        .parse()
+        .unwrap();
+        engine.replay(&recording).unwrap();
+        engine.advance(Duration::from_millis(10)).unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::from_rgb8(252, 252, 252));
+
+        engine.advance(Duration::from_millis(490)).unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::from_rgb8(128, 128, 128));
+        // The last frame it's lit on is just before it fades out completely.
+        engine.advance(Duration::from_millis(500)).unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::from_rgb8(3, 3, 3));
+    }
+
+    #[test]
+    fn heatmap() {
+        let mut heatmap = Heatmap::new().with_half_life(Duration::from_secs(1));
+        heatmap.key_event(Duration::from_secs(0), KeyEvent::press(Key::A));
+        heatmap.key_event(Duration::from_secs(0), KeyEvent::press(Key::A));
+        heatmap.key_event(Duration::from_secs(0), KeyEvent::release(Key::A));
+        heatmap.key_event(Duration::from_secs(1), KeyEvent::press(Key::B));
+        assert_eq!(heatmap.heat(Key::A), 1.0);
+        assert_eq!(heatmap.heat(Key::B), 1.0);
+
+        let mut frame = Bitmap::new();
+        heatmap.render(Duration::from_secs(2), &mut frame);
+        assert_eq!(heatmap.heat(Key::A), 0.5);
+        assert_eq!(frame.get(Key::A), Some(Color::RED));
+        assert_eq!(frame.alpha(Key::C), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "half life must be more than 0")]
+    fn heatmap_zero_half_life() {
+        let _ = Heatmap::new().with_half_life(Duration::ZERO);
+    }
+}
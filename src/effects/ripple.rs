@@ -0,0 +1,117 @@
+use super::rng::Rng;
+use super::{dim, speed, Palette};
+use crate::{Bitmap, Color, Effect, Key};
+use std::time::Duration;
+
+/// How many cells a ripple's ring moves out each second, at a speed of 1.
+pub(super) const CELLS_PER_SECOND: f64 = 12.0;
+/// How many cells thick a ripple's ring is.
+const RING_WIDTH: f64 = 1.5;
+/// How far a ripple spreads before it's faded out, which is far enough to cross the whole keyboard.
+pub(super) const MAX_RADIUS: f64 = 22.0;
+
+/// Rings of color which spread out from a key, one after another.
+///
+/// Keys without a ring on them are left transparent. By default, a ripple starts from a random key
+/// every second, in a random color of the rainbow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ripple {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    origin: Option<(usize, usize)>,
+    interval: Duration,
+}
+
+impl Ripple {
+    /// Creates a ripple effect with the default settings.
+    pub fn new() -> Ripple {
+        Ripple {
+            speed: 1.0,
+            palette: Palette::rainbow(),
+            seed: 0,
+            origin: None,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Multiplies how fast the rings spread, and how often they start.
+    pub fn with_speed(mut self, speed: f64) -> Ripple {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors ripples are randomly picked from.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Ripple {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides the color of each ripple, and where it starts from.
+    pub fn with_seed(mut self, seed: u64) -> Ripple {
+        self.seed = seed;
+        self
+    }
+
+    /// Starts every ripple from `key`, instead of a random one.
+    ///
+    /// Keys which aren't in the bitmap grid are ignored.
+    pub fn with_origin(mut self, key: Key) -> Ripple {
+        self.origin = key.bitmap_position().or(self.origin);
+        self
+    }
+
+    /// Sets the time between each ripple starting, at a speed of 1.
+    pub fn with_interval(mut self, interval: Duration) -> Ripple {
+        self.interval = interval;
+        self
+    }
+}
+
+impl Default for Ripple {
+    fn default() -> Ripple {
+        Ripple::new()
+    }
+}
+
+impl Effect for Ripple {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let time = time.as_secs_f64() * speed(self.speed);
+        // Very short intervals would mean drawing a huge number of ripples on top of each other.
+        let interval = self.interval.as_secs_f64().max(0.01);
+        let lifetime = MAX_RADIUS / CELLS_PER_SECOND;
+        let latest = (time / interval).floor() as u64;
+        let earliest = ((time - lifetime) / interval).max(0.0).ceil() as u64;
+
+        // Older ripples are drawn first, so newer ones are on top.
+        for ripple in earliest..=latest {
+            // Each ripple is decided by its own generator, so they don't depend on the frames before.
+            let mut rng = Rng::new(self.seed ^ ripple.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let color = self.palette.cyclic(rng.next_f64());
+            let origin = self.origin.unwrap_or_else(|| loop {
+                let (row, col) = (rng.below(Bitmap::HEIGHT), rng.below(Bitmap::WIDTH));
+                if Key::at(row, col).is_some() {
+                    break (row, col);
+                }
+            });
+            let radius = (time - ripple as f64 * interval) * CELLS_PER_SECOND;
+            draw_ring(frame, origin, radius, color);
+        }
+    }
+}
+
+/// Draws a ring of radius `radius` cells around `origin`, which fades out as it spreads.
+pub(super) fn draw_ring(frame: &mut Bitmap, origin: (usize, usize), radius: f64, color: Color) {
+    if !(0.0..MAX_RADIUS).contains(&radius) {
+        return;
+    }
+    for row in 0..Bitmap::HEIGHT {
+        for col in 0..Bitmap::WIDTH {
+            let distance = (row as f64 - origin.0 as f64).hypot(col as f64 - origin.1 as f64);
+            let ring = 1.0 - (distance - radius).abs() / RING_WIDTH;
+            if ring > 0.0 {
+                frame.set_cell(row, col, dim(color, ring * (1.0 - radius / MAX_RADIUS)));
+            }
+        }
+    }
+}
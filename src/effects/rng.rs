@@ -0,0 +1,77 @@
+/// A small xorshift random number generator, so effects are reproducible from a seed without
+/// depending on a `rand` crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from `seed`. Every seed, including 0, gives a different sequence.
+    pub(crate) fn new(seed: u64) -> Rng {
+        // Xorshift gets stuck on a state of 0, and gives similar sequences for similar states,
+        // so the seed is mixed with splitmix64 first.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        Rng {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// Returns the next random 64-bit number, using xorshift64*.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a random number from 0 up to but not including 1.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random number from 0 up to but not including `n`.
+    ///
+    /// # Panics
+    /// Panics if `n` is 0.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "range must not be empty");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns `true` with a probability of `p`.
+    pub(crate) fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(0).next_u64(), Rng::new(1).next_u64());
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(0);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+            seen[rng.below(6)] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
@@ -0,0 +1,128 @@
+use super::rng::Rng;
+use super::{dim, speed, Palette, Stepper};
+use crate::{Bitmap, Color, Effect, Key};
+use std::time::Duration;
+
+/// The number of times the stars are updated each second, at a speed of 1.
+const STEPS_PER_SECOND: f64 = 30.0;
+
+/// Random keys twinkling on and fading out.
+///
+/// Keys without a star are left transparent. By default, stars are colors of the rainbow,
+/// about eight appear each second, and each takes a second to fade out.
+#[derive(Debug, Clone)]
+pub struct Starlight {
+    speed: f64,
+    palette: Palette,
+    seed: u64,
+    density: f64,
+    fade: Duration,
+    rng: Rng,
+    stepper: Stepper,
+    /// The color and brightness of the star on each cell.
+    stars: [[(Color, f64); Bitmap::WIDTH]; Bitmap::HEIGHT],
+}
+
+impl Starlight {
+    /// Creates a starlight effect with the default settings.
+    pub fn new() -> Starlight {
+        Starlight {
+            speed: 1.0,
+            palette: Palette::rainbow(),
+            seed: 0,
+            density: 8.0,
+            fade: Duration::from_secs(1),
+            rng: Rng::new(0),
+            stepper: Stepper::default(),
+            stars: [[(Color::BLACK, 0.0); Bitmap::WIDTH]; Bitmap::HEIGHT],
+        }
+    }
+
+    /// Multiplies how fast stars appear and fade out.
+    pub fn with_speed(mut self, speed: f64) -> Starlight {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the colors stars are randomly picked from.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Starlight {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the seed which decides where stars appear.
+    pub fn with_seed(mut self, seed: u64) -> Starlight {
+        self.seed = seed;
+        self.reset();
+        self
+    }
+
+    /// Sets the average number of stars which appear each second.
+    pub fn with_density(mut self, density: f64) -> Starlight {
+        self.density = density.max(0.0);
+        self
+    }
+
+    /// Sets how long each star takes to fade out.
+    pub fn with_fade(mut self, fade: Duration) -> Starlight {
+        self.fade = fade;
+        self
+    }
+
+    fn reset(&mut self) {
+        self.rng = Rng::new(self.seed);
+        self.stars = [[(Color::BLACK, 0.0); Bitmap::WIDTH]; Bitmap::HEIGHT];
+    }
+
+    fn step(&mut self) {
+        let fade_steps = self.fade.as_secs_f64() * STEPS_PER_SECOND;
+        for (_, brightness) in self.stars.iter_mut().flatten() {
+            *brightness = if fade_steps > 0.0 {
+                (*brightness - 1.0 / fade_steps).max(0.0)
+            } else {
+                0.0
+            };
+        }
+
+        let stars = self.density / STEPS_PER_SECOND;
+        let count = stars as usize + self.rng.chance(stars.fract()) as usize;
+        for _ in 0..count {
+            let (row, col) = (
+                self.rng.below(Bitmap::HEIGHT),
+                self.rng.below(Bitmap::WIDTH),
+            );
+            let color = self.palette.cyclic(self.rng.next_f64());
+            if Key::at(row, col).is_some() {
+                self.stars[row][col] = (color, 1.0);
+            }
+        }
+    }
+}
+
+impl Default for Starlight {
+    fn default() -> Starlight {
+        Starlight::new()
+    }
+}
+
+impl Effect for Starlight {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let (restart, steps) = self
+            .stepper
+            .advance(time, STEPS_PER_SECOND * speed(self.speed));
+        if restart {
+            self.reset();
+        }
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (row, stars) in self.stars.iter().enumerate() {
+            for (col, &(color, brightness)) in stars.iter().enumerate() {
+                if brightness > 0.0 {
+                    frame.set_cell(row, col, dim(color, brightness));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use super::{dim, speed, Direction, Palette};
+use crate::{Bitmap, Effect};
+use std::time::Duration;
+
+/// Bands of color which scroll across the keyboard.
+///
+/// By default, it scrolls a rainbow to the right, going through the whole rainbow every two seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RainbowWave {
+    speed: f64,
+    direction: Direction,
+    palette: Palette,
+    wavelength: f64,
+}
+
+impl RainbowWave {
+    /// Creates a rainbow wave with the default settings.
+    pub fn new() -> RainbowWave {
+        RainbowWave {
+            speed: 1.0,
+            direction: Direction::Right,
+            palette: Palette::rainbow(),
+            wavelength: 1.0,
+        }
+    }
+
+    /// Multiplies how fast the wave scrolls.
+    pub fn with_speed(mut self, speed: f64) -> RainbowWave {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the direction the wave scrolls in.
+    pub fn with_direction(mut self, direction: Direction) -> RainbowWave {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the colors the wave goes through, instead of a rainbow.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> RainbowWave {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets how many keyboards long the palette is stretched over, which has to be more than 0. The default is 1.
+    pub fn with_wavelength(mut self, wavelength: f64) -> RainbowWave {
+        self.wavelength = wavelength.max(f64::EPSILON);
+        self
+    }
+}
+
+impl Default for RainbowWave {
+    fn default() -> RainbowWave {
+        RainbowWave::new()
+    }
+}
+
+impl Effect for RainbowWave {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let offset = time.as_secs_f64() * speed(self.speed) * 0.5;
+        for row in 0..Bitmap::HEIGHT {
+            for col in 0..Bitmap::WIDTH {
+                let position = self.direction.progress(row, col) / self.wavelength;
+                frame.set_cell(row, col, self.palette.cyclic(position - offset));
+            }
+        }
+    }
+}
+
+/// A bar with a fading tail which sweeps across the keyboard, colored by a gradient along the way.
+///
+/// Keys the bar isn't on are left transparent. By default, it sweeps to the right every two seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientSweep {
+    speed: f64,
+    direction: Direction,
+    palette: Palette,
+    width: f64,
+}
+
+impl GradientSweep {
+    /// Creates a gradient sweep with the default settings.
+    pub fn new() -> GradientSweep {
+        GradientSweep {
+            speed: 1.0,
+            direction: Direction::Right,
+            palette: Palette::ocean(),
+            width: 0.3,
+        }
+    }
+
+    /// Multiplies how fast the bar sweeps.
+    pub fn with_speed(mut self, speed: f64) -> GradientSweep {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the direction the bar sweeps in.
+    pub fn with_direction(mut self, direction: Direction) -> GradientSweep {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the gradient the bar is colored by, from the edge it starts at to the edge it ends at.
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> GradientSweep {
+        self.palette = palette.into();
+        self
+    }
+
+    /// Sets the length of the bar and its tail, as a fraction of the keyboard. The default is 0.3.
+    pub fn with_width(mut self, width: f64) -> GradientSweep {
+        self.width = width.max(f64::EPSILON);
+        self
+    }
+}
+
+impl Default for GradientSweep {
+    fn default() -> GradientSweep {
+        GradientSweep::new()
+    }
+}
+
+impl Effect for GradientSweep {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        // The head goes past the far edge until its tail has left the keyboard too.
+        let sweep = (time.as_secs_f64() * speed(self.speed) * 0.5).fract();
+        let head = sweep * (1.0 + self.width);
+        for row in 0..Bitmap::HEIGHT {
+            for col in 0..Bitmap::WIDTH {
+                let position = self.direction.progress(row, col);
+                let behind = head - position;
+                if (0.0..self.width).contains(&behind) {
+                    let color = self.palette.gradient(position);
+                    frame.set_cell(row, col, dim(color, 1.0 - behind / self.width));
+                }
+            }
+        }
+    }
+}
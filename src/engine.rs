@@ -0,0 +1,658 @@
+//! Software effects, rendered a frame at a time and displayed with `Sdk::set_lighting_from_bitmap()`.
+
+use super::{Bitmap, Error, KeyEvent, LightingBackend, Recording, Sdk};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// An animation which can be run by an `Engine`.
+///
+/// It's implemented for closures taking the same arguments as `render()`.
+///
+/// # Example
+/// ```
+/// use lightsync::{Bitmap, Color, Effect};
+/// use std::time::Duration;
+///
+/// /// Fades the whole keyboard between black and red every second.
+/// struct Breathe;
+///
+/// impl Effect for Breathe {
+///     fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+///         let t = time.as_secs_f64() % 2.0;
+///         let brightness = if t < 1.0 { t } else { 2.0 - t };
+///         frame.fill(Color::from_hsv(0.0, 1.0, brightness));
+///     }
+/// }
+/// ```
+pub trait Effect: Send {
+    /// Draws the frame `time` after the effect started onto `frame`.
+    ///
+    /// `frame` starts out transparent, so keys which aren't drawn on are left alone.
+    fn render(&mut self, time: Duration, frame: &mut Bitmap);
+
+    /// Reacts to a key being pressed or released `time` after the effect started.
+    ///
+    /// Effects ignore key events by default.
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        let _ = (time, event);
+    }
+}
+
+impl<F: FnMut(Duration, &mut Bitmap) + Send> Effect for F {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        self(time, frame)
+    }
+}
+
+/// Lets an effect be changed while an `Engine` is running it.
+impl<E: Effect> Effect for Arc<Mutex<E>> {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        self.lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .render(time, frame)
+    }
+
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        self.lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .key_event(time, event)
+    }
+}
+
+impl Effect for Box<dyn Effect> {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        (**self).render(time, frame)
+    }
+
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        (**self).key_event(time, event)
+    }
+}
+
+/// Whether an engine is currently rendering frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EngineState {
+    Stopped,
+    Running,
+    Paused,
+}
+
+/// The state shared by `Engine` and `HeadlessEngine`.
+struct Runner<B: LightingBackend> {
+    sdk: Arc<Sdk<B>>,
+    effect: Option<Box<dyn Effect>>,
+    frame_interval: Duration,
+    state: EngineState,
+    /// The time since the effect started, not counting time spent paused.
+    time: Duration,
+    frames: u64,
+    last_error: Option<Error>,
+}
+
+impl<B: LightingBackend> Runner<B> {
+    fn new(sdk: Arc<Sdk<B>>, frame_rate: u32) -> Runner<B> {
+        Runner {
+            sdk,
+            effect: None,
+            frame_interval: frame_interval(frame_rate),
+            state: EngineState::Stopped,
+            time: Duration::from_secs(0),
+            frames: 0,
+            last_error: None,
+        }
+    }
+
+    fn start(&mut self) -> Result<(), Error> {
+        self.state = EngineState::Running;
+        self.time = Duration::from_secs(0);
+        self.render()
+    }
+
+    fn pause(&mut self) {
+        if self.state == EngineState::Running {
+            self.state = EngineState::Paused;
+        }
+    }
+
+    fn resume(&mut self) {
+        if self.state == EngineState::Paused {
+            self.state = EngineState::Running;
+        }
+    }
+
+    /// Moves the effect on by `elapsed` and renders a frame, if the engine is running.
+    fn tick(&mut self, elapsed: Duration) -> Result<(), Error> {
+        if self.state != EngineState::Running {
+            return Ok(());
+        }
+        self.time += elapsed;
+        self.render()
+    }
+
+    /// Sends `event` to the effect as happening `since_frame` after the last frame, if the engine isn't stopped.
+    fn key_event(&mut self, event: KeyEvent, since_frame: Duration) {
+        if self.state == EngineState::Stopped {
+            return;
+        }
+        if let Some(effect) = &mut self.effect {
+            effect.key_event(self.time + since_frame, event);
+        }
+    }
+
+    fn render(&mut self) -> Result<(), Error> {
+        let effect = match &mut self.effect {
+            Some(effect) => effect,
+            None => return Ok(()),
+        };
+        let mut frame = Bitmap::new();
+        effect.render(self.time, &mut frame);
+        self.frames += 1;
+        let result = self.sdk.set_lighting_from_bitmap(&frame);
+        if let Err(err) = result {
+            self.last_error = Some(err);
+        }
+        result
+    }
+}
+
+fn frame_interval(frame_rate: u32) -> Duration {
+    assert!(frame_rate > 0, "frame rate must be more than 0");
+    Duration::from_secs(1) / frame_rate
+}
+
+/// Runs an `Effect` on a background thread, displaying a new frame at a fixed frame rate.
+///
+/// Frames are displayed with `Sdk::set_lighting_from_bitmap()`, so other calls to the `Sdk`
+/// can still be made while the engine is running, to set keys the effect leaves transparent.
+///
+/// The engine is stopped when it's dropped.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Color, Engine, Sdk};
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let sdk = Arc::new(Sdk::init()?);
+/// let mut engine = Engine::new(sdk, 30);
+/// engine.set_effect(|time: Duration, frame: &mut lightsync::Bitmap| {
+///     let hue = time.as_secs_f64() * 90.0;
+///     frame.fill(Color::from_hsv(hue, 1.0, 1.0));
+/// });
+/// engine.start();
+/// thread::sleep(Duration::from_secs(4));
+/// engine.stop();
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+pub struct Engine<B: LightingBackend> {
+    shared: Arc<Shared<B>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared<B: LightingBackend> {
+    runner: Mutex<Runner<B>>,
+    /// Notified when the engine is stopped, to wake up the background thread.
+    stopped: Condvar,
+}
+
+impl<B: LightingBackend> Shared<B> {
+    fn runner(&self) -> MutexGuard<'_, Runner<B>> {
+        // An effect panicking doesn't leave the runner inconsistent.
+        self.runner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<B: LightingBackend + Send + Sync + 'static> Engine<B> {
+    /// Creates a stopped engine which will display frames on `sdk` `frame_rate` times a second.
+    ///
+    /// # Panics
+    /// Panics if `frame_rate` is 0.
+    pub fn new(sdk: Arc<Sdk<B>>, frame_rate: u32) -> Engine<B> {
+        Engine {
+            shared: Arc::new(Shared {
+                runner: Mutex::new(Runner::new(sdk, frame_rate)),
+                stopped: Condvar::new(),
+            }),
+            thread: None,
+        }
+    }
+
+    /// Starts running the effect from the beginning on a background thread.
+    ///
+    /// If the engine is already running, the effect is restarted.
+    pub fn start(&mut self) {
+        self.stop();
+        // Errors are recorded in `last_error()`.
+        let _ = self.shared.runner().start();
+
+        let shared = Arc::clone(&self.shared);
+        self.thread = Some(thread::spawn(move || {
+            let mut last_frame = Instant::now();
+            loop {
+                let mut runner = shared.runner();
+                let next_frame = last_frame + runner.frame_interval;
+                let now = Instant::now();
+                if now < next_frame {
+                    runner = shared
+                        .stopped
+                        .wait_timeout(runner, next_frame - now)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0;
+                }
+                if runner.state == EngineState::Stopped {
+                    break;
+                }
+                let now = Instant::now();
+                if now >= next_frame {
+                    let _ = runner.tick(now - last_frame);
+                    last_frame = now;
+                }
+                // Let `stop` and the other methods in between frames, even when rendering a frame
+                // takes longer than the frame interval.
+                drop(runner);
+                thread::yield_now();
+            }
+        }));
+    }
+
+    /// Stops running the effect, and waits for the background thread to exit.
+    ///
+    /// The last frame is left on the devices.
+    pub fn stop(&mut self) {
+        self.shared.runner().state = EngineState::Stopped;
+        self.shared.stopped.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Returns a handle which sends key events to the effect, which can be moved to the thread
+    /// reading input, like an OS keyboard hook.
+    pub fn input(&self) -> InputHandle {
+        let shared = Arc::clone(&self.shared);
+        InputHandle {
+            send: Arc::new(move |event| shared.runner().key_event(event, Duration::from_secs(0))),
+        }
+    }
+}
+
+impl<B: LightingBackend> Engine<B> {
+    /// Sends `event` to the effect, as happening on the frame the engine is currently on.
+    ///
+    /// Events are ignored while the engine is stopped.
+    pub fn key_event(&self, event: KeyEvent) {
+        self.shared
+            .runner()
+            .key_event(event, Duration::from_secs(0));
+    }
+
+    /// Sets the effect to run, starting from the frame the engine is currently on.
+    pub fn set_effect(&self, effect: impl Effect + 'static) {
+        self.shared.runner().effect = Some(Box::new(effect));
+    }
+
+    /// Sets the number of frames displayed each second.
+    ///
+    /// # Panics
+    /// Panics if `frame_rate` is 0.
+    pub fn set_frame_rate(&self, frame_rate: u32) {
+        self.shared.runner().frame_interval = frame_interval(frame_rate);
+    }
+
+    /// Freezes the effect on its current frame until `resume()` is called.
+    pub fn pause(&self) {
+        self.shared.runner().pause();
+    }
+
+    /// Continues running the effect after `pause()`, from the frame it was paused on.
+    pub fn resume(&self) {
+        self.shared.runner().resume();
+    }
+
+    /// Returns whether the engine is running, paused or stopped.
+    pub fn state(&self) -> EngineState {
+        self.shared.runner().state
+    }
+
+    /// Returns the time since the effect started, not counting time spent paused.
+    pub fn time(&self) -> Duration {
+        self.shared.runner().time
+    }
+
+    /// Returns the number of frames rendered so far.
+    pub fn frames(&self) -> u64 {
+        self.shared.runner().frames
+    }
+
+    /// Returns the last error returned while displaying a frame, if any.
+    pub fn last_error(&self) -> Option<Error> {
+        self.shared.runner().last_error
+    }
+}
+
+impl<B: LightingBackend> Drop for Engine<B> {
+    fn drop(&mut self) {
+        self.shared.runner().state = EngineState::Stopped;
+        self.shared.stopped.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<B: LightingBackend> fmt::Debug for Engine<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let runner = self.shared.runner();
+        f.debug_struct("Engine")
+            .field("state", &runner.state)
+            .field("frame_interval", &runner.frame_interval)
+            .field("time", &runner.time)
+            .finish()
+    }
+}
+
+/// Sends key events to the effect an `Engine` is running, from any thread.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::effects::TypingRipple;
+/// use lightsync::{Engine, Key, KeyEvent, Sdk};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let mut engine = Engine::new(Arc::new(Sdk::init()?), 30);
+/// engine.set_effect(TypingRipple::new());
+/// engine.start();
+///
+/// let input = engine.input();
+/// thread::spawn(move || {
+///     // Read key presses from somewhere, and send them on.
+///     input.send(KeyEvent::press(Key::A));
+///     input.send(KeyEvent::release(Key::A));
+/// });
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct InputHandle {
+    send: Arc<dyn Fn(KeyEvent) + Send + Sync>,
+}
+
+impl InputHandle {
+    /// Sends `event` to the effect, like `Engine::key_event()`.
+    pub fn send(&self, event: KeyEvent) {
+        (self.send)(event)
+    }
+}
+
+impl fmt::Debug for InputHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InputHandle").finish()
+    }
+}
+
+/// An `Engine` without a background thread, which only renders frames when its clock is moved on with `advance()`.
+///
+/// This makes effects deterministic, for testing them against a `MockBackend`.
+///
+/// # Example
+/// ```
+/// use lightsync::mock::MockBackend;
+/// use lightsync::{Bitmap, Color, HeadlessEngine, Key, Sdk};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let mock = MockBackend::new();
+/// let mut engine = HeadlessEngine::new(Arc::new(Sdk::with_backend(mock.clone())?), 10);
+/// engine.set_effect(|time: Duration, frame: &mut Bitmap| {
+///     if time >= Duration::from_secs(1) {
+///         frame.set(Key::Space, Color::RED);
+///     }
+/// });
+/// engine.start()?;
+///
+/// engine.advance(Duration::from_millis(900))?;
+/// assert_eq!(mock.key_color(Key::Space), Color::BLACK);
+/// engine.advance(Duration::from_millis(100))?;
+/// assert_eq!(mock.key_color(Key::Space), Color::RED);
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+pub struct HeadlessEngine<B: LightingBackend> {
+    runner: Runner<B>,
+    /// The time passed to `advance()` which hasn't added up to a frame yet.
+    pending: Duration,
+}
+
+impl<B: LightingBackend> HeadlessEngine<B> {
+    /// Creates a stopped engine which will display frames on `sdk` `frame_rate` times each simulated second.
+    ///
+    /// # Panics
+    /// Panics if `frame_rate` is 0.
+    pub fn new(sdk: Arc<Sdk<B>>, frame_rate: u32) -> HeadlessEngine<B> {
+        HeadlessEngine {
+            runner: Runner::new(sdk, frame_rate),
+            pending: Duration::from_secs(0),
+        }
+    }
+
+    /// Starts running the effect from the beginning, and displays its first frame.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.pending = Duration::from_secs(0);
+        self.runner.start()
+    }
+
+    /// Stops running the effect. The last frame is left on the devices.
+    pub fn stop(&mut self) {
+        self.runner.state = EngineState::Stopped;
+    }
+
+    /// Moves the clock on by `elapsed`, displaying every frame which is due in that time.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn advance(&mut self, elapsed: Duration) -> Result<(), Error> {
+        self.pending += elapsed;
+        while self.pending >= self.runner.frame_interval {
+            self.pending -= self.runner.frame_interval;
+            self.runner.tick(self.runner.frame_interval)?;
+        }
+        Ok(())
+    }
+
+    /// Sends `event` to the effect, as happening at the current time on the engine's clock.
+    ///
+    /// Events are ignored while the engine is stopped.
+    pub fn key_event(&mut self, event: KeyEvent) {
+        // The clock may be part of the way to the next frame, which only counts while running.
+        let since_frame = if self.runner.state == EngineState::Running {
+            self.pending
+        } else {
+            Duration::from_secs(0)
+        };
+        self.runner.key_event(event, since_frame);
+    }
+
+    /// Plays back `recording` from the current time, moving the clock on to each event in turn.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn replay(&mut self, recording: &Recording) -> Result<(), Error> {
+        let mut played = Duration::from_secs(0);
+        for &(time, event) in recording.events() {
+            self.advance(time - played)?;
+            played = time;
+            self.key_event(event);
+        }
+        Ok(())
+    }
+
+    /// Sets the effect to run, starting from the frame the engine is currently on.
+    pub fn set_effect(&mut self, effect: impl Effect + 'static) {
+        self.runner.effect = Some(Box::new(effect));
+    }
+
+    /// Sets the number of frames displayed each simulated second.
+    ///
+    /// # Panics
+    /// Panics if `frame_rate` is 0.
+    pub fn set_frame_rate(&mut self, frame_rate: u32) {
+        self.runner.frame_interval = frame_interval(frame_rate);
+    }
+
+    /// Returns the simulated time between frames.
+    pub fn frame_interval(&self) -> Duration {
+        self.runner.frame_interval
+    }
+
+    /// Freezes the effect on its current frame until `resume()` is called.
+    pub fn pause(&mut self) {
+        self.runner.pause();
+    }
+
+    /// Continues running the effect after `pause()`, from the frame it was paused on.
+    pub fn resume(&mut self) {
+        self.runner.resume();
+    }
+
+    /// Returns whether the engine is running, paused or stopped.
+    pub fn state(&self) -> EngineState {
+        self.runner.state
+    }
+
+    /// Returns the time since the effect started, not counting time spent paused.
+    pub fn time(&self) -> Duration {
+        self.runner.time
+    }
+
+    /// Returns the number of frames rendered so far.
+    pub fn frames(&self) -> u64 {
+        self.runner.frames
+    }
+
+    /// Returns the `Sdk` frames are displayed on.
+    pub fn sdk(&self) -> &Sdk<B> {
+        &self.runner.sdk
+    }
+}
+
+impl<B: LightingBackend> fmt::Debug for HeadlessEngine<B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeadlessEngine")
+            .field("state", &self.runner.state)
+            .field("frame_interval", &self.runner.frame_interval)
+            .field("time", &self.runner.time)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+    use crate::{Color, Key};
+
+    /// Lights up one more key of the top row every 100ms.
+    fn sweep(time: Duration, frame: &mut Bitmap) {
+        let lit = (time.as_millis() / 100) as usize;
+        for col in 0..lit.min(Bitmap::WIDTH) {
+            frame.set_cell(0, col, Color::RED);
+        }
+    }
+
+    fn headless(frame_rate: u32) -> (MockBackend, HeadlessEngine<MockBackend>) {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        let mut engine = HeadlessEngine::new(Arc::new(sdk), frame_rate);
+        engine.set_effect(sweep);
+        (mock, engine)
+    }
+
+    #[test]
+    fn renders_frames_at_frame_rate() {
+        let (mock, mut engine) = headless(10);
+        engine.advance(Duration::from_secs(1)).unwrap();
+        // Nothing happens until the engine is started.
+        assert_eq!(engine.frames(), 0);
+
+        engine.start().unwrap();
+        assert_eq!(engine.frames(), 1);
+        engine.advance(Duration::from_millis(250)).unwrap();
+        assert_eq!(engine.frames(), 3);
+        assert_eq!(engine.time(), Duration::from_millis(200));
+        assert_eq!(mock.key_color(Key::F1), Color::RED);
+        assert_eq!(mock.key_color(Key::F2), Color::BLACK);
+
+        engine.advance(Duration::from_millis(50)).unwrap();
+        assert_eq!(engine.frames(), 4);
+        assert_eq!(mock.key_color(Key::F2), Color::RED);
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let (mock, mut engine) = headless(10);
+        engine.start().unwrap();
+        engine.advance(Duration::from_millis(100)).unwrap();
+        engine.pause();
+        engine.advance(Duration::from_secs(1)).unwrap();
+        assert_eq!(engine.state(), EngineState::Paused);
+        assert_eq!(engine.frames(), 2);
+        assert_eq!(mock.key_color(Key::F1), Color::BLACK);
+
+        engine.resume();
+        engine.advance(Duration::from_millis(100)).unwrap();
+        assert_eq!(engine.time(), Duration::from_millis(200));
+        assert_eq!(mock.key_color(Key::F1), Color::RED);
+
+        engine.stop();
+        engine.advance(Duration::from_secs(1)).unwrap();
+        assert_eq!(engine.frames(), 3);
+    }
+
+    #[test]
+    fn errors() {
+        let (mock, mut engine) = headless(10);
+        engine.start().unwrap();
+        mock.disconnect();
+        assert_eq!(
+            engine.advance(Duration::from_millis(100)),
+            Err(Error::ConnectionLost)
+        );
+    }
+
+    #[test]
+    fn background_thread() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        let mut engine = Engine::new(Arc::new(sdk), 100);
+        engine.set_effect(|_: Duration, frame: &mut Bitmap| frame.fill(Color::GREEN));
+        engine.start();
+        thread::sleep(Duration::from_millis(100));
+        engine.stop();
+
+        let frames = engine.frames();
+        assert!(frames > 1);
+        assert_eq!(engine.state(), EngineState::Stopped);
+        assert_eq!(engine.last_error(), None);
+        assert_eq!(mock.key_color(Key::A), Color::GREEN);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(engine.frames(), frames);
+    }
+
+    #[test]
+    fn stops_when_frames_are_slow() {
+        let sdk = Sdk::with_backend(MockBackend::new()).unwrap();
+        let mut engine = Engine::new(Arc::new(sdk), 100);
+        // Every frame takes longer than the frame interval, so the engine is always behind.
+        engine.set_effect(|_: Duration, _: &mut Bitmap| thread::sleep(Duration::from_millis(20)));
+        engine.start();
+        thread::sleep(Duration::from_millis(50));
+        engine.set_frame_rate(50);
+        engine.stop();
+        assert_eq!(engine.state(), EngineState::Stopped);
+    }
+}
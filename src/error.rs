@@ -0,0 +1,36 @@
+use std::fmt;
+
+/// The ways in which a call to the SDK can fail.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The SDK hasn't been initialized, or has already been shut down.
+    NotInitialized,
+    /// The connection to Logitech Gaming Software is broken, or it isn't running.
+    ConnectionLost,
+    /// There's already an instance of the SDK running.
+    AlreadyRunning,
+    /// An argument was outside the range accepted by the SDK.
+    InvalidArgument,
+    /// A duration was too long to pass to the SDK, which takes a 32-bit number of milliseconds.
+    DurationTooLong,
+    /// A name, path or label contained a null byte, so it couldn't be passed to the SDK.
+    NulInName,
+    /// The backend doesn't support this function.
+    Unsupported,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Error::NotInitialized => "the SDK hasn't been initialized",
+            Error::ConnectionLost => "the connection to the SDK has been lost",
+            Error::AlreadyRunning => "there is already an instance of the SDK running",
+            Error::InvalidArgument => "an argument was outside the range accepted by the SDK",
+            Error::DurationTooLong => "duration is too long to pass to the SDK",
+            Error::NulInName => "string passed to the SDK contains a null byte",
+            Error::Unsupported => "the backend doesn't support this function",
+        })
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,577 @@
+mod font;
+
+use super::preview::label;
+use super::{Color, EngineState, HeadlessEngine, Key, LightingBackend, Snapshot};
+use std::io::{self, Write};
+
+/// The position and size of each key in the bitmap, in units of the width of a letter key,
+/// on a full-size ANSI keyboard.
+#[rustfmt::skip]
+const LAYOUT: [(Key, f64, f64, f64, f64); 104] = [
+    (Key::Esc, 0.0, 0.0, 1.0, 1.0),
+    (Key::F1, 2.0, 0.0, 1.0, 1.0),
+    (Key::F2, 3.0, 0.0, 1.0, 1.0),
+    (Key::F3, 4.0, 0.0, 1.0, 1.0),
+    (Key::F4, 5.0, 0.0, 1.0, 1.0),
+    (Key::F5, 6.5, 0.0, 1.0, 1.0),
+    (Key::F6, 7.5, 0.0, 1.0, 1.0),
+    (Key::F7, 8.5, 0.0, 1.0, 1.0),
+    (Key::F8, 9.5, 0.0, 1.0, 1.0),
+    (Key::F9, 11.0, 0.0, 1.0, 1.0),
+    (Key::F10, 12.0, 0.0, 1.0, 1.0),
+    (Key::F11, 13.0, 0.0, 1.0, 1.0),
+    (Key::F12, 14.0, 0.0, 1.0, 1.0),
+    (Key::PrintScreen, 15.25, 0.0, 1.0, 1.0),
+    (Key::ScrollLock, 16.25, 0.0, 1.0, 1.0),
+    (Key::PauseBreak, 17.25, 0.0, 1.0, 1.0),
+
+    (Key::Tilde, 0.0, 1.5, 1.0, 1.0),
+    (Key::One, 1.0, 1.5, 1.0, 1.0),
+    (Key::Two, 2.0, 1.5, 1.0, 1.0),
+    (Key::Three, 3.0, 1.5, 1.0, 1.0),
+    (Key::Four, 4.0, 1.5, 1.0, 1.0),
+    (Key::Five, 5.0, 1.5, 1.0, 1.0),
+    (Key::Six, 6.0, 1.5, 1.0, 1.0),
+    (Key::Seven, 7.0, 1.5, 1.0, 1.0),
+    (Key::Eight, 8.0, 1.5, 1.0, 1.0),
+    (Key::Nine, 9.0, 1.5, 1.0, 1.0),
+    (Key::Zero, 10.0, 1.5, 1.0, 1.0),
+    (Key::Minus, 11.0, 1.5, 1.0, 1.0),
+    (Key::Equals, 12.0, 1.5, 1.0, 1.0),
+    (Key::Backspace, 13.0, 1.5, 2.0, 1.0),
+    (Key::Insert, 15.25, 1.5, 1.0, 1.0),
+    (Key::Home, 16.25, 1.5, 1.0, 1.0),
+    (Key::PageUp, 17.25, 1.5, 1.0, 1.0),
+    (Key::NumLock, 18.5, 1.5, 1.0, 1.0),
+    (Key::NumSlash, 19.5, 1.5, 1.0, 1.0),
+    (Key::NumAsterisk, 20.5, 1.5, 1.0, 1.0),
+    (Key::NumMinus, 21.5, 1.5, 1.0, 1.0),
+
+    (Key::Tab, 0.0, 2.5, 1.5, 1.0),
+    (Key::Q, 1.5, 2.5, 1.0, 1.0),
+    (Key::W, 2.5, 2.5, 1.0, 1.0),
+    (Key::E, 3.5, 2.5, 1.0, 1.0),
+    (Key::R, 4.5, 2.5, 1.0, 1.0),
+    (Key::T, 5.5, 2.5, 1.0, 1.0),
+    (Key::Y, 6.5, 2.5, 1.0, 1.0),
+    (Key::U, 7.5, 2.5, 1.0, 1.0),
+    (Key::I, 8.5, 2.5, 1.0, 1.0),
+    (Key::O, 9.5, 2.5, 1.0, 1.0),
+    (Key::P, 10.5, 2.5, 1.0, 1.0),
+    (Key::OpenBracket, 11.5, 2.5, 1.0, 1.0),
+    (Key::CloseBracket, 12.5, 2.5, 1.0, 1.0),
+    (Key::Backslash, 13.5, 2.5, 1.5, 1.0),
+    (Key::KeyboardDelete, 15.25, 2.5, 1.0, 1.0),
+    (Key::End, 16.25, 2.5, 1.0, 1.0),
+    (Key::PageDown, 17.25, 2.5, 1.0, 1.0),
+    (Key::NumSeven, 18.5, 2.5, 1.0, 1.0),
+    (Key::NumEight, 19.5, 2.5, 1.0, 1.0),
+    (Key::NumNine, 20.5, 2.5, 1.0, 1.0),
+    (Key::NumPlus, 21.5, 2.5, 1.0, 2.0),
+
+    (Key::CapsLock, 0.0, 3.5, 1.75, 1.0),
+    (Key::A, 1.75, 3.5, 1.0, 1.0),
+    (Key::S, 2.75, 3.5, 1.0, 1.0),
+    (Key::D, 3.75, 3.5, 1.0, 1.0),
+    (Key::F, 4.75, 3.5, 1.0, 1.0),
+    (Key::G, 5.75, 3.5, 1.0, 1.0),
+    (Key::H, 6.75, 3.5, 1.0, 1.0),
+    (Key::J, 7.75, 3.5, 1.0, 1.0),
+    (Key::K, 8.75, 3.5, 1.0, 1.0),
+    (Key::L, 9.75, 3.5, 1.0, 1.0),
+    (Key::Semicolon, 10.75, 3.5, 1.0, 1.0),
+    (Key::Apostrophe, 11.75, 3.5, 1.0, 1.0),
+    (Key::Enter, 12.75, 3.5, 2.25, 1.0),
+    (Key::NumFour, 18.5, 3.5, 1.0, 1.0),
+    (Key::NumFive, 19.5, 3.5, 1.0, 1.0),
+    (Key::NumSix, 20.5, 3.5, 1.0, 1.0),
+
+    (Key::LeftShift, 0.0, 4.5, 2.25, 1.0),
+    (Key::Z, 2.25, 4.5, 1.0, 1.0),
+    (Key::X, 3.25, 4.5, 1.0, 1.0),
+    (Key::C, 4.25, 4.5, 1.0, 1.0),
+    (Key::V, 5.25, 4.5, 1.0, 1.0),
+    (Key::B, 6.25, 4.5, 1.0, 1.0),
+    (Key::N, 7.25, 4.5, 1.0, 1.0),
+    (Key::M, 8.25, 4.5, 1.0, 1.0),
+    (Key::Comma, 9.25, 4.5, 1.0, 1.0),
+    (Key::Period, 10.25, 4.5, 1.0, 1.0),
+    (Key::ForwardSlash, 11.25, 4.5, 1.0, 1.0),
+    (Key::RightShift, 12.25, 4.5, 2.75, 1.0),
+    (Key::ArrowUp, 16.25, 4.5, 1.0, 1.0),
+    (Key::NumOne, 18.5, 4.5, 1.0, 1.0),
+    (Key::NumTwo, 19.5, 4.5, 1.0, 1.0),
+    (Key::NumThree, 20.5, 4.5, 1.0, 1.0),
+    (Key::NumEnter, 21.5, 4.5, 1.0, 2.0),
+
+    (Key::LeftControl, 0.0, 5.5, 1.25, 1.0),
+    (Key::LeftWindows, 1.25, 5.5, 1.25, 1.0),
+    (Key::LeftAlt, 2.5, 5.5, 1.25, 1.0),
+    (Key::Space, 3.75, 5.5, 6.25, 1.0),
+    (Key::RightAlt, 10.0, 5.5, 1.25, 1.0),
+    (Key::RightWindows, 11.25, 5.5, 1.25, 1.0),
+    (Key::ApplicationSelect, 12.5, 5.5, 1.25, 1.0),
+    (Key::RightControl, 13.75, 5.5, 1.25, 1.0),
+    (Key::ArrowLeft, 15.25, 5.5, 1.0, 1.0),
+    (Key::ArrowDown, 16.25, 5.5, 1.0, 1.0),
+    (Key::ArrowRight, 17.25, 5.5, 1.0, 1.0),
+    (Key::NumZero, 18.5, 5.5, 2.0, 1.0),
+    (Key::NumPeriod, 20.5, 5.5, 1.0, 1.0),
+];
+
+/// The size of the keyboard, in the same units as `LAYOUT`.
+const LAYOUT_WIDTH: f64 = 22.5;
+const LAYOUT_HEIGHT: f64 = 6.5;
+
+/// The color of the keyboard behind the keys.
+const CASE: Color = Color::from_rgb8(0x28, 0x28, 0x28);
+/// An opaque pixel of the color of the keyboard's case.
+const CASE_PIXEL: [u8; 4] = [CASE.red, CASE.green, CASE.blue, 255];
+
+fn png_error(err: png::EncodingError) -> io::Error {
+    match err {
+        png::EncodingError::IoError(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+fn gif_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+/// Escapes `text` to be put in an SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+}
+
+/// A rectangle of pixels, from the top left corner to just past the bottom right corner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Rect {
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+/// Draws a keyboard with each key colored by the lighting of per-key devices, as a PNG, SVG, or animated GIF or PNG.
+///
+/// Keys are drawn at their physical size on a full-size ANSI keyboard, and labelled with their names.
+/// Keys which aren't in the bitmap grid, like the G keys, aren't drawn.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::effects::RainbowWave;
+/// use lightsync::mock::MockBackend;
+/// use lightsync::{Color, HeadlessEngine, Key, KeyboardImage, Sdk};
+/// use std::fs::{self, File};
+/// use std::sync::Arc;
+///
+/// let sdk = Sdk::with_backend(MockBackend::new())?;
+/// sdk.set_lighting_for_key(Key::W, Color::RED)?;
+/// let image = KeyboardImage::new();
+/// image.write_png(&sdk.snapshot(), File::create("keyboard.png")?)?;
+/// fs::write("keyboard.svg", image.to_svg(&sdk.snapshot()))?;
+///
+/// // Record 2 seconds of an effect.
+/// let mut engine = HeadlessEngine::new(Arc::new(sdk), 25);
+/// engine.set_effect(RainbowWave::new());
+/// image.write_gif(&mut engine, 50, File::create("rainbow.gif")?)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyboardImage {
+    scale: u32,
+    labels: bool,
+}
+
+impl KeyboardImage {
+    /// Creates an image with labelled keys, where letter keys are 40 pixels wide.
+    pub fn new() -> KeyboardImage {
+        KeyboardImage {
+            scale: 40,
+            labels: true,
+        }
+    }
+
+    /// Sets the width of letter keys in pixels, which the rest of the keyboard is sized by.
+    ///
+    /// # Panics
+    /// Panics if `scale` is less than 8 or more than 1000.
+    pub fn with_scale(mut self, scale: u32) -> KeyboardImage {
+        assert!((8..=1000).contains(&scale), "invalid scale {}", scale);
+        self.scale = scale;
+        self
+    }
+
+    /// Sets whether keys are labelled.
+    pub fn with_labels(mut self, labels: bool) -> KeyboardImage {
+        self.labels = labels;
+        self
+    }
+
+    /// Returns the space left around the keys, in pixels.
+    fn margin(&self) -> u32 {
+        self.scale / 4
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        (LAYOUT_WIDTH * self.scale as f64) as u32 + 2 * self.margin()
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        (LAYOUT_HEIGHT * self.scale as f64) as u32 + 2 * self.margin()
+    }
+
+    /// Returns the pixels covered by a key at `x` and `y` with a width of `w` and height of `h`, in layout units.
+    fn rect(&self, (x, y, w, h): (f64, f64, f64, f64)) -> Rect {
+        let scale = self.scale as f64;
+        let gap = (self.scale / 20).max(1);
+        let pixel = |units: f64| (units * scale).round() as u32 + self.margin();
+        Rect {
+            left: pixel(x) + gap,
+            top: pixel(y) + gap,
+            right: pixel(x + w) - gap,
+            bottom: pixel(y + h) - gap,
+        }
+    }
+
+    /// Returns the radius of the corners of keys, in pixels.
+    fn radius(&self) -> u32 {
+        self.scale / 10
+    }
+
+    /// Draws the keyboard as RGBA pixels, from the top left to the bottom right.
+    pub fn to_rgba(&self, snapshot: &Snapshot) -> Vec<u8> {
+        let (width, height) = (self.width(), self.height());
+        let mut pixels = CASE_PIXEL.repeat((width * height) as usize);
+        let mut set = |x: u32, y: u32, color: Color| {
+            let i = ((y * width + x) * 4) as usize;
+            pixels[i..i + 3].copy_from_slice(&[color.red, color.green, color.blue]);
+        };
+
+        for &(key, x, y, w, h) in LAYOUT.iter() {
+            let rect = self.rect((x, y, w, h));
+            let color = snapshot.key_color(key);
+            let radius = self.radius() as i64;
+            for py in rect.top..rect.bottom {
+                for px in rect.left..rect.right {
+                    // Distance into each rounded corner, or 0 outside of them.
+                    let dx = (rect.left as i64 + radius - px as i64)
+                        .max(px as i64 - (rect.right as i64 - 1 - radius))
+                        .max(0);
+                    let dy = (rect.top as i64 + radius - py as i64)
+                        .max(py as i64 - (rect.bottom as i64 - 1 - radius))
+                        .max(0);
+                    if dx * dx + dy * dy <= radius * radius {
+                        set(px, py, color);
+                    }
+                }
+            }
+
+            if !self.labels {
+                continue;
+            }
+            let text = if color.is_light() {
+                Color::BLACK
+            } else {
+                Color::WHITE
+            };
+            let size = (self.scale / 40).max(1);
+            let label = label(key);
+            let text_width = label.len() as u32 * (font::WIDTH as u32 + 1) * size - size;
+            let left = (rect.left + rect.right).saturating_sub(text_width) / 2;
+            let top = (rect.top + rect.bottom).saturating_sub(font::HEIGHT as u32 * size) / 2;
+            for (i, c) in label.chars().enumerate() {
+                let rows = font::glyph(c).unwrap_or_default();
+                let glyph_left = left + i as u32 * (font::WIDTH as u32 + 1) * size;
+                for (row, bits) in rows.iter().enumerate() {
+                    for col in 0..font::WIDTH {
+                        if bits & (1 << (font::WIDTH - 1 - col)) == 0 {
+                            continue;
+                        }
+                        for sy in 0..size {
+                            for sx in 0..size {
+                                let px = glyph_left + col as u32 * size + sx;
+                                let py = top + row as u32 * size + sy;
+                                // Labels too long for small keys are cut off at the key's edges.
+                                if (rect.left..rect.right).contains(&px)
+                                    && (rect.top..rect.bottom).contains(&py)
+                                {
+                                    set(px, py, text);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    /// Writes the keyboard to `out` as a PNG.
+    ///
+    /// A `Bitmap` can be drawn by converting it to a `Snapshot` with `Snapshot::from()`.
+    ///
+    /// # Errors
+    /// Returns any error from writing to `out`.
+    pub fn write_png(&self, snapshot: &Snapshot, out: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer
+            .write_image_data(&self.to_rgba(snapshot))
+            .map_err(png_error)?;
+        writer.finish().map_err(png_error)
+    }
+
+    /// Draws the keyboard as an SVG.
+    pub fn to_svg(&self, snapshot: &Snapshot) -> String {
+        let (width, height) = (self.width(), self.height());
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height
+        );
+        svg.push_str(&format!(
+            "<rect width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\"/>\n",
+            width,
+            height,
+            self.radius(),
+            CASE
+        ));
+        for &(key, x, y, w, h) in LAYOUT.iter() {
+            let rect = self.rect((x, y, w, h));
+            let color = snapshot.key_color(key);
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\"/>\n",
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                self.radius(),
+                color
+            ));
+            if self.labels {
+                let text = if color.is_light() {
+                    "#000000"
+                } else {
+                    "#ffffff"
+                };
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" fill=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" \
+                     text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    (rect.left + rect.right) as f64 / 2.0,
+                    (rect.top + rect.bottom) as f64 / 2.0,
+                    text,
+                    self.scale * 3 / 10,
+                    escape(label(key))
+                ));
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Runs `engine` for `frames` frames and returns the lighting on each of them.
+    ///
+    /// The engine is started if it's stopped, and otherwise the first frame is its current lighting.
+    fn record<B: LightingBackend>(
+        &self,
+        engine: &mut HeadlessEngine<B>,
+        frames: usize,
+    ) -> io::Result<Vec<Snapshot>> {
+        if engine.state() == EngineState::Stopped {
+            engine.start().map_err(io::Error::other)?;
+        }
+        let mut snapshots = Vec::with_capacity(frames);
+        for i in 0..frames {
+            if i > 0 {
+                engine
+                    .advance(engine.frame_interval())
+                    .map_err(io::Error::other)?;
+            }
+            snapshots.push(engine.sdk().snapshot());
+        }
+        Ok(snapshots)
+    }
+
+    /// Runs `engine` for `frames` frames, and writes them to `out` as a looping animated GIF.
+    ///
+    /// The engine is started if it's stopped, and otherwise the first frame is its current lighting.
+    /// GIFs can only have delays in hundredths of a second, so the frame rate is rounded to fit.
+    ///
+    /// # Errors
+    /// Returns any error from writing to `out`, or an error of kind `io::ErrorKind::Other`
+    /// wrapping the `Error` if displaying a frame fails.
+    pub fn write_gif<B: LightingBackend>(
+        &self,
+        engine: &mut HeadlessEngine<B>,
+        frames: usize,
+        out: impl Write,
+    ) -> io::Result<()> {
+        let snapshots = self.record(engine, frames)?;
+        let (width, height) = (self.width() as u16, self.height() as u16);
+        let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+        let delay = (engine.frame_interval().as_millis() as f64 / 10.0).round() as u16;
+        for snapshot in &snapshots {
+            let mut frame =
+                gif::Frame::from_rgba_speed(width, height, &mut self.to_rgba(snapshot), 10);
+            frame.delay = delay.max(1);
+            encoder.write_frame(&frame).map_err(gif_error)?;
+        }
+        Ok(())
+    }
+
+    /// Runs `engine` for `frames` frames, and writes them to `out` as a looping animated PNG.
+    ///
+    /// The engine is started if it's stopped, and otherwise the first frame is its current lighting.
+    ///
+    /// # Errors
+    /// Returns any error from writing to `out`, or an error of kind `io::ErrorKind::Other`
+    /// wrapping the `Error` if displaying a frame fails.
+    pub fn write_apng<B: LightingBackend>(
+        &self,
+        engine: &mut HeadlessEngine<B>,
+        frames: usize,
+        out: impl Write,
+    ) -> io::Result<()> {
+        let snapshots = self.record(engine, frames)?;
+        let mut encoder = png::Encoder::new(out, self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(snapshots.len() as u32, 0)
+            .map_err(png_error)?;
+        let delay = engine.frame_interval().as_millis().min(u16::MAX as u128) as u16;
+        encoder.set_frame_delay(delay, 1000).map_err(png_error)?;
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        for snapshot in &snapshots {
+            writer
+                .write_image_data(&self.to_rgba(snapshot))
+                .map_err(png_error)?;
+        }
+        writer.finish().map_err(png_error)
+    }
+}
+
+impl Default for KeyboardImage {
+    fn default() -> KeyboardImage {
+        KeyboardImage::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+    use crate::{Bitmap, Sdk};
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn layout() {
+        let keys: HashSet<_> = LAYOUT.iter().map(|&(key, ..)| key).collect();
+        assert_eq!(keys.len(), LAYOUT.len());
+        assert!(Key::ALL
+            .iter()
+            .filter(|key| key.bitmap_position().is_some())
+            .all(|key| keys.contains(key)));
+
+        for &(key, x, y, w, h) in LAYOUT.iter() {
+            assert!(x + w <= LAYOUT_WIDTH && y + h <= LAYOUT_HEIGHT, "{:?}", key);
+            for c in label(key).chars() {
+                assert!(font::glyph(c).is_some(), "{:?}", c);
+            }
+        }
+    }
+
+    #[test]
+    fn pixels() {
+        let mut bitmap = Bitmap::new();
+        bitmap.set(Key::Esc, Color::RED);
+        let image = KeyboardImage::new().with_labels(false);
+        assert_eq!((image.width(), image.height()), (920, 280));
+
+        let pixels = image.to_rgba(&Snapshot::from(&bitmap));
+        assert_eq!(pixels.len(), 920 * 280 * 4);
+        let pixel = |x: usize, y: usize| &pixels[(y * 920 + x) * 4..][..4];
+        // The middle of Esc, its rounded corner, and the middle of F1.
+        assert_eq!(pixel(30, 30), [255, 0, 0, 255]);
+        assert_eq!(pixel(12, 12), [0x28, 0x28, 0x28, 255]);
+        assert_eq!(pixel(110, 30), [0, 0, 0, 255]);
+
+        // The labels of dark keys are white.
+        let pixels = KeyboardImage::new().to_rgba(&Snapshot::from(&bitmap));
+        assert!(pixels.chunks(4).any(|pixel| pixel == [255, 255, 255, 255]));
+
+        // Labels are kept inside their keys, even when the keys are too small for them.
+        let image = KeyboardImage::new().with_scale(8);
+        let unlabelled = image.with_labels(false).to_rgba(&Snapshot::default());
+        let labelled = image.to_rgba(&Snapshot::default());
+        let rects: Vec<_> = LAYOUT
+            .iter()
+            .map(|&(_, x, y, w, h)| image.rect((x, y, w, h)))
+            .collect();
+        for (i, (a, b)) in labelled.chunks(4).zip(unlabelled.chunks(4)).enumerate() {
+            let (x, y) = (i as u32 % image.width(), i as u32 / image.width());
+            if a != b {
+                assert!(rects.iter().any(|rect| (rect.left..rect.right).contains(&x)
+                    && (rect.top..rect.bottom).contains(&y)));
+            }
+        }
+    }
+
+    #[test]
+    fn svg() {
+        let mut bitmap = Bitmap::new();
+        bitmap.set(Key::Backslash, Color::WHITE);
+        let svg = KeyboardImage::new().to_svg(&Snapshot::from(&bitmap));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"920\""));
+        assert_eq!(svg.matches("<rect").count(), 105);
+        assert!(svg.contains(
+            "<rect x=\"552\" y=\"112\" width=\"56\" height=\"36\" rx=\"4\" fill=\"#ffffff\"/>\n\
+             <text x=\"580\" y=\"130\" fill=\"#000000\""
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(escape("<'&'>"), "&lt;&apos;&amp;&apos;&gt;");
+    }
+
+    #[test]
+    fn encoding() {
+        let image = KeyboardImage::new().with_scale(8);
+        let mut png = Vec::new();
+        image.write_png(&Snapshot::default(), &mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let sdk = Sdk::with_backend(MockBackend::new()).unwrap();
+        let mut engine = HeadlessEngine::new(Arc::new(sdk), 10);
+        engine.set_effect(|time: Duration, frame: &mut Bitmap| {
+            frame.set(
+                Key::Space,
+                Color::from_hsv(time.as_secs_f64() * 360.0, 1.0, 1.0),
+            )
+        });
+        let mut gif = Vec::new();
+        image.write_gif(&mut engine, 3, &mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(engine.frames(), 3);
+
+        let mut apng = Vec::new();
+        image.write_apng(&mut engine, 3, &mut apng).unwrap();
+        assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+        assert_eq!(engine.frames(), 5);
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Error, Key};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Whether a key was pressed or released.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+/// A key being pressed or released, to send to an effect with `Engine::key_event()`.
+///
+/// Key events can come from anywhere, like an OS keyboard hook, evdev, or a `Recording`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub state: KeyState,
+}
+
+impl KeyEvent {
+    /// Creates an event for `key` being pressed.
+    pub const fn press(key: Key) -> KeyEvent {
+        KeyEvent {
+            key,
+            state: KeyState::Pressed,
+        }
+    }
+
+    /// Creates an event for `key` being released.
+    pub const fn release(key: Key) -> KeyEvent {
+        KeyEvent {
+            key,
+            state: KeyState::Released,
+        }
+    }
+
+    /// Returns whether the key was pressed.
+    pub fn is_press(self) -> bool {
+        self.state == KeyState::Pressed
+    }
+}
+
+/// A list of key events and the times they happened, to replay with `HeadlessEngine::replay()`.
+///
+/// Recordings can be written to and parsed from text, with an event on each line made of the time
+/// in seconds, `press` or `release`, and the name of the key. Blank lines and lines starting with `#`
+/// are ignored.
+///
+/// # Example
+/// ```
+/// use lightsync::{Key, KeyEvent, Recording};
+/// use std::time::Duration;
+///
+/// let recording: Recording = "
+///     0.0 press H
+///     0.1 release H
+///     0.25 press I
+///     0.3 release I
+/// ".parse()?;
+/// assert_eq!(
+///     recording.events()[2],
+///     (Duration::from_millis(250), KeyEvent::press(Key::I))
+/// );
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Recording {
+    events: Vec<(Duration, KeyEvent)>,
+}
+
+impl Recording {
+    /// Creates an empty recording.
+    pub fn new() -> Recording {
+        Recording::default()
+    }
+
+    /// Adds `event` to the recording, happening `time` after the recording started.
+    ///
+    /// Events are kept in order of time, and events at the same time are kept in the order they were added.
+    pub fn push(&mut self, time: Duration, event: KeyEvent) {
+        let index = self.events.partition_point(|&(other, _)| other <= time);
+        self.events.insert(index, (time, event));
+    }
+
+    /// Returns the events in the recording, and the times they happened, in order of time.
+    pub fn events(&self) -> &[(Duration, KeyEvent)] {
+        &self.events
+    }
+
+    /// Returns the time of the last event in the recording.
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map_or(Duration::from_secs(0), |&(time, _)| time)
+    }
+}
+
+impl FromStr for Recording {
+    type Err = Error;
+
+    /// Parses a recording from text.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if any line isn't a valid event.
+    fn from_str(s: &str) -> Result<Recording, Error> {
+        let mut recording = Recording::new();
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<_> = line.split_whitespace().collect();
+            let (time, state, key) = match parts[..] {
+                [time, state, key] => (time, state, key),
+                _ => return Err(Error::InvalidArgument),
+            };
+            let time = time
+                .parse::<f64>()
+                .ok()
+                .and_then(|time| Duration::try_from_secs_f64(time).ok())
+                .ok_or(Error::InvalidArgument)?;
+            let state = match state {
+                "press" => KeyState::Pressed,
+                "release" => KeyState::Released,
+                _ => return Err(Error::InvalidArgument),
+            };
+            let event = KeyEvent {
+                key: key.parse()?,
+                state,
+            };
+            recording.push(time, event);
+        }
+        Ok(recording)
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (time, event) in &self.events {
+            let state = match event.state {
+                KeyState::Pressed => "press",
+                KeyState::Released => "release",
+            };
+            writeln!(f, "{} {} {:?}", time.as_secs_f64(), state, event.key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_order() {
+        let mut recording = Recording::new();
+        recording.push(Duration::from_secs(2), KeyEvent::press(Key::A));
+        recording.push(Duration::from_secs(1), KeyEvent::press(Key::B));
+        recording.push(Duration::from_secs(2), KeyEvent::release(Key::A));
+        assert_eq!(
+            recording.events(),
+            [
+                (Duration::from_secs(1), KeyEvent::press(Key::B)),
+                (Duration::from_secs(2), KeyEvent::press(Key::A)),
+                (Duration::from_secs(2), KeyEvent::release(Key::A)),
+            ]
+        );
+        assert_eq!(recording.duration(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn recording_text() {
+        let mut recording = Recording::new();
+        recording.push(Duration::from_millis(0), KeyEvent::press(Key::Space));
+        recording.push(Duration::from_millis(1500), KeyEvent::release(Key::Space));
+        let text = recording.to_string();
+        assert_eq!(text, "0 press Space\n1.5 release Space\n");
+        assert_eq!(text.parse(), Ok(recording));
+
+        assert_eq!("1 press".parse::<Recording>(), Err(Error::InvalidArgument));
+        assert_eq!(
+            "-1 press A".parse::<Recording>(),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!(
+            "1e30 press A".parse::<Recording>(),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!("1 tap A".parse::<Recording>(), Err(Error::InvalidArgument));
+        assert_eq!(
+            "1 press Nope".parse::<Recording>(),
+            Err(Error::InvalidArgument)
+        );
+    }
+}
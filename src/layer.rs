@@ -0,0 +1,431 @@
+use super::{Bitmap, Color, Effect, Key, KeyEvent};
+use std::fmt;
+use std::time::Duration;
+
+/// How a layer's colors are combined with the layers below it.
+///
+/// Wherever there's nothing below a layer, its colors are used as they are, whatever the blend mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// The layer's color replaces the one below.
+    #[default]
+    Normal,
+    /// The colors are added together, saturating at white.
+    Add,
+    /// The colors are multiplied together, which only ever darkens.
+    Multiply,
+    /// The inverse colors are multiplied together, which only ever lightens.
+    Screen,
+    /// The brightest of each channel is kept.
+    Max,
+}
+
+impl BlendMode {
+    /// Blends `above` onto `below`, as if `above` was fully opaque.
+    pub fn blend(self, below: Color, above: Color) -> Color {
+        let channel = |below: u8, above: u8| -> u8 {
+            let (below, above) = (below as u32, above as u32);
+            let blended = match self {
+                BlendMode::Normal => above,
+                BlendMode::Add => (below + above).min(255),
+                BlendMode::Multiply => (below * above + 127) / 255,
+                BlendMode::Screen => 255 - ((255 - below) * (255 - above) + 127) / 255,
+                BlendMode::Max => below.max(above),
+            };
+            blended as u8
+        };
+        Color::from_rgb8(
+            channel(below.red, above.red),
+            channel(below.green, above.green),
+            channel(below.blue, above.blue),
+        )
+    }
+}
+
+/// An effect in a `LayerStack`, along with how it's composited onto the layers below it.
+///
+/// Layers are rendered with the time since they were first composited, so a layer pushed
+/// in the middle of an animation still starts from its first frame.
+pub struct Layer {
+    effect: Box<dyn Effect>,
+    priority: i32,
+    opacity: f64,
+    /// The cells the layer is allowed to draw on, or `None` for all of them.
+    mask: Option<Box<[[bool; Bitmap::WIDTH]; Bitmap::HEIGHT]>>,
+    blend_mode: BlendMode,
+    /// The time of the first frame the layer was composited on.
+    start: Option<Duration>,
+}
+
+impl Layer {
+    /// Creates an opaque layer with a priority of 0, which can draw on every key and uses `BlendMode::Normal`.
+    pub fn new(effect: impl Effect + 'static) -> Layer {
+        Layer {
+            effect: Box::new(effect),
+            priority: 0,
+            opacity: 1.0,
+            mask: None,
+            blend_mode: BlendMode::Normal,
+            start: None,
+        }
+    }
+
+    /// Sets the priority of the layer. Layers with higher priorities are composited on top,
+    /// and layers with the same priority are composited in the order they were pushed.
+    pub fn with_priority(mut self, priority: i32) -> Layer {
+        self.set_priority(priority);
+        self
+    }
+
+    /// Sets the opacity of the layer from 0 to 1, which is multiplied by the alpha of each cell it draws.
+    ///
+    /// Opacities outside of 0 to 1 are clamped.
+    pub fn with_opacity(mut self, opacity: f64) -> Layer {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Only lets the layer draw on `keys`. Keys which aren't in the bitmap grid are ignored.
+    pub fn with_mask(mut self, keys: impl IntoIterator<Item = Key>) -> Layer {
+        self.set_mask(keys);
+        self
+    }
+
+    /// Sets how the layer is combined with the layers below it.
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Layer {
+        self.set_blend_mode(blend_mode);
+        self
+    }
+
+    /// Returns the priority of the layer.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Sets the priority of the layer.
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    /// Returns the opacity of the layer from 0 to 1.
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Sets the opacity of the layer from 0 to 1. Opacities outside of 0 to 1 are clamped.
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Only lets the layer draw on `keys`. Keys which aren't in the bitmap grid are ignored.
+    pub fn set_mask(&mut self, keys: impl IntoIterator<Item = Key>) {
+        let mut mask = Box::new([[false; Bitmap::WIDTH]; Bitmap::HEIGHT]);
+        for (row, col) in keys.into_iter().filter_map(Key::bitmap_position) {
+            mask[row][col] = true;
+        }
+        self.mask = Some(mask);
+    }
+
+    /// Lets the layer draw on every key again.
+    pub fn clear_mask(&mut self) {
+        self.mask = None;
+    }
+
+    /// Returns how the layer is combined with the layers below it.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets how the layer is combined with the layers below it.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Converts the stack's time to the layer's time.
+    fn layer_time(&mut self, time: Duration) -> Duration {
+        let start = self.start.get_or_insert(time);
+        // The stack's time going backwards means it's been restarted.
+        if time < *start {
+            *start = time;
+        }
+        time - *start
+    }
+
+    /// Renders the layer at `time`, and composites it onto `frame`.
+    fn composite(&mut self, time: Duration, frame: &mut Bitmap) {
+        if self.opacity == 0.0 {
+            return;
+        }
+        let time = self.layer_time(time);
+        let mut layer = Bitmap::new();
+        self.effect.render(time, &mut layer);
+
+        for row in 0..Bitmap::HEIGHT {
+            for col in 0..Bitmap::WIDTH {
+                let masked = matches!(&self.mask, Some(mask) if !mask[row][col]);
+                let alpha = layer.cell_alpha(row, col);
+                if masked || alpha == 0 {
+                    continue;
+                }
+                let coverage = alpha as f64 / 255.0 * self.opacity;
+                let above = layer.cell(row, col);
+                let below = frame.cell(row, col);
+                let below_alpha = frame.cell_alpha(row, col) as f64 / 255.0;
+                // Porter-Duff "over", so stacking translucent layers builds up opacity. The blend mode only
+                // applies as far as there's something below to blend with.
+                let alpha = coverage + below_alpha * (1.0 - coverage);
+                let blended = above.lerp(self.blend_mode.blend(below, above), below_alpha);
+                let color = below.lerp(blended, coverage / alpha);
+                frame.set_cell_with_alpha(row, col, color, (alpha * 255.0).round() as u8);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Layer")
+            .field("priority", &self.priority)
+            .field("opacity", &self.opacity)
+            .field("masked", &self.mask.is_some())
+            .field("blend_mode", &self.blend_mode)
+            .finish()
+    }
+}
+
+/// Identifies a layer pushed onto a `LayerStack`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LayerId(u64);
+
+/// A stack of layers which are composited into a single frame.
+///
+/// A `LayerStack` is itself an `Effect`, so it can be run by an `Engine`. To add and remove layers
+/// while the engine is running, share it as an `Arc<Mutex<LayerStack>>`, which is also an `Effect`.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{BlendMode, Bitmap, Color, Engine, Key, Layer, LayerStack, Sdk};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let stack = Arc::new(Mutex::new(LayerStack::new()));
+/// let mut engine = Engine::new(Arc::new(Sdk::init()?), 30);
+/// engine.set_effect(Arc::clone(&stack));
+/// engine.start();
+///
+/// let mut stack = stack.lock().unwrap();
+/// stack.push(Layer::new(|_: Duration, frame: &mut Bitmap| frame.fill(Color::BLUE)));
+/// let notification = stack.push(
+///     Layer::new(|_: Duration, frame: &mut Bitmap| frame.fill(Color::RED))
+///         .with_priority(10)
+///         .with_opacity(0.5)
+///         .with_mask(vec![Key::Esc, Key::F1, Key::F2, Key::F3, Key::F4])
+///         .with_blend_mode(BlendMode::Screen),
+/// );
+/// // ...
+/// stack.remove(notification);
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct LayerStack {
+    layers: Vec<(LayerId, Layer)>,
+    next_id: u64,
+}
+
+impl LayerStack {
+    /// Creates an empty stack, which composites to a transparent frame.
+    pub fn new() -> LayerStack {
+        LayerStack::default()
+    }
+
+    /// Adds `layer` to the stack, and returns an id to change or remove it with.
+    pub fn push(&mut self, layer: Layer) -> LayerId {
+        let id = LayerId(self.next_id);
+        self.next_id += 1;
+        self.layers.push((id, layer));
+        id
+    }
+
+    /// Removes the layer with the id `id` from the stack, and returns it.
+    ///
+    /// Returns `None` if it's already been removed.
+    pub fn remove(&mut self, id: LayerId) -> Option<Layer> {
+        let index = self.layers.iter().position(|(layer, _)| *layer == id)?;
+        Some(self.layers.remove(index).1)
+    }
+
+    /// Returns the layer with the id `id`, or `None` if it's been removed.
+    pub fn get(&self, id: LayerId) -> Option<&Layer> {
+        self.layers
+            .iter()
+            .find(|(layer, _)| *layer == id)
+            .map(|(_, layer)| layer)
+    }
+
+    /// Returns the layer with the id `id` to change, or `None` if it's been removed.
+    pub fn get_mut(&mut self, id: LayerId) -> Option<&mut Layer> {
+        self.layers
+            .iter_mut()
+            .find(|(layer, _)| *layer == id)
+            .map(|(_, layer)| layer)
+    }
+
+    /// Returns the number of layers in the stack.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns whether the stack has no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Removes every layer from the stack.
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    /// Renders every layer at `time` and composites them onto `frame`, from the lowest priority to the highest.
+    pub fn composite(&mut self, time: Duration, frame: &mut Bitmap) {
+        // A stable sort keeps layers with the same priority in the order they were pushed.
+        self.layers.sort_by_key(|(_, layer)| layer.priority);
+        for (_, layer) in &mut self.layers {
+            layer.composite(time, frame);
+        }
+    }
+}
+
+impl Effect for LayerStack {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        self.composite(time, frame);
+    }
+
+    /// Sends `event` to every layer.
+    fn key_event(&mut self, time: Duration, event: KeyEvent) {
+        for (_, layer) in &mut self.layers {
+            let time = layer.layer_time(time);
+            layer.effect.key_event(time, event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(color: Color) -> impl Effect {
+        move |_: Duration, frame: &mut Bitmap| frame.fill(color)
+    }
+
+    fn composite(stack: &mut LayerStack) -> Bitmap {
+        let mut frame = Bitmap::new();
+        stack.composite(Duration::from_secs(0), &mut frame);
+        frame
+    }
+
+    #[test]
+    fn blend_modes() {
+        let below = Color::from_rgb8(200, 100, 0);
+        let above = Color::from_rgb8(100, 100, 255);
+        assert_eq!(BlendMode::Normal.blend(below, above), above);
+        assert_eq!(
+            BlendMode::Add.blend(below, above),
+            Color::from_rgb8(255, 200, 255)
+        );
+        assert_eq!(
+            BlendMode::Multiply.blend(below, above),
+            Color::from_rgb8(78, 39, 0)
+        );
+        assert_eq!(
+            BlendMode::Screen.blend(below, above),
+            Color::from_rgb8(222, 161, 255)
+        );
+        assert_eq!(
+            BlendMode::Max.blend(below, above),
+            Color::from_rgb8(200, 100, 255)
+        );
+        assert_eq!(BlendMode::Multiply.blend(below, Color::WHITE), below);
+        assert_eq!(BlendMode::Screen.blend(below, Color::BLACK), below);
+    }
+
+    #[test]
+    fn priorities() {
+        let mut stack = LayerStack::new();
+        stack.push(Layer::new(solid(Color::RED)).with_priority(1));
+        let green = stack.push(Layer::new(solid(Color::GREEN)));
+        assert_eq!(composite(&mut stack).get(Key::A), Some(Color::RED));
+
+        stack.get_mut(green).unwrap().set_priority(2);
+        assert_eq!(composite(&mut stack).get(Key::A), Some(Color::GREEN));
+
+        // Layers with the same priority are composited in the order they were pushed.
+        stack.push(Layer::new(solid(Color::BLUE)).with_priority(2));
+        assert_eq!(composite(&mut stack).get(Key::A), Some(Color::BLUE));
+
+        assert!(stack.remove(green).is_some());
+        assert!(stack.remove(green).is_none());
+        assert_eq!(stack.len(), 2);
+    }
+
+    #[test]
+    fn opacity_and_masks() {
+        let mut stack = LayerStack::new();
+        stack.push(Layer::new(solid(Color::BLACK)));
+        stack.push(
+            Layer::new(solid(Color::WHITE))
+                .with_opacity(0.5)
+                .with_mask(vec![Key::A, Key::G1]),
+        );
+        let frame = composite(&mut stack);
+        assert_eq!(frame.get(Key::A), Some(Color::from_rgb8(128, 128, 128)));
+        assert_eq!(frame.alpha(Key::A), Some(255));
+        assert_eq!(frame.get(Key::B), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn transparency() {
+        let mut stack = LayerStack::new();
+        assert_eq!(composite(&mut stack), Bitmap::new());
+
+        // Nothing below a layer means its colors are used as they are.
+        stack.push(
+            Layer::new(|_: Duration, frame: &mut Bitmap| {
+                frame.set_with_alpha(Key::A, Color::RED, 128)
+            })
+            .with_blend_mode(BlendMode::Multiply),
+        );
+        let frame = composite(&mut stack);
+        assert_eq!(frame.get(Key::A), Some(Color::RED));
+        assert_eq!(frame.alpha(Key::A), Some(128));
+        assert_eq!(frame.alpha(Key::B), Some(0));
+
+        stack.push(Layer::new(|_: Duration, frame: &mut Bitmap| {
+            frame.set_with_alpha(Key::A, Color::BLUE, 128)
+        }));
+        let frame = composite(&mut stack);
+        // The red below is only half there, so the blue covers up more than half of it.
+        assert_eq!(frame.get(Key::A), Some(Color::from_rgb8(85, 0, 170)));
+        assert_eq!(frame.alpha(Key::A), Some(192));
+    }
+
+    #[test]
+    fn layer_time() {
+        let mut stack = LayerStack::new();
+        let mut frame = Bitmap::new();
+        stack.composite(Duration::from_secs(5), &mut frame);
+        stack.push(Layer::new(|time: Duration, frame: &mut Bitmap| {
+            if time == Duration::from_secs(1) {
+                frame.set(Key::A, Color::RED);
+            }
+        }));
+        stack.composite(Duration::from_secs(5), &mut frame);
+        stack.composite(Duration::from_secs(6), &mut frame);
+        assert_eq!(frame.get(Key::A), Some(Color::RED));
+
+        // The stack's time going back to before the layer started means the stack was restarted.
+        let mut frame = Bitmap::new();
+        stack.composite(Duration::from_secs(0), &mut frame);
+        stack.composite(Duration::from_secs(1), &mut frame);
+        assert_eq!(frame.get(Key::A), Some(Color::RED));
+    }
+}
@@ -0,0 +1,739 @@
+use super::Key;
+use phf::phf_map;
+
+/// The layout of a keyboard, which decides the character typed by each key.
+///
+/// Characters which are typed with AltGr, like `@` on a German keyboard, aren't mapped to any key.
+/// Neither are characters on the extra key next to the left shift on ISO keyboards, since there's no `Key` for it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyboardLayout {
+    /// US QWERTY.
+    #[default]
+    Us,
+    /// UK QWERTY.
+    Uk,
+    /// German QWERTZ.
+    German,
+    /// French AZERTY.
+    French,
+    /// US Dvorak.
+    Dvorak,
+    /// US Colemak.
+    Colemak,
+}
+
+impl KeyboardLayout {
+    /// Returns the key which types `char` on this layout, and whether shift needs to be held.
+    ///
+    /// Returns `None` if `char` can't be typed with a single key and shift.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::{Key, KeyboardLayout};
+    ///
+    /// assert_eq!(KeyboardLayout::Us.key_for_char('y'), Some((Key::Y, false)));
+    /// assert_eq!(KeyboardLayout::German.key_for_char('Y'), Some((Key::Z, true)));
+    /// assert_eq!(KeyboardLayout::German.key_for_char('@'), None);
+    /// ```
+    pub fn key_for_char(self, char: char) -> Option<(Key, bool)> {
+        let map = match self {
+            KeyboardLayout::Us => &US,
+            KeyboardLayout::Uk => &UK,
+            KeyboardLayout::German => &GERMAN,
+            KeyboardLayout::French => &FRENCH,
+            KeyboardLayout::Dvorak => &DVORAK,
+            KeyboardLayout::Colemak => &COLEMAK,
+        };
+        map.get(&char).copied()
+    }
+}
+
+/// A US ANSI layout.
+static US: phf::Map<char, (Key, bool)> = phf_map! {
+    '`' => (Key::Tilde, false),
+    '~' => (Key::Tilde, true),
+    '1' => (Key::One, false),
+    '!' => (Key::One, true),
+    '2' => (Key::Two, false),
+    '@' => (Key::Two, true),
+    '3' => (Key::Three, false),
+    '#' => (Key::Three, true),
+    '4' => (Key::Four, false),
+    '$' => (Key::Four, true),
+    '5' => (Key::Five, false),
+    '%' => (Key::Five, true),
+    '6' => (Key::Six, false),
+    '^' => (Key::Six, true),
+    '7' => (Key::Seven, false),
+    '&' => (Key::Seven, true),
+    '8' => (Key::Eight, false),
+    '*' => (Key::Eight, true),
+    '9' => (Key::Nine, false),
+    '(' => (Key::Nine, true),
+    '0' => (Key::Zero, false),
+    ')' => (Key::Zero, true),
+    '-' => (Key::Minus, false),
+    '_' => (Key::Minus, true),
+    '=' => (Key::Equals, false),
+    '+' => (Key::Equals, true),
+    '[' => (Key::OpenBracket, false),
+    '{' => (Key::OpenBracket, true),
+    ']' => (Key::CloseBracket, false),
+    '}' => (Key::CloseBracket, true),
+    '\\' => (Key::Backslash, false),
+    '|' => (Key::Backslash, true),
+    ';' => (Key::Semicolon, false),
+    ':' => (Key::Semicolon, true),
+    '\'' => (Key::Apostrophe, false),
+    '"' => (Key::Apostrophe, true),
+    ',' => (Key::Comma, false),
+    '<' => (Key::Comma, true),
+    '.' => (Key::Period, false),
+    '>' => (Key::Period, true),
+    '/' => (Key::ForwardSlash, false),
+    '?' => (Key::ForwardSlash, true),
+    'q' => (Key::Q, false),
+    'Q' => (Key::Q, true),
+    'w' => (Key::W, false),
+    'W' => (Key::W, true),
+    'e' => (Key::E, false),
+    'E' => (Key::E, true),
+    'r' => (Key::R, false),
+    'R' => (Key::R, true),
+    't' => (Key::T, false),
+    'T' => (Key::T, true),
+    'y' => (Key::Y, false),
+    'Y' => (Key::Y, true),
+    'u' => (Key::U, false),
+    'U' => (Key::U, true),
+    'i' => (Key::I, false),
+    'I' => (Key::I, true),
+    'o' => (Key::O, false),
+    'O' => (Key::O, true),
+    'p' => (Key::P, false),
+    'P' => (Key::P, true),
+    'a' => (Key::A, false),
+    'A' => (Key::A, true),
+    's' => (Key::S, false),
+    'S' => (Key::S, true),
+    'd' => (Key::D, false),
+    'D' => (Key::D, true),
+    'f' => (Key::F, false),
+    'F' => (Key::F, true),
+    'g' => (Key::G, false),
+    'G' => (Key::G, true),
+    'h' => (Key::H, false),
+    'H' => (Key::H, true),
+    'j' => (Key::J, false),
+    'J' => (Key::J, true),
+    'k' => (Key::K, false),
+    'K' => (Key::K, true),
+    'l' => (Key::L, false),
+    'L' => (Key::L, true),
+    'z' => (Key::Z, false),
+    'Z' => (Key::Z, true),
+    'x' => (Key::X, false),
+    'X' => (Key::X, true),
+    'c' => (Key::C, false),
+    'C' => (Key::C, true),
+    'v' => (Key::V, false),
+    'V' => (Key::V, true),
+    'b' => (Key::B, false),
+    'B' => (Key::B, true),
+    'n' => (Key::N, false),
+    'N' => (Key::N, true),
+    'm' => (Key::M, false),
+    'M' => (Key::M, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+/// A UK ISO layout.
+static UK: phf::Map<char, (Key, bool)> = phf_map! {
+    '`' => (Key::Tilde, false),
+    '¬' => (Key::Tilde, true),
+    '1' => (Key::One, false),
+    '!' => (Key::One, true),
+    '2' => (Key::Two, false),
+    '"' => (Key::Two, true),
+    '3' => (Key::Three, false),
+    '£' => (Key::Three, true),
+    '4' => (Key::Four, false),
+    '$' => (Key::Four, true),
+    '5' => (Key::Five, false),
+    '%' => (Key::Five, true),
+    '6' => (Key::Six, false),
+    '^' => (Key::Six, true),
+    '7' => (Key::Seven, false),
+    '&' => (Key::Seven, true),
+    '8' => (Key::Eight, false),
+    '*' => (Key::Eight, true),
+    '9' => (Key::Nine, false),
+    '(' => (Key::Nine, true),
+    '0' => (Key::Zero, false),
+    ')' => (Key::Zero, true),
+    '-' => (Key::Minus, false),
+    '_' => (Key::Minus, true),
+    '=' => (Key::Equals, false),
+    '+' => (Key::Equals, true),
+    '[' => (Key::OpenBracket, false),
+    '{' => (Key::OpenBracket, true),
+    ']' => (Key::CloseBracket, false),
+    '}' => (Key::CloseBracket, true),
+    '#' => (Key::Backslash, false),
+    '~' => (Key::Backslash, true),
+    ';' => (Key::Semicolon, false),
+    ':' => (Key::Semicolon, true),
+    '\'' => (Key::Apostrophe, false),
+    '@' => (Key::Apostrophe, true),
+    ',' => (Key::Comma, false),
+    '<' => (Key::Comma, true),
+    '.' => (Key::Period, false),
+    '>' => (Key::Period, true),
+    '/' => (Key::ForwardSlash, false),
+    '?' => (Key::ForwardSlash, true),
+    'q' => (Key::Q, false),
+    'Q' => (Key::Q, true),
+    'w' => (Key::W, false),
+    'W' => (Key::W, true),
+    'e' => (Key::E, false),
+    'E' => (Key::E, true),
+    'r' => (Key::R, false),
+    'R' => (Key::R, true),
+    't' => (Key::T, false),
+    'T' => (Key::T, true),
+    'y' => (Key::Y, false),
+    'Y' => (Key::Y, true),
+    'u' => (Key::U, false),
+    'U' => (Key::U, true),
+    'i' => (Key::I, false),
+    'I' => (Key::I, true),
+    'o' => (Key::O, false),
+    'O' => (Key::O, true),
+    'p' => (Key::P, false),
+    'P' => (Key::P, true),
+    'a' => (Key::A, false),
+    'A' => (Key::A, true),
+    's' => (Key::S, false),
+    'S' => (Key::S, true),
+    'd' => (Key::D, false),
+    'D' => (Key::D, true),
+    'f' => (Key::F, false),
+    'F' => (Key::F, true),
+    'g' => (Key::G, false),
+    'G' => (Key::G, true),
+    'h' => (Key::H, false),
+    'H' => (Key::H, true),
+    'j' => (Key::J, false),
+    'J' => (Key::J, true),
+    'k' => (Key::K, false),
+    'K' => (Key::K, true),
+    'l' => (Key::L, false),
+    'L' => (Key::L, true),
+    'z' => (Key::Z, false),
+    'Z' => (Key::Z, true),
+    'x' => (Key::X, false),
+    'X' => (Key::X, true),
+    'c' => (Key::C, false),
+    'C' => (Key::C, true),
+    'v' => (Key::V, false),
+    'V' => (Key::V, true),
+    'b' => (Key::B, false),
+    'B' => (Key::B, true),
+    'n' => (Key::N, false),
+    'N' => (Key::N, true),
+    'm' => (Key::M, false),
+    'M' => (Key::M, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+/// A German QWERTZ layout.
+static GERMAN: phf::Map<char, (Key, bool)> = phf_map! {
+    '^' => (Key::Tilde, false),
+    '°' => (Key::Tilde, true),
+    '1' => (Key::One, false),
+    '!' => (Key::One, true),
+    '2' => (Key::Two, false),
+    '"' => (Key::Two, true),
+    '3' => (Key::Three, false),
+    '§' => (Key::Three, true),
+    '4' => (Key::Four, false),
+    '$' => (Key::Four, true),
+    '5' => (Key::Five, false),
+    '%' => (Key::Five, true),
+    '6' => (Key::Six, false),
+    '&' => (Key::Six, true),
+    '7' => (Key::Seven, false),
+    '/' => (Key::Seven, true),
+    '8' => (Key::Eight, false),
+    '(' => (Key::Eight, true),
+    '9' => (Key::Nine, false),
+    ')' => (Key::Nine, true),
+    '0' => (Key::Zero, false),
+    '=' => (Key::Zero, true),
+    'ß' => (Key::Minus, false),
+    '?' => (Key::Minus, true),
+    '´' => (Key::Equals, false),
+    '`' => (Key::Equals, true),
+    'ü' => (Key::OpenBracket, false),
+    'Ü' => (Key::OpenBracket, true),
+    '+' => (Key::CloseBracket, false),
+    '*' => (Key::CloseBracket, true),
+    '#' => (Key::Backslash, false),
+    '\'' => (Key::Backslash, true),
+    'ö' => (Key::Semicolon, false),
+    'Ö' => (Key::Semicolon, true),
+    'ä' => (Key::Apostrophe, false),
+    'Ä' => (Key::Apostrophe, true),
+    ',' => (Key::Comma, false),
+    ';' => (Key::Comma, true),
+    '.' => (Key::Period, false),
+    ':' => (Key::Period, true),
+    '-' => (Key::ForwardSlash, false),
+    '_' => (Key::ForwardSlash, true),
+    'q' => (Key::Q, false),
+    'Q' => (Key::Q, true),
+    'w' => (Key::W, false),
+    'W' => (Key::W, true),
+    'e' => (Key::E, false),
+    'E' => (Key::E, true),
+    'r' => (Key::R, false),
+    'R' => (Key::R, true),
+    't' => (Key::T, false),
+    'T' => (Key::T, true),
+    'u' => (Key::U, false),
+    'U' => (Key::U, true),
+    'i' => (Key::I, false),
+    'I' => (Key::I, true),
+    'o' => (Key::O, false),
+    'O' => (Key::O, true),
+    'p' => (Key::P, false),
+    'P' => (Key::P, true),
+    'a' => (Key::A, false),
+    'A' => (Key::A, true),
+    's' => (Key::S, false),
+    'S' => (Key::S, true),
+    'd' => (Key::D, false),
+    'D' => (Key::D, true),
+    'f' => (Key::F, false),
+    'F' => (Key::F, true),
+    'g' => (Key::G, false),
+    'G' => (Key::G, true),
+    'h' => (Key::H, false),
+    'H' => (Key::H, true),
+    'j' => (Key::J, false),
+    'J' => (Key::J, true),
+    'k' => (Key::K, false),
+    'K' => (Key::K, true),
+    'l' => (Key::L, false),
+    'L' => (Key::L, true),
+    'x' => (Key::X, false),
+    'X' => (Key::X, true),
+    'c' => (Key::C, false),
+    'C' => (Key::C, true),
+    'v' => (Key::V, false),
+    'V' => (Key::V, true),
+    'b' => (Key::B, false),
+    'B' => (Key::B, true),
+    'n' => (Key::N, false),
+    'N' => (Key::N, true),
+    'm' => (Key::M, false),
+    'M' => (Key::M, true),
+    'z' => (Key::Y, false),
+    'Z' => (Key::Y, true),
+    'y' => (Key::Z, false),
+    'Y' => (Key::Z, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+/// A French AZERTY layout.
+static FRENCH: phf::Map<char, (Key, bool)> = phf_map! {
+    '²' => (Key::Tilde, false),
+    '&' => (Key::One, false),
+    '1' => (Key::One, true),
+    'é' => (Key::Two, false),
+    '2' => (Key::Two, true),
+    '"' => (Key::Three, false),
+    '3' => (Key::Three, true),
+    '\'' => (Key::Four, false),
+    '4' => (Key::Four, true),
+    '(' => (Key::Five, false),
+    '5' => (Key::Five, true),
+    '-' => (Key::Six, false),
+    '6' => (Key::Six, true),
+    'è' => (Key::Seven, false),
+    '7' => (Key::Seven, true),
+    '_' => (Key::Eight, false),
+    '8' => (Key::Eight, true),
+    'ç' => (Key::Nine, false),
+    '9' => (Key::Nine, true),
+    'à' => (Key::Zero, false),
+    '0' => (Key::Zero, true),
+    ')' => (Key::Minus, false),
+    '°' => (Key::Minus, true),
+    '=' => (Key::Equals, false),
+    '+' => (Key::Equals, true),
+    '^' => (Key::OpenBracket, false),
+    '¨' => (Key::OpenBracket, true),
+    '$' => (Key::CloseBracket, false),
+    '£' => (Key::CloseBracket, true),
+    'ù' => (Key::Apostrophe, false),
+    '%' => (Key::Apostrophe, true),
+    '*' => (Key::Backslash, false),
+    'µ' => (Key::Backslash, true),
+    ',' => (Key::M, false),
+    '?' => (Key::M, true),
+    ';' => (Key::Comma, false),
+    '.' => (Key::Comma, true),
+    ':' => (Key::Period, false),
+    '/' => (Key::Period, true),
+    '!' => (Key::ForwardSlash, false),
+    '§' => (Key::ForwardSlash, true),
+    'e' => (Key::E, false),
+    'E' => (Key::E, true),
+    'r' => (Key::R, false),
+    'R' => (Key::R, true),
+    't' => (Key::T, false),
+    'T' => (Key::T, true),
+    'y' => (Key::Y, false),
+    'Y' => (Key::Y, true),
+    'u' => (Key::U, false),
+    'U' => (Key::U, true),
+    'i' => (Key::I, false),
+    'I' => (Key::I, true),
+    'o' => (Key::O, false),
+    'O' => (Key::O, true),
+    'p' => (Key::P, false),
+    'P' => (Key::P, true),
+    's' => (Key::S, false),
+    'S' => (Key::S, true),
+    'd' => (Key::D, false),
+    'D' => (Key::D, true),
+    'f' => (Key::F, false),
+    'F' => (Key::F, true),
+    'g' => (Key::G, false),
+    'G' => (Key::G, true),
+    'h' => (Key::H, false),
+    'H' => (Key::H, true),
+    'j' => (Key::J, false),
+    'J' => (Key::J, true),
+    'k' => (Key::K, false),
+    'K' => (Key::K, true),
+    'l' => (Key::L, false),
+    'L' => (Key::L, true),
+    'x' => (Key::X, false),
+    'X' => (Key::X, true),
+    'c' => (Key::C, false),
+    'C' => (Key::C, true),
+    'v' => (Key::V, false),
+    'V' => (Key::V, true),
+    'b' => (Key::B, false),
+    'B' => (Key::B, true),
+    'n' => (Key::N, false),
+    'N' => (Key::N, true),
+    'a' => (Key::Q, false),
+    'A' => (Key::Q, true),
+    'z' => (Key::W, false),
+    'Z' => (Key::W, true),
+    'q' => (Key::A, false),
+    'Q' => (Key::A, true),
+    'w' => (Key::Z, false),
+    'W' => (Key::Z, true),
+    'm' => (Key::Semicolon, false),
+    'M' => (Key::Semicolon, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+/// A US Dvorak layout.
+static DVORAK: phf::Map<char, (Key, bool)> = phf_map! {
+    '`' => (Key::Tilde, false),
+    '~' => (Key::Tilde, true),
+    '1' => (Key::One, false),
+    '!' => (Key::One, true),
+    '2' => (Key::Two, false),
+    '@' => (Key::Two, true),
+    '3' => (Key::Three, false),
+    '#' => (Key::Three, true),
+    '4' => (Key::Four, false),
+    '$' => (Key::Four, true),
+    '5' => (Key::Five, false),
+    '%' => (Key::Five, true),
+    '6' => (Key::Six, false),
+    '^' => (Key::Six, true),
+    '7' => (Key::Seven, false),
+    '&' => (Key::Seven, true),
+    '8' => (Key::Eight, false),
+    '*' => (Key::Eight, true),
+    '9' => (Key::Nine, false),
+    '(' => (Key::Nine, true),
+    '0' => (Key::Zero, false),
+    ')' => (Key::Zero, true),
+    '[' => (Key::Minus, false),
+    '{' => (Key::Minus, true),
+    ']' => (Key::Equals, false),
+    '}' => (Key::Equals, true),
+    '\'' => (Key::Q, false),
+    '"' => (Key::Q, true),
+    ',' => (Key::W, false),
+    '<' => (Key::W, true),
+    '.' => (Key::E, false),
+    '>' => (Key::E, true),
+    '/' => (Key::OpenBracket, false),
+    '?' => (Key::OpenBracket, true),
+    '=' => (Key::CloseBracket, false),
+    '+' => (Key::CloseBracket, true),
+    '\\' => (Key::Backslash, false),
+    '|' => (Key::Backslash, true),
+    '-' => (Key::Apostrophe, false),
+    '_' => (Key::Apostrophe, true),
+    ';' => (Key::Z, false),
+    ':' => (Key::Z, true),
+    'p' => (Key::R, false),
+    'P' => (Key::R, true),
+    'y' => (Key::T, false),
+    'Y' => (Key::T, true),
+    'f' => (Key::Y, false),
+    'F' => (Key::Y, true),
+    'g' => (Key::U, false),
+    'G' => (Key::U, true),
+    'c' => (Key::I, false),
+    'C' => (Key::I, true),
+    'r' => (Key::O, false),
+    'R' => (Key::O, true),
+    'l' => (Key::P, false),
+    'L' => (Key::P, true),
+    'a' => (Key::A, false),
+    'A' => (Key::A, true),
+    'o' => (Key::S, false),
+    'O' => (Key::S, true),
+    'e' => (Key::D, false),
+    'E' => (Key::D, true),
+    'u' => (Key::F, false),
+    'U' => (Key::F, true),
+    'i' => (Key::G, false),
+    'I' => (Key::G, true),
+    'd' => (Key::H, false),
+    'D' => (Key::H, true),
+    'h' => (Key::J, false),
+    'H' => (Key::J, true),
+    't' => (Key::K, false),
+    'T' => (Key::K, true),
+    'n' => (Key::L, false),
+    'N' => (Key::L, true),
+    's' => (Key::Semicolon, false),
+    'S' => (Key::Semicolon, true),
+    'q' => (Key::X, false),
+    'Q' => (Key::X, true),
+    'j' => (Key::C, false),
+    'J' => (Key::C, true),
+    'k' => (Key::V, false),
+    'K' => (Key::V, true),
+    'x' => (Key::B, false),
+    'X' => (Key::B, true),
+    'b' => (Key::N, false),
+    'B' => (Key::N, true),
+    'm' => (Key::M, false),
+    'M' => (Key::M, true),
+    'w' => (Key::Comma, false),
+    'W' => (Key::Comma, true),
+    'v' => (Key::Period, false),
+    'V' => (Key::Period, true),
+    'z' => (Key::ForwardSlash, false),
+    'Z' => (Key::ForwardSlash, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+/// A US Colemak layout.
+static COLEMAK: phf::Map<char, (Key, bool)> = phf_map! {
+    '`' => (Key::Tilde, false),
+    '~' => (Key::Tilde, true),
+    '1' => (Key::One, false),
+    '!' => (Key::One, true),
+    '2' => (Key::Two, false),
+    '@' => (Key::Two, true),
+    '3' => (Key::Three, false),
+    '#' => (Key::Three, true),
+    '4' => (Key::Four, false),
+    '$' => (Key::Four, true),
+    '5' => (Key::Five, false),
+    '%' => (Key::Five, true),
+    '6' => (Key::Six, false),
+    '^' => (Key::Six, true),
+    '7' => (Key::Seven, false),
+    '&' => (Key::Seven, true),
+    '8' => (Key::Eight, false),
+    '*' => (Key::Eight, true),
+    '9' => (Key::Nine, false),
+    '(' => (Key::Nine, true),
+    '0' => (Key::Zero, false),
+    ')' => (Key::Zero, true),
+    '-' => (Key::Minus, false),
+    '_' => (Key::Minus, true),
+    '=' => (Key::Equals, false),
+    '+' => (Key::Equals, true),
+    '[' => (Key::OpenBracket, false),
+    '{' => (Key::OpenBracket, true),
+    ']' => (Key::CloseBracket, false),
+    '}' => (Key::CloseBracket, true),
+    '\\' => (Key::Backslash, false),
+    '|' => (Key::Backslash, true),
+    ';' => (Key::P, false),
+    ':' => (Key::P, true),
+    '\'' => (Key::Apostrophe, false),
+    '"' => (Key::Apostrophe, true),
+    ',' => (Key::Comma, false),
+    '<' => (Key::Comma, true),
+    '.' => (Key::Period, false),
+    '>' => (Key::Period, true),
+    '/' => (Key::ForwardSlash, false),
+    '?' => (Key::ForwardSlash, true),
+    'q' => (Key::Q, false),
+    'Q' => (Key::Q, true),
+    'w' => (Key::W, false),
+    'W' => (Key::W, true),
+    'f' => (Key::E, false),
+    'F' => (Key::E, true),
+    'p' => (Key::R, false),
+    'P' => (Key::R, true),
+    'g' => (Key::T, false),
+    'G' => (Key::T, true),
+    'j' => (Key::Y, false),
+    'J' => (Key::Y, true),
+    'l' => (Key::U, false),
+    'L' => (Key::U, true),
+    'u' => (Key::I, false),
+    'U' => (Key::I, true),
+    'y' => (Key::O, false),
+    'Y' => (Key::O, true),
+    'a' => (Key::A, false),
+    'A' => (Key::A, true),
+    'r' => (Key::S, false),
+    'R' => (Key::S, true),
+    's' => (Key::D, false),
+    'S' => (Key::D, true),
+    't' => (Key::F, false),
+    'T' => (Key::F, true),
+    'd' => (Key::G, false),
+    'D' => (Key::G, true),
+    'h' => (Key::H, false),
+    'H' => (Key::H, true),
+    'n' => (Key::J, false),
+    'N' => (Key::J, true),
+    'e' => (Key::K, false),
+    'E' => (Key::K, true),
+    'i' => (Key::L, false),
+    'I' => (Key::L, true),
+    'o' => (Key::Semicolon, false),
+    'O' => (Key::Semicolon, true),
+    'z' => (Key::Z, false),
+    'Z' => (Key::Z, true),
+    'x' => (Key::X, false),
+    'X' => (Key::X, true),
+    'c' => (Key::C, false),
+    'C' => (Key::C, true),
+    'v' => (Key::V, false),
+    'V' => (Key::V, true),
+    'b' => (Key::B, false),
+    'B' => (Key::B, true),
+    'k' => (Key::N, false),
+    'K' => (Key::N, true),
+    'm' => (Key::M, false),
+    'M' => (Key::M, true),
+    '\x08' => (Key::Backspace, false),
+    '\t' => (Key::Tab, false),
+    '\n' => (Key::Enter, false),
+    '\r' => (Key::Enter, false),
+    ' ' => (Key::Space, false),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUTS: [KeyboardLayout; 6] = [
+        KeyboardLayout::Us,
+        KeyboardLayout::Uk,
+        KeyboardLayout::German,
+        KeyboardLayout::French,
+        KeyboardLayout::Dvorak,
+        KeyboardLayout::Colemak,
+    ];
+
+    #[test]
+    fn letters_and_digits() {
+        for &layout in LAYOUTS.iter() {
+            for char in ('a'..='z').chain('0'..='9') {
+                let (key, shift) = layout.key_for_char(char).unwrap();
+                let upper = char.to_ascii_uppercase();
+                if char.is_ascii_digit() {
+                    // Digits are shifted on AZERTY.
+                    assert_eq!(shift, layout == KeyboardLayout::French);
+                } else {
+                    assert!(!shift);
+                    assert_eq!(layout.key_for_char(upper), Some((key, true)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layouts() {
+        assert_eq!(KeyboardLayout::Uk.key_for_char('"'), Some((Key::Two, true)));
+        assert_eq!(
+            KeyboardLayout::Uk.key_for_char('#'),
+            Some((Key::Backslash, false))
+        );
+        assert_eq!(KeyboardLayout::Uk.key_for_char('\\'), None);
+        assert_eq!(
+            KeyboardLayout::German.key_for_char('z'),
+            Some((Key::Y, false))
+        );
+        assert_eq!(
+            KeyboardLayout::German.key_for_char('ß'),
+            Some((Key::Minus, false))
+        );
+        assert_eq!(
+            KeyboardLayout::French.key_for_char('a'),
+            Some((Key::Q, false))
+        );
+        assert_eq!(
+            KeyboardLayout::French.key_for_char('m'),
+            Some((Key::Semicolon, false))
+        );
+        assert_eq!(
+            KeyboardLayout::French.key_for_char('1'),
+            Some((Key::One, true))
+        );
+        assert_eq!(
+            KeyboardLayout::Dvorak.key_for_char('s'),
+            Some((Key::Semicolon, false))
+        );
+        assert_eq!(
+            KeyboardLayout::Colemak.key_for_char('o'),
+            Some((Key::Semicolon, false))
+        );
+        assert_eq!(
+            KeyboardLayout::Colemak.key_for_char(':'),
+            Some((Key::P, true))
+        );
+    }
+}
@@ -1,22 +1,221 @@
-#![cfg(windows)]
-
 //! This is a wrapper around Logitech's LED SDK.
 
+// Lets the code generated by `lightsync-derive` refer to `::lightsync` from inside this crate too.
+extern crate self as lightsync;
+
+pub mod backend;
+#[cfg(windows)]
 mod bindings;
+mod bitmap;
+mod code;
+mod color;
+mod config;
+#[cfg(feature = "config-file")]
+mod config_file;
+pub mod effects;
+mod engine;
+mod error;
+#[cfg(feature = "image")]
+mod image;
+mod input;
+mod layer;
+mod layout;
+pub mod mock;
+mod preview;
+#[cfg(windows)]
 pub mod raw;
+#[cfg(feature = "scene")]
+mod scene;
+mod shadow;
+#[cfg(test)]
+#[cfg_attr(not(any(feature = "scene", feature = "config-file")), allow(dead_code))]
+mod test_util;
+mod timeline;
+#[cfg_attr(not(windows), allow(dead_code))]
+mod wide;
 
+pub use backend::{LightingBackend, Logitech};
+pub use bitmap::Bitmap;
+pub use code::{HidCode, KeyCode, QuartzCode, ScanCode};
+pub use color::Color;
+pub use config::{get_option, ConfigOption, ConfigWatcher, LightsyncConfig};
+#[cfg(feature = "config-file")]
+pub use config_file::ConfigFile;
+pub use engine::{Effect, Engine, EngineState, HeadlessEngine, InputHandle};
+pub use error::Error;
+#[cfg(feature = "image")]
+pub use image::KeyboardImage;
+pub use input::{KeyEvent, KeyState, Recording};
+pub use layer::{BlendMode, Layer, LayerId, LayerStack};
+pub use layout::KeyboardLayout;
+#[cfg(feature = "derive")]
+pub use lightsync_derive::LightsyncConfig;
+pub use preview::TerminalPreview;
+#[cfg(feature = "scene")]
+pub use scene::{Scene, SceneError, SceneWatcher};
+pub use shadow::{LightingGuard, Snapshot, TargetGuard};
+pub use timeline::{Easing, Keyframe, Timeline, Track};
+
+use bitflags::bitflags;
+use code::KEY_CODES;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
-use phf::phf_map;
-use std::convert::TryInto;
+use shadow::Shadow;
+use std::convert::{TryFrom, TryInto};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
 use std::time::Duration;
 
-const BITMAP_WIDTH: i32 = 21;
-const BITMAP_HEIGHT: i32 = 6;
-const BYTES_PER_KEY: i32 = 4;
+pub(crate) const BITMAP_WIDTH: i32 = 21;
+pub(crate) const BITMAP_HEIGHT: i32 = 6;
+pub(crate) const BYTES_PER_KEY: i32 = 4;
 
 pub const BITMAP_SIZE: i32 = BITMAP_WIDTH * BITMAP_HEIGHT * BYTES_PER_KEY;
 
+/// The key at each cell of the bitmap passed to `Sdk::set_lighting_from_bitmap()`, indexed by row and then column.
+pub(crate) const BITMAP_LAYOUT: [[Option<Key>; BITMAP_WIDTH as usize]; BITMAP_HEIGHT as usize] = [
+    [
+        Some(Key::Esc),
+        Some(Key::F1),
+        Some(Key::F2),
+        Some(Key::F3),
+        Some(Key::F4),
+        Some(Key::F5),
+        Some(Key::F6),
+        Some(Key::F7),
+        Some(Key::F8),
+        Some(Key::F9),
+        Some(Key::F10),
+        Some(Key::F11),
+        Some(Key::F12),
+        Some(Key::PrintScreen),
+        Some(Key::ScrollLock),
+        Some(Key::PauseBreak),
+        None,
+        None,
+        None,
+        None,
+        None,
+    ],
+    [
+        Some(Key::Tilde),
+        Some(Key::One),
+        Some(Key::Two),
+        Some(Key::Three),
+        Some(Key::Four),
+        Some(Key::Five),
+        Some(Key::Six),
+        Some(Key::Seven),
+        Some(Key::Eight),
+        Some(Key::Nine),
+        Some(Key::Zero),
+        Some(Key::Minus),
+        Some(Key::Equals),
+        Some(Key::Backspace),
+        Some(Key::Insert),
+        Some(Key::Home),
+        Some(Key::PageUp),
+        Some(Key::NumLock),
+        Some(Key::NumSlash),
+        Some(Key::NumAsterisk),
+        Some(Key::NumMinus),
+    ],
+    [
+        Some(Key::Tab),
+        Some(Key::Q),
+        Some(Key::W),
+        Some(Key::E),
+        Some(Key::R),
+        Some(Key::T),
+        Some(Key::Y),
+        Some(Key::U),
+        Some(Key::I),
+        Some(Key::O),
+        Some(Key::P),
+        Some(Key::OpenBracket),
+        Some(Key::CloseBracket),
+        Some(Key::Backslash),
+        Some(Key::KeyboardDelete),
+        Some(Key::End),
+        Some(Key::PageDown),
+        Some(Key::NumSeven),
+        Some(Key::NumEight),
+        Some(Key::NumNine),
+        Some(Key::NumPlus),
+    ],
+    [
+        Some(Key::CapsLock),
+        Some(Key::A),
+        Some(Key::S),
+        Some(Key::D),
+        Some(Key::F),
+        Some(Key::G),
+        Some(Key::H),
+        Some(Key::J),
+        Some(Key::K),
+        Some(Key::L),
+        Some(Key::Semicolon),
+        Some(Key::Apostrophe),
+        None,
+        Some(Key::Enter),
+        None,
+        None,
+        None,
+        Some(Key::NumFour),
+        Some(Key::NumFive),
+        Some(Key::NumSix),
+        None,
+    ],
+    [
+        Some(Key::LeftShift),
+        Some(Key::Z),
+        Some(Key::X),
+        Some(Key::C),
+        Some(Key::V),
+        Some(Key::B),
+        Some(Key::N),
+        Some(Key::M),
+        Some(Key::Comma),
+        Some(Key::Period),
+        Some(Key::ForwardSlash),
+        None,
+        None,
+        Some(Key::RightShift),
+        None,
+        Some(Key::ArrowUp),
+        None,
+        Some(Key::NumOne),
+        Some(Key::NumTwo),
+        Some(Key::NumThree),
+        Some(Key::NumEnter),
+    ],
+    [
+        Some(Key::LeftControl),
+        Some(Key::LeftWindows),
+        Some(Key::LeftAlt),
+        None,
+        None,
+        Some(Key::Space),
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(Key::RightAlt),
+        Some(Key::RightWindows),
+        Some(Key::ApplicationSelect),
+        Some(Key::RightControl),
+        Some(Key::ArrowLeft),
+        Some(Key::ArrowDown),
+        Some(Key::ArrowRight),
+        Some(Key::NumZero),
+        Some(Key::NumPeriod),
+        None,
+    ],
+];
+
 /// Types of devices with different kinds of lighting.
+///
+/// These are the values taken by the SDK; `Sdk` takes them as `TargetDevices` instead.
 pub mod lighting {
     pub const MONOCHROME: i32 = 0b001;
     pub const RGB: i32 = 0b010;
@@ -24,6 +223,50 @@ pub mod lighting {
     pub const ALL: i32 = 0b111;
 }
 
+bitflags! {
+    /// A set of kinds of devices with different kinds of lighting, to be targeted by `Sdk`.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::TargetDevices;
+    ///
+    /// let target = TargetDevices::RGB | TargetDevices::MONOCHROME;
+    /// assert!(!target.contains(TargetDevices::PERKEY_RGB));
+    /// assert_eq!(target | TargetDevices::PERKEY_RGB, TargetDevices::ALL);
+    /// assert_eq!(TargetDevices::from_bits(0b1000), None);
+    /// ```
+    pub struct TargetDevices: i32 {
+        const MONOCHROME = lighting::MONOCHROME;
+        const RGB = lighting::RGB;
+        const PERKEY_RGB = lighting::PERKEY_RGB;
+        const ALL = lighting::ALL;
+    }
+}
+
+impl Default for TargetDevices {
+    fn default() -> TargetDevices {
+        TargetDevices::ALL
+    }
+}
+
+impl TryFrom<i32> for TargetDevices {
+    type Error = Error;
+
+    /// Converts a combination of the constants in `lighting`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `bits` isn't a combination of those constants.
+    fn try_from(bits: i32) -> Result<TargetDevices, Error> {
+        TargetDevices::from_bits(bits).ok_or(Error::InvalidArgument)
+    }
+}
+
+impl From<TargetDevices> for i32 {
+    fn from(target: TargetDevices) -> i32 {
+        target.bits()
+    }
+}
+
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
 pub enum Key {
@@ -154,168 +397,310 @@ pub enum DeviceType {
     Speaker = 14,
 }
 
-static ASCII_MAP: phf::Map<char, Key> = phf_map! {
-    '`' => Key::Tilde,
-    '~' => Key::Tilde,
-    '1' => Key::One,
-    '!' => Key::One,
-    '2' => Key::Two,
-    '@' => Key::Two,
-    '3' => Key::Three,
-    '#' => Key::Three,
-    '4' => Key::Four,
-    '$' => Key::Four,
-    '5' => Key::Five,
-    '%' => Key::Five,
-    '6' => Key::Six,
-    '^' => Key::Six,
-    '7' => Key::Seven,
-    '&' => Key::Seven,
-    '8' => Key::Eight,
-    '*' => Key::Eight,
-    '9' => Key::Nine,
-    '(' => Key::Nine,
-    '0' => Key::Zero,
-    ')' => Key::Zero,
-    '-' => Key::Minus,
-    '_' => Key::Minus,
-    '=' => Key::Equals,
-    '+' => Key::Equals,
-    '\x08' => Key::Backspace,
-    '\t' => Key::Tab,
-    'q' => Key::Q,
-    'Q' => Key::Q,
-    'w' => Key::W,
-    'W' => Key::W,
-    'e' => Key::E,
-    'E' => Key::E,
-    'r' => Key::R,
-    'R' => Key::R,
-    't' => Key::T,
-    'T' => Key::T,
-    'y' => Key::Y,
-    'Y' => Key::Y,
-    'u' => Key::U,
-    'U' => Key::U,
-    'i' => Key::I,
-    'I' => Key::I,
-    'o' => Key::O,
-    'O' => Key::O,
-    'p' => Key::P,
-    'P' => Key::P,
-    '[' => Key::OpenBracket,
-    '{' => Key::OpenBracket,
-    ']' => Key::CloseBracket,
-    '}' => Key::CloseBracket,
-    '\\' => Key::Backslash,
-    '|' => Key::Backslash,
-    'a' => Key::A,
-    'A' => Key::A,
-    's' => Key::S,
-    'S' => Key::S,
-    'd' => Key::D,
-    'D' => Key::D,
-    'f' => Key::F,
-    'F' => Key::F,
-    'g' => Key::G,
-    'G' => Key::G,
-    'h' => Key::H,
-    'H' => Key::H,
-    'j' => Key::J,
-    'J' => Key::J,
-    'k' => Key::K,
-    'K' => Key::K,
-    'l' => Key::L,
-    'L' => Key::L,
-    ';' => Key::Semicolon,
-    ':' => Key::Semicolon,
-    '\'' => Key::Apostrophe,
-    '"' => Key::Apostrophe,
-    '\n' => Key::Enter,
-    '\r' => Key::Enter,
-    'z' => Key::Z,
-    'Z' => Key::Z,
-    'x' => Key::X,
-    'X' => Key::X,
-    'c' => Key::C,
-    'C' => Key::C,
-    'v' => Key::V,
-    'V' => Key::V,
-    'b' => Key::B,
-    'B' => Key::B,
-    'n' => Key::N,
-    'N' => Key::N,
-    'm' => Key::M,
-    'M' => Key::M,
-    ',' => Key::Comma,
-    '<' => Key::Comma,
-    '.' => Key::Period,
-    '>' => Key::Period,
-    '/' => Key::ForwardSlash,
-    '?' => Key::ForwardSlash,
-};
-
 impl Key {
-    fn scan_code() {
-        todo!()
+    /// Every key, in the order they're declared.
+    pub const ALL: [Key; 115] = [
+        Key::Esc,
+        Key::One,
+        Key::Two,
+        Key::Three,
+        Key::Four,
+        Key::Five,
+        Key::Six,
+        Key::Seven,
+        Key::Eight,
+        Key::Nine,
+        Key::Zero,
+        Key::Minus,
+        Key::Equals,
+        Key::Backspace,
+        Key::Tab,
+        Key::Q,
+        Key::W,
+        Key::E,
+        Key::R,
+        Key::T,
+        Key::Y,
+        Key::U,
+        Key::I,
+        Key::O,
+        Key::P,
+        Key::OpenBracket,
+        Key::CloseBracket,
+        Key::Enter,
+        Key::LeftControl,
+        Key::A,
+        Key::S,
+        Key::D,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::Semicolon,
+        Key::Apostrophe,
+        Key::Tilde,
+        Key::LeftShift,
+        Key::Backslash,
+        Key::Z,
+        Key::X,
+        Key::C,
+        Key::V,
+        Key::B,
+        Key::N,
+        Key::M,
+        Key::Comma,
+        Key::Period,
+        Key::ForwardSlash,
+        Key::RightShift,
+        Key::NumAsterisk,
+        Key::LeftAlt,
+        Key::Space,
+        Key::CapsLock,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::NumLock,
+        Key::ScrollLock,
+        Key::NumSeven,
+        Key::NumEight,
+        Key::NumNine,
+        Key::NumMinus,
+        Key::NumFour,
+        Key::NumFive,
+        Key::NumSix,
+        Key::NumPlus,
+        Key::NumOne,
+        Key::NumTwo,
+        Key::NumThree,
+        Key::NumZero,
+        Key::NumPeriod,
+        Key::F11,
+        Key::F12,
+        Key::NumEnter,
+        Key::RightControl,
+        Key::NumSlash,
+        Key::PrintScreen,
+        Key::RightAlt,
+        Key::PauseBreak,
+        Key::Home,
+        Key::ArrowUp,
+        Key::PageUp,
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::End,
+        Key::ArrowDown,
+        Key::PageDown,
+        Key::Insert,
+        Key::KeyboardDelete,
+        Key::LeftWindows,
+        Key::RightWindows,
+        Key::ApplicationSelect,
+        Key::G1,
+        Key::G2,
+        Key::G3,
+        Key::G4,
+        Key::G5,
+        Key::G6,
+        Key::G7,
+        Key::G8,
+        Key::G9,
+        Key::GLogo,
+        Key::GBadge,
+    ];
+
+    /// Returns the row and column of this key in a `Bitmap`.
+    ///
+    /// Returns `None` for keys which don't have a cell in the bitmap: G1 to G9, `GLogo` and `GBadge`.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::Key;
+    ///
+    /// assert_eq!(Key::W.bitmap_position(), Some((2, 2)));
+    /// assert_eq!(Key::G1.bitmap_position(), None);
+    /// ```
+    pub fn bitmap_position(self) -> Option<(usize, usize)> {
+        BITMAP_LAYOUT.iter().enumerate().find_map(|(row, keys)| {
+            keys.iter()
+                .position(|&key| key == Some(self))
+                .map(|col| (row, col))
+        })
     }
 
-    fn hid_code() {
-        todo!()
+    /// Returns the key at row `row` and column `col` of a `Bitmap`.
+    ///
+    /// Returns `None` if the cell is empty, or outside of the bitmap.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::Key;
+    ///
+    /// assert_eq!(Key::at(2, 2), Some(Key::W));
+    /// assert_eq!(Key::at(0, 20), None);
+    /// assert_eq!(Key::at(6, 0), None);
+    /// ```
+    pub fn at(row: usize, col: usize) -> Option<Key> {
+        BITMAP_LAYOUT.get(row)?.get(col).copied().flatten()
     }
 
-    fn quartz_code() {
-        todo!()
+    /// Returns the scan code of this key, or `None` for the G keys, `GLogo` and `GBadge`.
+    pub fn scan_code(self) -> Option<ScanCode> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.0 == self)
+            .map(|entry| entry.1)
+    }
+
+    /// Returns the USB HID usage ID of this key, or `None` for the G keys, `GLogo` and `GBadge`.
+    pub fn hid_code(self) -> Option<HidCode> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.0 == self)
+            .map(|entry| entry.2)
+    }
+
+    /// Returns the macOS virtual key code of this key, or `None` for the G keys, `GLogo` and `GBadge`.
+    pub fn quartz_code(self) -> Option<QuartzCode> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.0 == self)
+            .map(|entry| entry.3)
+    }
+
+    /// Returns the key with the scan code `code`, or `None` if there isn't one.
+    pub fn from_scan_code(code: ScanCode) -> Option<Key> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.1 == code)
+            .map(|entry| entry.0)
+    }
+
+    /// Returns the key with the USB HID usage ID `code`, or `None` if there isn't one.
+    pub fn from_hid_code(code: HidCode) -> Option<Key> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.2 == code)
+            .map(|entry| entry.0)
+    }
+
+    /// Returns the key with the macOS virtual key code `code`, or `None` if there isn't one.
+    pub fn from_quartz_code(code: QuartzCode) -> Option<Key> {
+        KEY_CODES
+            .iter()
+            .find(|entry| entry.3 == code)
+            .map(|entry| entry.0)
+    }
+
+    /// Returns the key with the code `code` in any code space, or `None` if there isn't one.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::{HidCode, Key, ScanCode};
+    ///
+    /// assert_eq!(Key::from_code(ScanCode(0x1E)), Some(Key::A));
+    /// assert_eq!(Key::from_code(HidCode(0x04)), Some(Key::A));
+    /// ```
+    pub fn from_code(code: impl Into<KeyCode>) -> Option<Key> {
+        match code.into() {
+            KeyCode::Scan(code) => Key::from_scan_code(code),
+            KeyCode::Hid(code) => Key::from_hid_code(code),
+            KeyCode::Quartz(code) => Key::from_quartz_code(code),
+        }
+    }
+
+    /// Returns the key which types `char` on a US keyboard, and whether shift needs to be held.
+    ///
+    /// Returns `None` if `char` can't be typed with a single key and shift.
+    /// Use `from_char_with_layout()` for other layouts.
+    ///
+    /// # Example
+    /// ```
+    /// use lightsync::Key;
+    ///
+    /// assert_eq!(Key::from_char('a'), Some((Key::A, false)));
+    /// assert_eq!(Key::from_char('?'), Some((Key::ForwardSlash, true)));
+    /// assert_eq!(Key::from_char('é'), None);
+    /// ```
+    pub fn from_char(char: char) -> Option<(Key, bool)> {
+        KeyboardLayout::Us.key_for_char(char)
+    }
+
+    /// Returns the key which types `char` on `layout`, and whether shift needs to be held.
+    ///
+    /// Returns `None` if `char` can't be typed with a single key and shift.
+    pub fn from_char_with_layout(char: char, layout: KeyboardLayout) -> Option<(Key, bool)> {
+        layout.key_for_char(char)
     }
 
     /// Get a `Key` value from an ascii `char`.
     ///
     /// This function will panic if `char` is not an ascii value.
+    /// Use `from_char()` to handle unmapped characters instead.
     pub fn from_ascii(char: &char) -> Key {
-        ASCII_MAP
-            .get(char)
-            .expect(format!("Character {} cannot be mapped to keyboard", char).as_str())
-            .clone()
+        Key::from_char(*char)
+            .map(|(key, _)| key)
+            .unwrap_or_else(|| panic!("Character {} cannot be mapped to keyboard", char))
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    /// Parses the name of a key, like `F1` or `NumEnter`, ignoring case.
+    fn from_str(s: &str) -> Result<Key, Error> {
+        Key::ALL
+            .iter()
+            .copied()
+            .find(|key| format!("{:?}", key).eq_ignore_ascii_case(s))
+            .ok_or(Error::InvalidArgument)
     }
 }
 
 impl PartialEq<char> for Key {
     fn eq(&self, other: &char) -> bool {
-        ASCII_MAP.get(other).map_or(false, |key| key == self)
+        Key::from_char(*other).map(|(key, _)| key) == Some(*self)
     }
 }
 
-/// A simple struct to represent an RGB color.
-pub type Color = (i32, i32, i32);
-
-/// This empty struct represents an initialised Logitech LED SDK.
+/// This struct represents an initialised Logitech LED SDK, or another `LightingBackend`.
 ///
 /// This means it's impossible to call SDK functions without first initializing it,
 /// and the SDK is automaticaly shut down when the `Sdk` is dropped.
-pub struct Sdk;
-impl Drop for Sdk {
+pub struct Sdk<B: LightingBackend = Logitech> {
+    backend: B,
+    /// The state set through this `Sdk`, since the SDK can't be asked for it.
+    shadow: Mutex<Shadow>,
+}
+impl<B: LightingBackend> Drop for Sdk<B> {
     fn drop(&mut self) {
-        raw::shutdown();
+        self.backend.shutdown();
     }
 }
 impl Sdk {
     /// If there isn't already another instance running,
     /// makes necessary initializations and disables any existing effects before returning an `Sdk` object.
     ///
-    /// If it returns None, it means that either the connection with the SDK is broken
-    /// or there's already an instance of the SDK running.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection with the SDK is broken,
+    /// or `Error::AlreadyRunning` if there's already an instance of the SDK running.
     ///
     /// # Example
-    /// ```
-    /// let sdk = lightsync::Sdk::init().unwrap();
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init()?;
     /// // do stuff
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn init() -> Option<Sdk> {
-        if raw::init() {
-            Some(Sdk)
-        } else {
-            None
-        }
+    pub fn init() -> Result<Sdk, Error> {
+        Sdk::with_backend(Logitech)
     }
 
     /// If there isn't already another instance running,
@@ -323,411 +708,665 @@ impl Sdk {
     ///
     /// It registers the integration with the name provided.
     ///
-    /// If it returns None, it means that either the connection with the SDK is broken
-    /// or there's already an instance of the SDK running.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection with the SDK is broken,
+    /// `Error::AlreadyRunning` if there's already an instance of the SDK running,
+    /// or `Error::NulInName` if `name` contains any null bytes.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init_with_name("foo")?;
+    /// // do stuff
+    /// # Ok::<(), lightsync::Error>(())
+    /// ```
+    pub fn init_with_name(name: &str) -> Result<Sdk, Error> {
+        Sdk::with_backend_and_name(Logitech, name)
+    }
+}
+impl<B: LightingBackend> Sdk<B> {
+    /// Initializes `backend` and returns an `Sdk` which uses it instead of the Logitech LED SDK.
     ///
-    /// # Panics
-    /// Panics if `name` cannot be converted to a C string (contains any null bytes).
+    /// # Errors
+    /// Returns whatever error the backend fails to initialize with.
     ///
     /// # Example
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::with_backend(lightsync::Logitech)?;
+    /// // do stuff
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    /// let sdk = lightsync::Sdk::init_with_name("foo").unwrap();
+    pub fn with_backend(backend: B) -> Result<Sdk<B>, Error> {
+        backend.init()?;
+        Ok(Sdk {
+            backend,
+            shadow: Mutex::default(),
+        })
+    }
+
+    /// Initializes `backend` with the integration name `name`,
+    /// and returns an `Sdk` which uses it instead of the Logitech LED SDK.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails to initialize with.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::with_backend_and_name(lightsync::Logitech, "foo")?;
     /// // do stuff
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn init_with_name(name: &str) -> Option<Sdk> {
-        if raw::init_with_name(name) {
-            Some(Sdk)
-        } else {
-            None
-        }
+    pub fn with_backend_and_name(backend: B, name: &str) -> Result<Sdk<B>, Error> {
+        backend.init_with_name(name)?;
+        Ok(Sdk {
+            backend,
+            shadow: Mutex::default(),
+        })
     }
 
-    /// Retrieves the version of the SDK installed on the user’s system.
+    /// Returns the backend this `Sdk` calls into.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub(crate) fn shadow(&self) -> MutexGuard<'_, Shadow> {
+        // The shadow state is always left consistent, so a panic while it was locked doesn't matter.
+        self.shadow.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the current color of `key` on per-key devices.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// The SDK can't be asked for the lighting, so this is the color `key` was last set to through this `Sdk`,
+    /// taking into account the targeted devices, and the alpha and excluded keys of bitmaps.
+    /// Keys which haven't been set since `init()` are assumed to be black, and effects like `flash_key()` are ignored.
     ///
     /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, Key};
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
+    /// sdk.set_lighting_for_key(Key::W, Color::new(100, 0, 0))?;
+    ///
+    /// assert_eq!(sdk.current_key_color(Key::W), Color::new(100, 0, 0));
+    /// assert_eq!(sdk.current_key_color(Key::A), Color::new(0, 100, 0));
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    /// let sdk = lightsync::init().unwrap();
-    /// println!("{:?}", sdk.version());
+    pub fn current_key_color(&self, key: Key) -> Color {
+        self.shadow().lighting.key_color(key)
+    }
+
+    /// Returns the current lighting of every kind of device.
+    ///
+    /// Like `current_key_color()`, this is the lighting last set through this `Sdk`.
+    pub fn snapshot(&self) -> Snapshot {
+        self.shadow().lighting.clone()
+    }
+
+    /// Returns the kinds of devices currently targeted, as last set with `set_target_devices()`.
+    pub fn target_devices(&self) -> TargetDevices {
+        TargetDevices::from_bits_truncate(self.shadow().target_device)
+    }
+
+    /// Returns the keys last excluded with `exclude_keys_from_bitmap()`.
+    pub fn excluded_keys(&self) -> Vec<Key> {
+        self.shadow().excluded_keys.clone()
+    }
+
+    /// Retrieves the version of the SDK installed on the user’s system.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init()?;
+    /// println!("{:?}", sdk.version()?);
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn version(&self) -> (i32, i32, i32) {
-        raw::get_sdk_version().expect("LogiLedGetSdkVersion failed")
+    pub fn version(&self) -> Result<(i32, i32, i32), Error> {
+        self.backend.get_sdk_version()
     }
 
     /// Sets the target devices for future calls. By default, all devices are targeted.
     ///
     /// The different kinds of devices are `MONOCHROME`, `RGB`, and `PERKEY_RGB`.
+    /// To only change the target temporarily, use `with_target()` or `targeting()`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
-    /// use lightsync::{Color, Key};
+    /// ```no_run
+    /// use lightsync::{Color, Key, TargetDevices};
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
-    /// sdk.set_target_devices(lightsync::lighting::RGB | lightsync::lighting::MONOCHROME);
+    /// sdk.set_target_devices(TargetDevices::RGB | TargetDevices::MONOCHROME)?;
     /// // This call will only affect MONOCHROME and RGB devices,
     /// // and PERKEY_RGB devices like a keybard won't be affected.
-    /// sdk.set_lighting(Color::new(100, 0, 0));
+    /// sdk.set_lighting(Color::new(100, 0, 0))?;
     ///
-    /// sdk.set_target_devices(lightsync::lighting::PERKEY_RGB);
+    /// sdk.set_target_devices(TargetDevices::PERKEY_RGB)?;
     /// // These calls will _only_ affect PERKEY_RGB devices.
-    /// sdk.set_lighting_for_key(Key::ArrowDown, Color::new(100, 0, 0));
-    /// sdk.flash_lighting(Color::new(50, 50, 50), Duration::from_millis(0), Duration::from_millis(300));
+    /// sdk.set_lighting_for_key(Key::ArrowDown, Color::new(100, 0, 0))?;
+    /// sdk.flash_lighting(Color::new(50, 50, 50), Duration::from_millis(0), Duration::from_millis(300))?;
     ///
-    /// sdk.set_target_devices(lightsync::lighting::ALL);
+    /// sdk.set_target_devices(TargetDevices::ALL)?;
     /// // Calls will now affect all connected devices again.
-    /// sdk.set_lighting(Color::new(50, 0, 0));
+    /// sdk.set_lighting(Color::new(50, 0, 0))?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn set_target_devices(&self, target_devices: i32) {
-        assert!(
-            raw::set_target_device(target_devices),
-            "LogiLedSetTargetDevice failed"
-        );
+    pub fn set_target_devices(&self, target_devices: TargetDevices) -> Result<(), Error> {
+        self.backend.set_target_device(target_devices.bits())?;
+        self.shadow().target_device = target_devices.bits();
+        Ok(())
     }
 
-    /// Sets the lighting color of all connected devices.
+    /// Targets `target_devices` while calling `f`, and then targets the previous devices again.
+    ///
+    /// The previous devices are targeted again even if `f` fails.
+    ///
+    /// # Errors
+    /// Returns the error returned by `f`, or `Error::ConnectionLost` if the connection to the SDK has been lost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, Key, TargetDevices};
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    ///
+    /// sdk.with_target(TargetDevices::PERKEY_RGB, |sdk| {
+    ///     sdk.set_lighting(Color::new(0, 0, 100))?;
+    ///     sdk.set_lighting_for_key(Key::W, Color::new(100, 0, 0))
+    /// })?;
+    /// // Calls will now affect all connected devices again.
+    /// # Ok::<(), lightsync::Error>(())
+    /// ```
+    pub fn with_target<T>(
+        &self,
+        target_devices: TargetDevices,
+        f: impl FnOnce(&Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let guard = self.targeting(target_devices)?;
+        let result = f(self);
+        let restored = guard.restore();
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Targets `target_devices`, and returns a guard which targets the previous devices again when it's dropped.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, TargetDevices};
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    /// {
+    ///     let _target = sdk.targeting(TargetDevices::RGB)?;
+    ///     sdk.set_lighting(Color::new(100, 0, 0))?;
+    /// }
+    /// // Calls will now affect all connected devices again.
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    /// let sdk = lightsync::init().unwrap();
+    pub fn targeting(&self, target_devices: TargetDevices) -> Result<TargetGuard<'_, B>, Error> {
+        let previous = self.target_devices();
+        self.set_target_devices(target_devices)?;
+        Ok(TargetGuard {
+            sdk: self,
+            previous: Some(previous),
+        })
+    }
+
+    /// Sets the lighting color of all connected devices.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Green
-    /// sdk.set_lighting(lightsync::Color::new(0, 100, 0));
+    /// sdk.set_lighting(lightsync::Color::new(0, 100, 0))?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn set_lighting(&self, color: Color) {
-        assert!(raw::set_lighting(color), "LogiLedSetLighting failed");
+    pub fn set_lighting(&self, color: Color) -> Result<(), Error> {
+        self.backend.set_lighting(color)?;
+        self.shadow().set_lighting(color);
+        Ok(())
     }
 
     /// Sets the lighting in a specific zone of a device.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
-    /// let sdk = lightsync::init().unwrap();
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Set the logo on mice to green. (zones vary for specific devices)
-    /// sdk.set_lighting_for_zone(lightsync::DeviceType::Mouse, 1, lightsync::Color::new(0, 100, 0));
+    /// sdk.set_lighting_for_zone(lightsync::DeviceType::Mouse, 1, lightsync::Color::new(0, 100, 0))?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn set_lighting_for_zone(&self, device_type: DeviceType, zone: i32, color: Color) {
-        assert!(
-            raw::set_lighting_for_target_zone(device_type, zone, color),
-            "LogiLedSetLightingForTargetZone failed"
-        )
+    pub fn set_lighting_for_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error> {
+        self.backend
+            .set_lighting_for_target_zone(device_type, zone, color)?;
+        self.shadow().lighting.set_zone(device_type, zone, color);
+        Ok(())
     }
 
     /// Saves the current lighting so it can be restored after a temporary effect is finished.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
-    /// sdk.set_lighting(Color::new(0, 100, 0));
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
     ///
     /// // Save the green lighting
-    /// sdk.save_lighting();
+    /// sdk.save_lighting()?;
     ///
     /// // Set the lighting to red for a second
-    /// sdk.set_lighting(Color::new(100, 0, 0));
+    /// sdk.set_lighting(Color::new(100, 0, 0))?;
     /// thread::sleep(Duration::from_millis(1000));
     ///
     /// // Restore the green lighting
-    /// sdk.restore_lighting();
+    /// sdk.restore_lighting()?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn save_lighting(&self) {
-        assert!(
-            raw::save_current_lighting(),
-            "LogiLedSaveCurrentLighting failed"
-        );
+    pub fn save_lighting(&self) -> Result<(), Error> {
+        self.backend.save_current_lighting()?;
+        let mut shadow = self.shadow();
+        shadow.saved = shadow.lighting.clone();
+        Ok(())
     }
 
     /// Restores the last saved lighting.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
-    /// sdk.set_lighting(Color::new(0, 100, 0));
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
     ///
     /// // Save the green lighting
-    /// sdk.save_lighting();
+    /// sdk.save_lighting()?;
     ///
     /// // Set the lighting to red for a second
-    /// sdk.set_lighting(Color::new(100, 0, 0));
+    /// sdk.set_lighting(Color::new(100, 0, 0))?;
     /// thread::sleep(Duration::from_millis(1000));
     ///
     /// // Restore the green lighting
-    /// sdk.restore_lighting();
+    /// sdk.restore_lighting()?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn restore_lighting(&self) {
-        assert!(raw::restore_lighting(), "LogiLedRestoreLighting failed");
+    pub fn restore_lighting(&self) -> Result<(), Error> {
+        self.backend.restore_lighting()?;
+        let mut shadow = self.shadow();
+        shadow.lighting = shadow.saved.clone();
+        Ok(())
+    }
+
+    /// Returns a guard which restores the current lighting when it's dropped.
+    ///
+    /// Unlike `save_lighting()` and `restore_lighting()`, which share a single slot in the SDK,
+    /// any number of guards can be alive at once without clobbering each other's saved lighting.
+    /// The lighting is tracked by the `Sdk` itself, so anything which hasn't been set since `init()`
+    /// is assumed to be black.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, Key};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
+    ///
+    /// {
+    ///     let _guard = sdk.temporarily();
+    ///     sdk.set_lighting(Color::new(100, 0, 0))?;
+    ///     {
+    ///         let _guard = sdk.temporarily();
+    ///         sdk.set_lighting_for_key(Key::Space, Color::new(0, 0, 100))?;
+    ///         thread::sleep(Duration::from_millis(1000));
+    ///     }
+    ///     // Back to red
+    ///     thread::sleep(Duration::from_millis(1000));
+    /// }
+    /// // Back to green
+    /// # Ok::<(), lightsync::Error>(())
+    /// ```
+    pub fn temporarily(&self) -> LightingGuard<'_, B> {
+        LightingGuard {
+            sdk: self,
+            id: self.shadow().push_guard(),
+        }
+    }
+
+    /// Changes the lighting of every device to `snapshot`, only setting the kinds of devices which differ.
+    pub(crate) fn restore_snapshot(&self, snapshot: &Snapshot) -> Result<(), Error> {
+        let current = self.snapshot();
+
+        // The previous target is set again by `with_target` even if a call fails part of the way through.
+        if current.color != snapshot.color || current.zones != snapshot.zones {
+            self.with_target(TargetDevices::MONOCHROME | TargetDevices::RGB, |sdk| {
+                sdk.set_lighting(snapshot.color)?;
+                for (&(device_type, zone), &color) in &snapshot.zones {
+                    sdk.set_lighting_for_zone(device_type, zone, color)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        if current.framebuffer != snapshot.framebuffer || current.other_keys != snapshot.other_keys
+        {
+            self.with_target(TargetDevices::PERKEY_RGB, |sdk| {
+                if current.framebuffer != snapshot.framebuffer {
+                    sdk.set_lighting_from_bitmap(&snapshot.to_bitmap())?;
+                }
+                // Excluded keys are skipped by the bitmap, and the G keys aren't in it at all.
+                let keys = sdk.shadow().excluded_keys.clone();
+                for key in keys.into_iter().chain(snapshot.other_keys.keys().copied()) {
+                    let color = snapshot.key_color(key);
+                    if sdk.shadow().lighting.key_color(key) != color {
+                        sdk.set_lighting_for_key(key, color)?;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
     }
 
     /// Saves the current lighting, plays a flashing effect at `interval` for `duration` and then restores the saved lighting.
     ///
     /// If you use a duration of 0, the effect will play until stopped with `stop_effects()`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::DurationTooLong` if a duration is longer than `i32::MAX` milliseconds,
+    /// or `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Note that this doesn't pause the thread.
-    /// sdk.flash_lighting(Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500));
+    /// sdk.flash_lighting(Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500))?;
     /// thread::sleep(Duration::from_millis(2000));
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn flash_lighting(&self, color: Color, duration: Duration, interval: Duration) {
-        assert!(
-            raw::flash_lighting(
-                color,
-                duration
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-                interval
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-            ),
-            "LogiLedFlashLighting failed"
-        )
+    pub fn flash_lighting(
+        &self,
+        color: Color,
+        duration: Duration,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        self.backend
+            .flash_lighting(color, millis(duration)?, millis(interval)?)
     }
 
     /// Saves the current lighting, plays a pulsing effect at `interval` for `duration` and then restores the saved lighting.
     ///
     /// If you use a duration of 0, the effect will play until stopped with `stop_effects()`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::DurationTooLong` if a duration is longer than `i32::MAX` milliseconds,
+    /// or `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Note that this doesn't pause the thread.
-    /// sdk.pulse_lighting(Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500));
+    /// sdk.pulse_lighting(Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500))?;
     /// thread::sleep(Duration::from_millis(2000));
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn pulse_lighting(&self, color: Color, duration: Duration, interval: Duration) {
-        assert!(
-            raw::pulse_lighting(
-                color,
-                duration
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-                interval
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-            ),
-            "LogiLedPulseLighting failed"
-        );
+    pub fn pulse_lighting(
+        &self,
+        color: Color,
+        duration: Duration,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        self.backend
+            .pulse_lighting(color, millis(duration)?, millis(interval)?)
     }
 
     /// Stops any of the preset effects (flashing/pulsing).
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Flash lighting endlessly
-    /// sdk.flash_lighting(Color::new(100, 0, 0), Duration::from_millis(0), Duration::from_millis(500));
+    /// sdk.flash_lighting(Color::new(100, 0, 0), Duration::from_millis(0), Duration::from_millis(500))?;
     /// thread::sleep(Duration::from_millis(2000));
     ///
     /// // Stop the flashing
-    /// sdk.stop_effects();
+    /// sdk.stop_effects()?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn stop_effects(&self) {
-        assert!(raw::stop_effects(), "LogiLedStopEffects failed");
+    pub fn stop_effects(&self) -> Result<(), Error> {
+        self.backend.stop_effects()
     }
 
-    /// Sets the lighting of per-key devices to the grid of RGBA colors `bitmap`.
+    /// Sets the lighting of per-key devices to `bitmap`.
     ///
-    /// To only update part of the keyboard, set the alpha of all other keys to 0.
+    /// Cells with an alpha of 0 are left alone, so to only update part of the keyboard,
+    /// leave the other keys transparent. See `Bitmap` for which key is at each cell.
     ///
-    /// # Parameters
-    /// - `bitmap`: A 21x6x4 array representing the grid of RGBA pixel values to display.
-    /// Each byte ranges from 0 to 255.
-    ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
-    ///
-    /// # Layout
-    /// The grid is arranged like so:
-    ///
-    /// |       | **0**       | **1**       | **2**   | **3** | **4** | **5** | **6** | **7** | **8** | **9**  | **10**       | **11**      | **12**       | **13**            | **14**         | **15**     | **16**    | **17**     | 18**     | **19**      | **20**   |
-    /// |-------|-------------|-------------|---------|-------|-------|-------|-------|-------|-------|--------|--------------|-------------|--------------|-------------------|----------------|------------|-----------|------------|----------|-------------|----------|
-    /// | **0** | Esc         | F1          | F2      | F3    | F4    | F5    | F6    | F7    | F8    | F9     | F10          | F11         | F12          | PrintScreen       | ScrollLock     | PauseBreak |           |            |          |             |          |
-    /// | **1** | Tilde       | One         | Two     | Three | Four  | Five  | Six   | Seven | Eight | Nine   | Zero         | Minus       | Equals       | Backspace         | Insert         | Home       | PageUp    | NumLock    | NumSlash | NumAsterisk | NumMinus |
-    /// | **2** | Tab         | Q           | W       | E     | R     | T     | Y     | U     | I     | O      | P            | OpenBracket | CloseBracket | Backslash         | KeyboardDelete | End        | PageDown  | NumSeven   | NumEight | NumNine     | NumPlus  |
-    /// | **3** | CapsLock    | A           | S       | D     | F     | G     | H     | J     | K     | L      | Semicolon    | Apostrophe  |              | Enter             |                |            |           | NumFour    | NumFive  | NumSix      |          |
-    /// | **4** | LeftShift   | Z           | X       | C     | V     | B     | N     | M     | Comma | Period | ForwardSlash |             |              | RightShift        |                | ArrowUp    |           | NumOne     | NumTwo   | NumThree    | NumEnter |
-    /// | **5** | LeftControl | LeftWindows | LeftAlt |       |       | Space |       |       |       |        |              | RightAlt    | RightWindows | ApplicationSelect | RightControl   | ArrowLeft  | ArrowDown | ArrowRight | NumZero  | NumPeriod   |          |
-    pub fn set_lighting_from_bitmap(&self, bitmap: &[[[u8; 4]; 21]; 6]) {
-        let mut bitmap: Vec<u8> = bitmap
-            .iter()
-            .flat_map(|row| row.iter().flat_map(|cell| cell.iter().cloned()))
-            .collect();
-        assert!(
-            raw::set_lighting_from_bitmap(&mut bitmap),
-            "LogiLedSetLightingFromBitmap failed"
-        )
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lightsync::{Bitmap, Color, Key};
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    ///
+    /// let mut bitmap = Bitmap::filled(Color::new(0, 0, 100));
+    /// bitmap.set(Key::Esc, Color::new(100, 0, 0));
+    /// sdk.set_lighting_from_bitmap(&bitmap)?;
+    /// # Ok::<(), lightsync::Error>(())
+    /// ```
+    pub fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error> {
+        self.backend.set_lighting_from_bitmap(bitmap)?;
+        self.shadow().set_bitmap(bitmap);
+        Ok(())
     }
 
     /// Sets a list of keys to be ignored when calling `set_lighting_from_bitmap()`.
-    pub fn exclude_keys_from_bitmap(&self, keys: &mut [Key]) {
-        assert!(
-            raw::exclude_keys_from_bitmap(keys),
-            "LogiLedExcludeKeysFromBitmap failed"
-        );
+    pub fn exclude_keys_from_bitmap(&self, keys: &mut [Key]) -> Result<(), Error> {
+        self.backend.exclude_keys_from_bitmap(keys)?;
+        self.shadow().excluded_keys = keys.to_vec();
+        Ok(())
     }
 
     /// Sets the key `key` to the desired color.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     ///
-    /// let sdk = lightsync::init().unwrap();
-    /// sdk.set_lighting_for_key(lightsync::Key::J, Color::new(100, 0, 0));
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting_for_key(lightsync::Key::J, Color::new(100, 0, 0))?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn set_lighting_for_key(&self, key: Key, color: Color) {
-        assert!(
-            raw::set_lighting_for_key_with_key_name(key, color),
-            "LogiLedSetLightingForKeyWithKeyName failed"
-        );
+    pub fn set_lighting_for_key(&self, key: Key, color: Color) -> Result<(), Error> {
+        self.backend
+            .set_lighting_for_key_with_key_name(key, color)?;
+        self.shadow().set_key(key, color);
+        Ok(())
     }
 
-    /// Saves the current color of `key`, which can later be restored with `restore_lighting_for_key()`.
+    /// Sets the key with the code `code` to the desired color.
+    ///
+    /// `code` can be a `ScanCode`, `HidCode` or `QuartzCode`, so keys can be lit straight from input events.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, HidCode, ScanCode};
+    ///
+    /// let sdk = lightsync::Sdk::init()?;
+    /// sdk.set_lighting_for_code(ScanCode(0x24), Color::new(100, 0, 0))?;
+    /// sdk.set_lighting_for_code(HidCode(0x0D), Color::new(100, 0, 0))?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
+    pub fn set_lighting_for_code(
+        &self,
+        code: impl Into<KeyCode>,
+        color: Color,
+    ) -> Result<(), Error> {
+        let code = code.into();
+        match code {
+            KeyCode::Scan(ScanCode(code)) => self
+                .backend
+                .set_lighting_for_key_with_scan_code(code.into(), color)?,
+            KeyCode::Hid(HidCode(code)) => self
+                .backend
+                .set_lighting_for_key_with_hid_code(code.into(), color)?,
+            KeyCode::Quartz(QuartzCode(code)) => self
+                .backend
+                .set_lighting_for_key_with_quartz_code(code.into(), color)?,
+        }
+        if let Some(key) = Key::from_code(code) {
+            self.shadow().set_key(key, color);
+        }
+        Ok(())
+    }
+
+    /// Saves the current color of `key`, which can later be restored with `restore_lighting_for_key()`.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    ///
+    /// # Example
+    /// ```no_run
     /// use lightsync::Color;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Make M red
-    /// sdk.set_lighting_for_key(lightsync::Key::M, Color::new(100, 0, 0));
-    /// sdk.save_lighting_for_key(lightsync::Key::M);
+    /// sdk.set_lighting_for_key(lightsync::Key::M, Color::new(100, 0, 0))?;
+    /// sdk.save_lighting_for_key(lightsync::Key::M)?;
     /// // Make the rest of the keyboard green
-    /// sdk.set_lighting(Color::new(0, 100, 0));
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
     /// // Change M back to red
-    /// sdk.restore_lighting_for_key(lightsync::Key::M);
+    /// sdk.restore_lighting_for_key(lightsync::Key::M)?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn save_lighting_for_key(&self, key: Key) {
-        assert!(
-            raw::save_lighting_for_key(key),
-            "LogiLedSaveLightingForKey failed"
-        );
+    pub fn save_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.save_lighting_for_key(key)?;
+        let mut shadow = self.shadow();
+        let color = shadow.lighting.key_color(key);
+        shadow.saved_keys.insert(key, color);
+        Ok(())
     }
 
     /// Restores the saved color for `key`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Make M red
-    /// sdk.set_lighting_for_key(lightsync::Key::M, Color::new(100, 0, 0));
-    /// sdk.save_lighting_for_key(lightsync::Key::M);
+    /// sdk.set_lighting_for_key(lightsync::Key::M, Color::new(100, 0, 0))?;
+    /// sdk.save_lighting_for_key(lightsync::Key::M)?;
     /// // Make the rest of the keyboard green
-    /// sdk.set_lighting(Color::new(0, 100, 0));
+    /// sdk.set_lighting(Color::new(0, 100, 0))?;
     /// // Change M back to red
-    /// sdk.restore_lighting_for_key(lightsync::Key::M);
+    /// sdk.restore_lighting_for_key(lightsync::Key::M)?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn restore_lighting_for_key(&self, key: Key) {
-        assert!(
-            raw::restore_lighting_for_key(key),
-            "LogiLedRestoreLightingForKey failed"
-        );
+    pub fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.restore_lighting_for_key(key)?;
+        let mut shadow = self.shadow();
+        if let Some(&color) = shadow.saved_keys.get(&key) {
+            shadow.lighting.set_key(lighting::ALL, key, color);
+        }
+        Ok(())
     }
 
     /// Starts a flashing effect at `interval` for `duration` on `key`.
     ///
     /// If you use a duration of 0, the effect will play until stopped with `stop_effects_on_key()`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::DurationTooLong` if a duration is longer than `i32::MAX` milliseconds,
+    /// or `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Note that this doesn't pause the thread.
-    /// sdk.flash_key(lightsync::Key::H, Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500));
+    /// sdk.flash_key(lightsync::Key::H, Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500))?;
     /// thread::sleep(Duration::from_millis(2000));
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn flash_key(&self, key: Key, color: Color, duration: Duration, interval: Duration) {
-        assert!(
-            raw::flash_single_key(
-                key,
-                color,
-                duration
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-                interval
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK")
-            ),
-            "LogiLedFlashSingleKey failed"
-        );
+    pub fn flash_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: Duration,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        self.backend
+            .flash_single_key(key, color, millis(duration)?, millis(interval)?)
     }
 
     /// Starts a pulsing effect from `start` to `end` for `duration` on `key`.
@@ -743,20 +1382,22 @@ impl Sdk {
     /// - `duration`: The duration of the effect.
     /// - `infinite`: Whether to run the effect until stopped.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::DurationTooLong` if a duration is longer than `i32::MAX` milliseconds,
+    /// or `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Note that this doesn't pause the thread.
-    /// sdk.pulse_key(lightsync::Key::H, Color::new(100, 0, 0), Color::new(0, 100, 0), Duration::from_millis(2000), false);
+    /// sdk.pulse_key(lightsync::Key::H, Color::new(100, 0, 0), Color::new(0, 100, 0), Duration::from_millis(2000), false)?;
     /// thread::sleep(Duration::from_millis(2000));
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
     pub fn pulse_key(
         &self,
@@ -765,47 +1406,117 @@ impl Sdk {
         end: Color,
         duration: Duration,
         infinite: bool,
-    ) {
-        assert!(
-            raw::pulse_single_key(
-                key,
-                start,
-                end,
-                duration
-                    .as_millis()
-                    .try_into()
-                    .expect("Duration is too long to pass to SDK"),
-                infinite,
-            ),
-            "LogiLedPulseSingleKey failed"
-        );
+    ) -> Result<(), Error> {
+        self.backend
+            .pulse_single_key(key, start, end, millis(duration)?, infinite)
     }
 
     /// Stops any ongoing effects on `key`.
     ///
-    /// # Panics
-    /// Panics if the connection to the SDK has been lost.
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
     ///
     /// # Example
-    /// ```
+    /// ```no_run
     /// use lightsync::Color;
     /// use std::thread;
     /// use std::time::Duration;
     ///
-    /// let sdk = lightsync::init().unwrap();
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Flash the key endlessly
-    /// sdk.flash_key(lightsync::Key::H, Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500));
+    /// sdk.flash_key(lightsync::Key::H, Color::new(100, 0, 0), Duration::from_millis(2000), Duration::from_millis(500))?;
     /// thread::sleep(Duration::from_millis(2000));
     ///
     /// // Stop the flashing
-    /// sdk.stop_effects_on_key(lightsync::Key::H);
+    /// sdk.stop_effects_on_key(lightsync::Key::H)?;
+    /// # Ok::<(), lightsync::Error>(())
+    /// ```
+    pub fn stop_effects_on_key(&self, key: Key) -> Result<(), Error> {
+        self.backend.stop_effects_on_key(key)
+    }
+
+    /// Gets a value of any `ConfigOption` type chosen by the user, or `default` if not chosen.
+    ///
+    /// Unlike `get_option()` and the other free functions, this reads the option through the backend,
+    /// so it works the same with a `ConfigFile` as with the Logitech SDK.
+    /// Paths and labels are the same as for `get_option()`.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with, like `Error::NulInName` if `path` or `label` contains
+    /// any null bytes.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lightsync::{Color, Sdk};
+    ///
+    /// let sdk = Sdk::init_with_name("foo")?;
+    /// let background = sdk.get_option("Colors/Background", Color::BLUE, Some("Background"))?;
+    /// let flash = sdk.get_option("Flash", true, None)?;
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
-    pub fn stop_effects_on_key(&self, key: Key) {
-        assert!(
-            raw::stop_effects_on_key(key),
-            "LogiLedStopEffectsOnKey failed"
-        )
+    pub fn get_option<T: ConfigOption>(
+        &self,
+        path: &str,
+        default: T,
+        label: Option<&str>,
+    ) -> Result<T, Error> {
+        config::labelled(&self.backend, path, label, |backend| {
+            T::get(backend, path, default)
+        })
+    }
+
+    /// Gets a number between `min` and `max` chosen by the user, or `default` if not chosen,
+    /// through the backend like `get_option()`.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with, like `Error::NulInName` if `path` or `label` contains
+    /// any null bytes.
+    pub fn get_range_option(
+        &self,
+        path: &str,
+        default: i32,
+        min: i32,
+        max: i32,
+        label: Option<&str>,
+    ) -> Result<i32, Error> {
+        config::labelled(&self.backend, path, label, |backend| {
+            backend.get_config_option_range(path, default, min, max)
+        })
+    }
+
+    /// Gets a key chosen by the user, by the name the SDK shows for it (e.g. "F5"), or `default` if not chosen,
+    /// through the backend like `get_option()`.
+    ///
+    /// # Errors
+    /// Returns whatever error the backend fails with, like `Error::NulInName` if `path`, `default` or `label`
+    /// contains any null bytes.
+    pub fn get_key_input_option(
+        &self,
+        path: &str,
+        default: &str,
+        label: Option<&str>,
+    ) -> Result<String, Error> {
+        config::labelled(&self.backend, path, label, |backend| {
+            backend.get_config_option_key_input(path, default)
+        })
+    }
+
+    /// Gets which of `choices` was chosen by the user, or the one at index `default` if not chosen,
+    /// through the backend like `get_option()`.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidArgument` if `default` isn't the index of one of `choices`, or whatever error
+    /// the backend fails with, like `Error::NulInName` if `path`, `label` or any of the choices contains any
+    /// null bytes.
+    pub fn get_select_option<'a, T: AsRef<str>>(
+        &self,
+        path: &str,
+        default: usize,
+        choices: &'a [T],
+        label: Option<&str>,
+    ) -> Result<&'a T, Error> {
+        select_option(&self.backend, path, default, choices, label)
     }
 
     /// Restores the last saved lighting and frees memory used by the SDK.
@@ -813,19 +1524,25 @@ impl Sdk {
     /// Dropping the Sdk will have the same effect.
     ///
     /// # Example
-    /// ```
-    /// let sdk = lightsync::init().unwrap();
+    /// ```no_run
+    /// let sdk = lightsync::Sdk::init()?;
     ///
     /// // Sdk object is consumed by shutdown and can't be mistakenly used afterwards.
     /// sdk.shutdown();
+    /// # Ok::<(), lightsync::Error>(())
     /// ```
     pub fn shutdown(self) {
         drop(self);
     }
 }
 
-// TODO: I could maybe define a trait `ConfigOption` which makes these functions generic.
-// not sure if that's a good thing though and some (range) have custom config
+/// Converts `duration` to a number of milliseconds which can be passed to the SDK.
+fn millis(duration: Duration) -> Result<i32, Error> {
+    duration
+        .as_millis()
+        .try_into()
+        .map_err(|_| Error::DurationTooLong)
+}
 
 /// Gets a color chosen by the user, or `default` if not chosen.
 ///
@@ -837,11 +1554,15 @@ impl Sdk {
 /// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
 ///
 /// You can also specify a label, if you want it to be different to the path.
-pub fn get_color_option(path: &str, default: Color, label: Option<&str>) -> Color {
-    if let Some(label) = label {
-        raw::set_config_option_label(path, label);
-    }
-    raw::get_config_option_color(path, default)
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
+pub fn get_color_option(path: &str, default: Color, label: Option<&str>) -> Result<Color, Error> {
+    get_option(path, default, label)
 }
 
 /// Gets a boolean chosen by the user, or `default` if not chosen.
@@ -854,11 +1575,15 @@ pub fn get_color_option(path: &str, default: Color, label: Option<&str>) -> Colo
 /// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
 ///
 /// You can also specify a label, if you want it to be different to the path.
-pub fn get_boolean_option(path: &str, default: bool, label: Option<&str>) -> bool {
-    if let Some(label) = label {
-        raw::set_config_option_label(path, label);
-    }
-    raw::get_config_option_bool(path, default)
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
+pub fn get_boolean_option(path: &str, default: bool, label: Option<&str>) -> Result<bool, Error> {
+    get_option(path, default, label)
 }
 
 /// Gets a number chosen by the user, or `default` if not chosen.
@@ -871,11 +1596,15 @@ pub fn get_boolean_option(path: &str, default: bool, label: Option<&str>) -> boo
 /// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
 ///
 /// You can also specify a label, if you want it to be different to the path.
-pub fn get_number_option(path: &str, default: f64, label: Option<&str>) -> f64 {
-    if let Some(label) = label {
-        raw::set_config_option_label(path, label);
-    }
-    raw::get_config_option_number(path, default)
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
+pub fn get_number_option(path: &str, default: f64, label: Option<&str>) -> Result<f64, Error> {
+    get_option(path, default, label)
 }
 
 /// Gets a number within a range chosen by the user, or `default` if not chosen.
@@ -888,11 +1617,23 @@ pub fn get_number_option(path: &str, default: f64, label: Option<&str>) -> f64 {
 /// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
 ///
 /// You can also specify a label, if you want it to be different to the path.
-pub fn get_range_option(path: &str, default: i32, min: i32, max: i32, label: Option<&str>) -> i32 {
-    if let Some(label) = label {
-        raw::set_config_option_label(path, label);
-    }
-    raw::get_config_option_range(path, default, min, max)
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_range_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
+pub fn get_range_option(
+    path: &str,
+    default: i32,
+    min: i32,
+    max: i32,
+    label: Option<&str>,
+) -> Result<i32, Error> {
+    config::labelled(&Logitech, path, label, |backend| {
+        backend.get_config_option_range(path, default, min, max)
+    })
 }
 
 /// Gets a rectangle chosen by the user, or `default` if not chosen.
@@ -905,16 +1646,215 @@ pub fn get_range_option(path: &str, default: i32, min: i32, max: i32, label: Opt
 /// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
 ///
 /// You can also specify a label, if you want it to be different to the path.
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path` or `label` contains any null bytes.
 pub fn get_rect_option(
     path: &str,
     default: (i32, i32, i32, i32),
     label: Option<&str>,
-) -> (i32, i32, i32, i32) {
-    if let Some(label) = label {
-        raw::set_config_option_label(path, label);
-    }
-    raw::get_config_option_rect(path, default.0, default.1, default.2, default.3)
+) -> Result<(i32, i32, i32, i32), Error> {
+    get_option(path, default, label)
+}
+
+/// Gets a string chosen by the user, or `default` if not chosen.
+///
+/// Path is the identifier for the option,
+/// which can be either just a name (e.g. "Terrorist")
+/// or a path in a two level tree (e.g. "Colors/Terrorist.")
+///
+/// If the path is two levels deep, it will be placed inside a section
+/// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
+///
+/// You can also specify a label, if you want it to be different to the path.
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path`, `default` or `label` contains any null bytes.
+pub fn get_string_option(path: &str, default: &str, label: Option<&str>) -> Result<String, Error> {
+    get_option(path, default.to_owned(), label)
+}
+
+/// Gets a key chosen by the user, or `default` if not chosen.
+///
+/// Keys are given by the name the SDK shows for them (e.g. "F5").
+///
+/// Path is the identifier for the option,
+/// which can be either just a name (e.g. "Terrorist")
+/// or a path in a two level tree (e.g. "Colors/Terrorist.")
+///
+/// If the path is two levels deep, it will be placed inside a section
+/// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
+///
+/// You can also specify a label, if you want it to be different to the path.
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_key_input_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path`, `default` or `label` contains any null bytes.
+pub fn get_key_input_option(
+    path: &str,
+    default: &str,
+    label: Option<&str>,
+) -> Result<String, Error> {
+    config::labelled(&Logitech, path, label, |backend| {
+        backend.get_config_option_key_input(path, default)
+    })
+}
+
+/// Gets which of `choices` was chosen by the user, or the one at index `default` if not chosen.
+///
+/// Each choice is shown to the user as its `AsRef<str>` representation, so they can be strings or a type
+/// of your own.
+///
+/// Path is the identifier for the option,
+/// which can be either just a name (e.g. "Terrorist")
+/// or a path in a two level tree (e.g. "Colors/Terrorist.")
+///
+/// If the path is two levels deep, it will be placed inside a section
+/// (e.g. "Colors/Terrorist" would be the option "Terrorist" in the section "Colors").
+///
+/// You can also specify a label, if you want it to be different to the path.
+///
+/// This always reads the option from the Logitech SDK. Use `Sdk::get_select_option()` to read it through the backend
+/// of an `Sdk`, like a `ConfigFile`.
+///
+/// # Errors
+/// Returns `Error::InvalidArgument` if `default` isn't the index of one of `choices`,
+/// `Error::NotInitialized` if there is no `Sdk` using the `Logitech` backend,
+/// or `Error::NulInName` if `path`, `label` or any of the choices contains any null bytes.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{get_select_option, Sdk};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Difficulty {
+///     Easy,
+///     Hard,
+/// }
+///
+/// impl AsRef<str> for Difficulty {
+///     fn as_ref(&self) -> &str {
+///         match self {
+///             Difficulty::Easy => "Easy",
+///             Difficulty::Hard => "Hard",
+///         }
+///     }
+/// }
+///
+/// let _sdk = Sdk::init()?;
+/// let choices = [Difficulty::Easy, Difficulty::Hard];
+/// let difficulty = get_select_option("Difficulty", 0, &choices, None)?;
+/// println!("Playing on {:?}", difficulty);
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+pub fn get_select_option<'a, T: AsRef<str>>(
+    path: &str,
+    default: usize,
+    choices: &'a [T],
+    label: Option<&str>,
+) -> Result<&'a T, Error> {
+    select_option(&Logitech, path, default, choices, label)
+}
+
+/// Gets which of `choices` was chosen for the option at `path` on `backend`, or the one at index `default`.
+fn select_option<'a, B: LightingBackend, T: AsRef<str>>(
+    backend: &B,
+    path: &str,
+    default: usize,
+    choices: &'a [T],
+    label: Option<&str>,
+) -> Result<&'a T, Error> {
+    let names: Vec<&str> = choices.iter().map(AsRef::as_ref).collect();
+    let default_name = *names.get(default).ok_or(Error::InvalidArgument)?;
+    let chosen = config::labelled(backend, path, label, |backend| {
+        backend.get_config_option_select(path, default_name, &names)
+    })?;
+    Ok(select(choices, default, &chosen))
+}
+
+/// Finds the choice named `chosen`, or the one at index `default` if there isn't one.
+fn select<'a, T: AsRef<str>>(choices: &'a [T], default: usize, chosen: &str) -> &'a T {
+    choices
+        .iter()
+        .find(|choice| choice.as_ref() == chosen)
+        .unwrap_or(&choices[default])
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn select_choice() {
+        let choices = ["Easy", "Medium", "Hard"];
+        assert_eq!(select(&choices, 0, "Hard"), &"Hard");
+        assert_eq!(select(&choices, 1, "Impossible"), &"Medium");
+        assert_eq!(
+            get_select_option("Difficulty", 3, &choices, None),
+            Err(Error::InvalidArgument)
+        );
+        assert_eq!(
+            get_select_option::<&str>("Difficulty", 0, &[], None),
+            Err(Error::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn bitmap_position_round_trip() {
+        for row in 0..BITMAP_HEIGHT as usize {
+            for col in 0..BITMAP_WIDTH as usize {
+                if let Some(key) = Key::at(row, col) {
+                    assert_eq!(key.bitmap_position(), Some((row, col)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn all_keys() {
+        let keys: Vec<Key> = (0..=0xF_FFFF)
+            .filter_map(|code| Key::try_from(code).ok())
+            .collect();
+        assert_eq!(keys, Key::ALL);
+
+        assert_eq!("F1".parse(), Ok(Key::F1));
+        assert_eq!("numenter".parse(), Ok(Key::NumEnter));
+        assert_eq!("Nope".parse::<Key>(), Err(Error::InvalidArgument));
+    }
+
+    #[test]
+    fn keys_without_bitmap_cells() {
+        let missing: Vec<Key> = (0..=0xF_FFFF)
+            .filter_map(|code| Key::try_from(code).ok())
+            .filter(|key| key.bitmap_position().is_none())
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                Key::G1,
+                Key::G2,
+                Key::G3,
+                Key::G4,
+                Key::G5,
+                Key::G6,
+                Key::G7,
+                Key::G8,
+                Key::G9,
+                Key::GLogo,
+                Key::GBadge,
+            ]
+        );
+    }
+}
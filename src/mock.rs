@@ -0,0 +1,713 @@
+//! A `LightingBackend` which simulates devices in memory, for testing code which uses `Sdk`.
+//!
+//! # Example
+//! ```
+//! use lightsync::mock::{Call, MockBackend};
+//! use lightsync::{Color, Key, Sdk};
+//!
+//! let mock = MockBackend::new();
+//! let sdk = Sdk::with_backend(mock.clone())?;
+//!
+//! sdk.set_lighting_for_key(Key::W, Color::new(100, 0, 0))?;
+//!
+//! assert_eq!(mock.key_color(Key::W), Color::new(100, 0, 0));
+//! assert_eq!(mock.calls().last(), Some(&Call::SetLightingForKeyWithKeyName(Key::W, Color::new(100, 0, 0))));
+//! # Ok::<(), lightsync::Error>(())
+//! ```
+
+use super::backend::LightingBackend;
+use super::shadow::Snapshot;
+use super::{
+    lighting, Bitmap, Color, DeviceType, Error, HidCode, Key, KeyCode, QuartzCode, ScanCode,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A call made to a `MockBackend`, with the arguments it was called with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    Init,
+    InitWithName(String),
+    GetSdkVersion,
+    SetTargetDevice(i32),
+    SaveCurrentLighting,
+    SetLighting(Color),
+    SetLightingForTargetZone(DeviceType, i32, Color),
+    RestoreLighting,
+    FlashLighting(Color, i32, i32),
+    PulseLighting(Color, i32, i32),
+    StopEffects,
+    SetLightingFromBitmap(Box<Bitmap>),
+    ExcludeKeysFromBitmap(Vec<Key>),
+    SetLightingForKeyWithScanCode(i32, Color),
+    SetLightingForKeyWithHidCode(i32, Color),
+    SetLightingForKeyWithQuartzCode(i32, Color),
+    SetLightingForKeyWithKeyName(Key, Color),
+    SaveLightingForKey(Key),
+    RestoreLightingForKey(Key),
+    FlashSingleKey(Key, Color, i32, i32),
+    PulseSingleKey(Key, Color, Color, i32, bool),
+    StopEffectsOnKey(Key),
+    Shutdown,
+    SetConfigOptionLabel(String, String),
+    GetConfigOptionNumber(String),
+    GetConfigOptionBool(String),
+    GetConfigOptionColor(String),
+    GetConfigOptionRange(String),
+    GetConfigOptionRect(String),
+    GetConfigOptionString(String),
+    GetConfigOptionKeyInput(String),
+    GetConfigOptionSelect(String, Vec<String>),
+}
+
+#[derive(Debug)]
+struct State {
+    initialized: bool,
+    name: Option<String>,
+    version: (i32, i32, i32),
+    /// Whether calls are currently failing as if the connection had been lost.
+    disconnected: bool,
+    /// The number of calls left before `disconnected` is set.
+    calls_until_disconnect: Option<usize>,
+    calls: Vec<Call>,
+    target_device: i32,
+    lighting: Snapshot,
+    saved: Snapshot,
+    saved_keys: HashMap<Key, Color>,
+    excluded_keys: Vec<Key>,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            initialized: false,
+            name: None,
+            version: (0, 0, 0),
+            disconnected: false,
+            calls_until_disconnect: None,
+            calls: Vec::new(),
+            target_device: lighting::ALL,
+            lighting: Snapshot::default(),
+            saved: Snapshot::default(),
+            saved_keys: HashMap::new(),
+            excluded_keys: Vec::new(),
+        }
+    }
+}
+
+impl State {
+    /// Logs `call` and returns whether the connection is still alive.
+    fn log(&mut self, call: Call) -> Result<(), Error> {
+        self.calls.push(call);
+        if let Some(remaining) = self.calls_until_disconnect {
+            if remaining == 0 {
+                self.disconnected = true;
+                self.calls_until_disconnect = None;
+            } else {
+                self.calls_until_disconnect = Some(remaining - 1);
+            }
+        }
+        if self.disconnected {
+            Err(Error::ConnectionLost)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Logs `call` and returns whether it should succeed.
+    ///
+    /// Like the real SDK, calls fail if the backend hasn't been initialized or the connection has been lost.
+    fn call(&mut self, call: Call) -> Result<(), Error> {
+        self.log(call)?;
+        if self.initialized {
+            Ok(())
+        } else {
+            Err(Error::NotInitialized)
+        }
+    }
+
+    fn init(&mut self, call: Call) -> Result<(), Error> {
+        self.log(call)?;
+        if self.initialized {
+            return Err(Error::AlreadyRunning);
+        }
+        self.initialized = true;
+        self.saved = self.lighting.clone();
+        Ok(())
+    }
+
+    /// Sets the color of the key with the code `code`, if there is one and per-key devices are targeted.
+    fn set_key_with_code<C: Into<KeyCode>>(
+        &mut self,
+        code: i32,
+        code_type: fn(u16) -> C,
+        color: Color,
+    ) {
+        let key = u16::try_from(code)
+            .ok()
+            .and_then(|code| Key::from_code(code_type(code)));
+        if let Some(key) = key {
+            self.lighting.set_key(self.target_device, key, color);
+        }
+    }
+}
+
+/// A `LightingBackend` which keeps the lighting of simulated devices in memory and records every call made to it.
+///
+/// `MockBackend` is a handle to shared state, so a clone can be kept to inspect
+/// the backend after the original has been moved into an `Sdk`.
+///
+/// The per-key lighting is stored in a 21x6 framebuffer laid out like the bitmap passed to
+/// `Sdk::set_lighting_from_bitmap()`. Devices without per-key lighting have a single color,
+/// which can be overridden for specific zones of each `DeviceType`.
+/// Only the kinds of devices selected with `set_target_device()` are affected by each call.
+///
+/// Calls made before initialization fail, as do calls made after the connection has been
+/// broken with `disconnect()` or `disconnect_after()`.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockBackend {
+    /// Creates a new `MockBackend` with every device turned off.
+    pub fn new() -> MockBackend {
+        MockBackend::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A test panicking while holding the lock shouldn't stop the state from being inspected.
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns every call made so far, in order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    /// Forgets the calls made so far.
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    /// Returns whether the backend is currently initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.state().initialized
+    }
+
+    /// Returns the name passed to `init_with_name()`, if any.
+    pub fn name(&self) -> Option<String> {
+        self.state().name.clone()
+    }
+
+    /// Sets the version returned by `get_sdk_version()`. It defaults to `(0, 0, 0)`.
+    pub fn set_version(&self, version: (i32, i32, i32)) {
+        self.state().version = version;
+    }
+
+    /// Makes every call from now on fail, as if the connection to the SDK had been lost.
+    pub fn disconnect(&self) {
+        self.state().disconnected = true;
+    }
+
+    /// Lets the next `calls` calls succeed, and then makes every call after that fail.
+    pub fn disconnect_after(&self, calls: usize) {
+        self.state().calls_until_disconnect = Some(calls);
+    }
+
+    /// Undoes `disconnect()` or `disconnect_after()`.
+    pub fn reconnect(&self) {
+        let mut state = self.state();
+        state.disconnected = false;
+        state.calls_until_disconnect = None;
+    }
+
+    /// Returns the kinds of devices currently targeted, as a combination of the constants in `lighting`.
+    pub fn target_device(&self) -> i32 {
+        self.state().target_device
+    }
+
+    /// Returns the current lighting of every kind of device.
+    pub fn snapshot(&self) -> Snapshot {
+        self.state().lighting.clone()
+    }
+
+    /// Returns the current color of `key` on per-key devices.
+    pub fn key_color(&self, key: Key) -> Color {
+        self.state().lighting.key_color(key)
+    }
+
+    /// Returns the current color of the framebuffer cell at `row` and `col`.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is outside the 21x6 grid.
+    pub fn cell_color(&self, row: usize, col: usize) -> Color {
+        self.state().lighting.cell_color(row, col)
+    }
+
+    /// Returns the current color of devices without per-key lighting.
+    pub fn color(&self) -> Color {
+        self.state().lighting.color()
+    }
+
+    /// Returns the current color of zone `zone` on devices of type `device_type`.
+    pub fn zone_color(&self, device_type: DeviceType, zone: i32) -> Color {
+        self.state().lighting.zone_color(device_type, zone)
+    }
+
+    /// Returns the keys last passed to `exclude_keys_from_bitmap()`.
+    pub fn excluded_keys(&self) -> Vec<Key> {
+        self.state().excluded_keys.clone()
+    }
+}
+
+impl LightingBackend for MockBackend {
+    fn init(&self) -> Result<(), Error> {
+        self.state().init(Call::Init)
+    }
+
+    fn init_with_name(&self, name: &str) -> Result<(), Error> {
+        let mut state = self.state();
+        state.init(Call::InitWithName(name.to_string()))?;
+        // The name is only set the first time.
+        if state.name.is_none() {
+            state.name = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    fn get_sdk_version(&self) -> Result<(i32, i32, i32), Error> {
+        let mut state = self.state();
+        state.call(Call::GetSdkVersion)?;
+        Ok(state.version)
+    }
+
+    fn set_target_device(&self, target_device: i32) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetTargetDevice(target_device))?;
+        if target_device & !lighting::ALL != 0 {
+            return Err(Error::InvalidArgument);
+        }
+        state.target_device = target_device;
+        Ok(())
+    }
+
+    fn save_current_lighting(&self) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SaveCurrentLighting)?;
+        state.saved = state.lighting.clone();
+        Ok(())
+    }
+
+    fn set_lighting(&self, color: Color) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLighting(color))?;
+        let target_device = state.target_device;
+        state.lighting.set_lighting(target_device, color);
+        Ok(())
+    }
+
+    fn set_lighting_for_target_zone(
+        &self,
+        device_type: DeviceType,
+        zone: i32,
+        color: Color,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingForTargetZone(device_type, zone, color))?;
+        state.lighting.set_zone(device_type, zone, color);
+        Ok(())
+    }
+
+    fn restore_lighting(&self) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::RestoreLighting)?;
+        state.lighting = state.saved.clone();
+        Ok(())
+    }
+
+    fn flash_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.state()
+            .call(Call::FlashLighting(color, duration, interval))
+    }
+
+    fn pulse_lighting(&self, color: Color, duration: i32, interval: i32) -> Result<(), Error> {
+        self.state()
+            .call(Call::PulseLighting(color, duration, interval))
+    }
+
+    fn stop_effects(&self) -> Result<(), Error> {
+        self.state().call(Call::StopEffects)
+    }
+
+    fn set_lighting_from_bitmap(&self, bitmap: &Bitmap) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingFromBitmap(Box::new(*bitmap)))?;
+        let State {
+            target_device,
+            excluded_keys,
+            lighting,
+            ..
+        } = &mut *state;
+        lighting.set_bitmap(*target_device, bitmap, excluded_keys);
+        Ok(())
+    }
+
+    fn exclude_keys_from_bitmap(&self, keys: &[Key]) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::ExcludeKeysFromBitmap(keys.to_vec()))?;
+        state.excluded_keys = keys.to_vec();
+        Ok(())
+    }
+
+    fn set_lighting_for_key_with_scan_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingForKeyWithScanCode(code, color))?;
+        state.set_key_with_code(code, ScanCode, color);
+        Ok(())
+    }
+
+    fn set_lighting_for_key_with_hid_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingForKeyWithHidCode(code, color))?;
+        state.set_key_with_code(code, HidCode, color);
+        Ok(())
+    }
+
+    fn set_lighting_for_key_with_quartz_code(&self, code: i32, color: Color) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingForKeyWithQuartzCode(code, color))?;
+        state.set_key_with_code(code, QuartzCode, color);
+        Ok(())
+    }
+
+    fn set_lighting_for_key_with_key_name(&self, key: Key, color: Color) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SetLightingForKeyWithKeyName(key, color))?;
+        let target_device = state.target_device;
+        state.lighting.set_key(target_device, key, color);
+        Ok(())
+    }
+
+    fn save_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::SaveLightingForKey(key))?;
+        let color = state.lighting.key_color(key);
+        state.saved_keys.insert(key, color);
+        Ok(())
+    }
+
+    fn restore_lighting_for_key(&self, key: Key) -> Result<(), Error> {
+        let mut state = self.state();
+        state.call(Call::RestoreLightingForKey(key))?;
+        if let Some(color) = state.saved_keys.get(&key).cloned() {
+            state.lighting.set_key(lighting::ALL, key, color);
+        }
+        Ok(())
+    }
+
+    fn flash_single_key(
+        &self,
+        key: Key,
+        color: Color,
+        duration: i32,
+        interval: i32,
+    ) -> Result<(), Error> {
+        self.state()
+            .call(Call::FlashSingleKey(key, color, duration, interval))
+    }
+
+    fn pulse_single_key(
+        &self,
+        key: Key,
+        start: Color,
+        end: Color,
+        duration: i32,
+        infinite: bool,
+    ) -> Result<(), Error> {
+        self.state()
+            .call(Call::PulseSingleKey(key, start, end, duration, infinite))
+    }
+
+    fn stop_effects_on_key(&self, key: Key) -> Result<(), Error> {
+        self.state().call(Call::StopEffectsOnKey(key))
+    }
+
+    fn shutdown(&self) {
+        let mut state = self.state();
+        if state.call(Call::Shutdown).is_ok() {
+            state.lighting = state.saved.clone();
+        }
+        state.initialized = false;
+    }
+
+    fn set_config_option_label(&self, path: &str, label: &str) -> Result<(), Error> {
+        self.state().call(Call::SetConfigOptionLabel(
+            path.to_string(),
+            label.to_string(),
+        ))
+    }
+
+    fn get_config_option_number(&self, path: &str, default: f64) -> Result<f64, Error> {
+        self.state()
+            .call(Call::GetConfigOptionNumber(path.to_string()))?;
+        Ok(default)
+    }
+
+    fn get_config_option_bool(&self, path: &str, default: bool) -> Result<bool, Error> {
+        self.state()
+            .call(Call::GetConfigOptionBool(path.to_string()))?;
+        Ok(default)
+    }
+
+    fn get_config_option_color(&self, path: &str, default: Color) -> Result<Color, Error> {
+        self.state()
+            .call(Call::GetConfigOptionColor(path.to_string()))?;
+        Ok(default)
+    }
+
+    fn get_config_option_range(
+        &self,
+        path: &str,
+        default: i32,
+        _min: i32,
+        _max: i32,
+    ) -> Result<i32, Error> {
+        self.state()
+            .call(Call::GetConfigOptionRange(path.to_string()))?;
+        Ok(default)
+    }
+
+    fn get_config_option_rect(
+        &self,
+        path: &str,
+        default: (i32, i32, i32, i32),
+    ) -> Result<(i32, i32, i32, i32), Error> {
+        self.state()
+            .call(Call::GetConfigOptionRect(path.to_string()))?;
+        Ok(default)
+    }
+
+    fn get_config_option_string(&self, path: &str, default: &str) -> Result<String, Error> {
+        self.state()
+            .call(Call::GetConfigOptionString(path.to_string()))?;
+        Ok(default.to_string())
+    }
+
+    fn get_config_option_key_input(&self, path: &str, default: &str) -> Result<String, Error> {
+        self.state()
+            .call(Call::GetConfigOptionKeyInput(path.to_string()))?;
+        Ok(default.to_string())
+    }
+
+    fn get_config_option_select(
+        &self,
+        path: &str,
+        default: &str,
+        values: &[&str],
+    ) -> Result<String, Error> {
+        self.state().call(Call::GetConfigOptionSelect(
+            path.to_string(),
+            values.iter().map(|value| value.to_string()).collect(),
+        ))?;
+        Ok(default.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Sdk, TargetDevices};
+
+    #[test]
+    fn init_fails_while_running() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        assert_eq!(
+            Sdk::with_backend(mock.clone()).err(),
+            Some(Error::AlreadyRunning)
+        );
+        drop(sdk);
+        assert!(!mock.is_initialized());
+        assert!(Sdk::with_backend_and_name(mock.clone(), "foo").is_ok());
+        assert_eq!(mock.name().as_deref(), Some("foo"));
+    }
+
+    #[test]
+    fn set_lighting_respects_target() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+
+        sdk.set_target_devices(TargetDevices::PERKEY_RGB).unwrap();
+        sdk.set_lighting(Color::new(100, 0, 0)).unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::new(100, 0, 0));
+        assert_eq!(mock.zone_color(DeviceType::Mouse, 1), Color::BLACK);
+
+        sdk.set_target_devices(TargetDevices::RGB).unwrap();
+        sdk.set_lighting(Color::new(0, 100, 0)).unwrap();
+        sdk.set_lighting_for_zone(DeviceType::Mouse, 1, Color::new(0, 0, 100))
+            .unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::new(100, 0, 0));
+        assert_eq!(mock.zone_color(DeviceType::Mouse, 0), Color::new(0, 100, 0));
+        assert_eq!(mock.zone_color(DeviceType::Mouse, 1), Color::new(0, 0, 100));
+    }
+
+    #[test]
+    fn bitmap_skips_transparent_and_excluded_keys() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting(Color::new(0, 0, 100)).unwrap();
+        sdk.exclude_keys_from_bitmap(&mut [Key::F1]).unwrap();
+
+        let mut bitmap = Bitmap::filled(Color::new(100, 0, 0));
+        bitmap.set_alpha(Key::Esc, 0);
+        sdk.set_lighting_from_bitmap(&bitmap).unwrap();
+
+        assert_eq!(mock.key_color(Key::Esc), Color::new(0, 0, 100));
+        assert_eq!(mock.key_color(Key::F1), Color::new(0, 0, 100));
+        assert_eq!(mock.key_color(Key::F2), Color::new(100, 0, 0));
+        assert_eq!(mock.cell_color(5, 20), Color::new(100, 0, 0));
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting(Color::new(0, 100, 0)).unwrap();
+        sdk.save_lighting().unwrap();
+        sdk.set_lighting_for_key(Key::G1, Color::new(100, 0, 0))
+            .unwrap();
+        sdk.save_lighting_for_key(Key::G1).unwrap();
+        sdk.set_lighting(Color::new(100, 0, 0)).unwrap();
+        assert_eq!(mock.key_color(Key::Space), Color::new(100, 0, 0));
+
+        sdk.restore_lighting().unwrap();
+        assert_eq!(mock.key_color(Key::Space), Color::new(0, 100, 0));
+        assert_eq!(mock.key_color(Key::G1), Color::new(0, 100, 0));
+
+        sdk.restore_lighting_for_key(Key::G1).unwrap();
+        assert_eq!(mock.key_color(Key::G1), Color::new(100, 0, 0));
+
+        // Shutting down restores the last saved lighting too.
+        sdk.set_lighting(Color::new(100, 0, 0)).unwrap();
+        sdk.shutdown();
+        assert_eq!(mock.key_color(Key::Space), Color::new(0, 100, 0));
+    }
+
+    #[test]
+    fn set_lighting_for_code() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting_for_code(ScanCode(0x1E), Color::RED)
+            .unwrap();
+        sdk.set_lighting_for_code(HidCode(0x16), Color::GREEN)
+            .unwrap();
+        sdk.set_lighting_for_code(QuartzCode(0x02), Color::BLUE)
+            .unwrap();
+        // There's no key with this code, so it's ignored.
+        sdk.set_lighting_for_code(HidCode(0x00), Color::WHITE)
+            .unwrap();
+
+        assert_eq!(mock.key_color(Key::A), Color::RED);
+        assert_eq!(mock.key_color(Key::S), Color::GREEN);
+        assert_eq!(mock.key_color(Key::D), Color::BLUE);
+        assert_eq!(
+            mock.calls()[1..],
+            [
+                Call::SetLightingForKeyWithScanCode(0x1E, Color::RED),
+                Call::SetLightingForKeyWithHidCode(0x16, Color::GREEN),
+                Call::SetLightingForKeyWithQuartzCode(0x02, Color::BLUE),
+                Call::SetLightingForKeyWithHidCode(0x00, Color::WHITE),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_calls_in_order() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.flash_key(
+            Key::H,
+            Color::new(100, 0, 0),
+            std::time::Duration::from_millis(2000),
+            std::time::Duration::from_millis(500),
+        )
+        .unwrap();
+        sdk.stop_effects_on_key(Key::H).unwrap();
+        drop(sdk);
+
+        assert_eq!(
+            mock.calls(),
+            vec![
+                Call::Init,
+                Call::FlashSingleKey(Key::H, Color::new(100, 0, 0), 2000, 500),
+                Call::StopEffectsOnKey(Key::H),
+                Call::Shutdown,
+            ]
+        );
+    }
+
+    #[test]
+    fn disconnect_after() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        mock.disconnect_after(2);
+        assert_eq!(sdk.set_lighting(Color::new(100, 0, 0)), Ok(()));
+        assert_eq!(sdk.set_lighting(Color::new(100, 0, 0)), Ok(()));
+        assert_eq!(
+            sdk.set_lighting(Color::new(100, 0, 0)),
+            Err(Error::ConnectionLost)
+        );
+
+        mock.reconnect();
+        assert_eq!(mock.set_target_device(0b1000), Err(Error::InvalidArgument));
+        assert_eq!(
+            sdk.flash_lighting(
+                Color::new(100, 0, 0),
+                std::time::Duration::from_secs(u64::MAX),
+                std::time::Duration::from_millis(500)
+            ),
+            Err(Error::DurationTooLong)
+        );
+    }
+
+    #[test]
+    fn config_options_return_defaults() {
+        let mock = MockBackend::new();
+        let _sdk = Sdk::with_backend(mock.clone()).unwrap();
+        assert_eq!(
+            mock.get_config_option_string("Names/Player", "Steve"),
+            Ok("Steve".to_string())
+        );
+        assert_eq!(
+            mock.get_config_option_key_input("Keys/Reload", "R"),
+            Ok("R".to_string())
+        );
+        assert_eq!(
+            mock.get_config_option_select("Difficulty", "Hard", &["Easy", "Hard"]),
+            Ok("Hard".to_string())
+        );
+        assert_eq!(
+            mock.calls()[1..],
+            [
+                Call::GetConfigOptionString("Names/Player".to_string()),
+                Call::GetConfigOptionKeyInput("Keys/Reload".to_string()),
+                Call::GetConfigOptionSelect(
+                    "Difficulty".to_string(),
+                    vec!["Easy".to_string(), "Hard".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_after_shutdown() {
+        let mock = MockBackend::new();
+        drop(Sdk::with_backend(mock.clone()).unwrap());
+        assert_eq!(
+            mock.set_lighting(Color::new(100, 0, 0)),
+            Err(Error::NotInitialized)
+        );
+    }
+}
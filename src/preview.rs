@@ -0,0 +1,431 @@
+use super::{
+    Bitmap, Color, DeviceType, EngineState, HeadlessEngine, Key, LightingBackend, Snapshot,
+};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The devices shown as swatches under the keyboard, and the number of zones shown for each.
+const ZONES: [(DeviceType, &str, i32); 3] = [
+    (DeviceType::Mouse, "Mouse", 3),
+    (DeviceType::Headset, "Headset", 2),
+    (DeviceType::Speaker, "Speaker", 5),
+];
+
+/// How often `TerminalPreview::animate()` redraws.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(33);
+
+const RESET: &str = "\x1b[0m";
+
+/// Returns a label for `key` which fits in a cell.
+pub(crate) fn label(key: Key) -> &'static str {
+    match key {
+        Key::Esc => "Esc",
+        Key::One => "1",
+        Key::Two => "2",
+        Key::Three => "3",
+        Key::Four => "4",
+        Key::Five => "5",
+        Key::Six => "6",
+        Key::Seven => "7",
+        Key::Eight => "8",
+        Key::Nine => "9",
+        Key::Zero => "0",
+        Key::Minus => "-",
+        Key::Equals => "=",
+        Key::Backspace => "Bksp",
+        Key::Tab => "Tab",
+        Key::Q => "Q",
+        Key::W => "W",
+        Key::E => "E",
+        Key::R => "R",
+        Key::T => "T",
+        Key::Y => "Y",
+        Key::U => "U",
+        Key::I => "I",
+        Key::O => "O",
+        Key::P => "P",
+        Key::OpenBracket => "[",
+        Key::CloseBracket => "]",
+        Key::Enter => "Enter",
+        Key::LeftControl => "Ctrl",
+        Key::A => "A",
+        Key::S => "S",
+        Key::D => "D",
+        Key::F => "F",
+        Key::G => "G",
+        Key::H => "H",
+        Key::J => "J",
+        Key::K => "K",
+        Key::L => "L",
+        Key::Semicolon => ";",
+        Key::Apostrophe => "'",
+        Key::Tilde => "`",
+        Key::LeftShift => "Shift",
+        Key::Backslash => "\\",
+        Key::Z => "Z",
+        Key::X => "X",
+        Key::C => "C",
+        Key::V => "V",
+        Key::B => "B",
+        Key::N => "N",
+        Key::M => "M",
+        Key::Comma => ",",
+        Key::Period => ".",
+        Key::ForwardSlash => "/",
+        Key::RightShift => "Shift",
+        Key::NumAsterisk => "*",
+        Key::LeftAlt => "Alt",
+        Key::Space => "Space",
+        Key::CapsLock => "Caps",
+        Key::F1 => "F1",
+        Key::F2 => "F2",
+        Key::F3 => "F3",
+        Key::F4 => "F4",
+        Key::F5 => "F5",
+        Key::F6 => "F6",
+        Key::F7 => "F7",
+        Key::F8 => "F8",
+        Key::F9 => "F9",
+        Key::F10 => "F10",
+        Key::NumLock => "NumLk",
+        Key::ScrollLock => "ScrLk",
+        Key::NumSeven => "7",
+        Key::NumEight => "8",
+        Key::NumNine => "9",
+        Key::NumMinus => "-",
+        Key::NumFour => "4",
+        Key::NumFive => "5",
+        Key::NumSix => "6",
+        Key::NumPlus => "+",
+        Key::NumOne => "1",
+        Key::NumTwo => "2",
+        Key::NumThree => "3",
+        Key::NumZero => "0",
+        Key::NumPeriod => ".",
+        Key::F11 => "F11",
+        Key::F12 => "F12",
+        Key::NumEnter => "Enter",
+        Key::RightControl => "Ctrl",
+        Key::NumSlash => "/",
+        Key::PrintScreen => "PrtSc",
+        Key::RightAlt => "Alt",
+        Key::PauseBreak => "Pause",
+        Key::Home => "Home",
+        Key::ArrowUp => "Up",
+        Key::PageUp => "PgUp",
+        Key::ArrowLeft => "Left",
+        Key::ArrowRight => "Right",
+        Key::End => "End",
+        Key::ArrowDown => "Down",
+        Key::PageDown => "PgDn",
+        Key::Insert => "Ins",
+        Key::KeyboardDelete => "Del",
+        Key::LeftWindows => "Win",
+        Key::RightWindows => "Win",
+        Key::ApplicationSelect => "Menu",
+        Key::G1 => "G1",
+        Key::G2 => "G2",
+        Key::G3 => "G3",
+        Key::G4 => "G4",
+        Key::G5 => "G5",
+        Key::G6 => "G6",
+        Key::G7 => "G7",
+        Key::G8 => "G8",
+        Key::G9 => "G9",
+        Key::GLogo => "Logo",
+        Key::GBadge => "Badge",
+    }
+}
+
+/// Writes the escape codes to draw text on `color`, in black or white, whichever is easier to read.
+fn paint(out: &mut String, color: Color) {
+    let text = if color.is_light() { 0 } else { 255 };
+    out.push_str(&format!(
+        "\x1b[48;2;{};{};{}m\x1b[38;2;{t};{t};{t}m",
+        color.red,
+        color.green,
+        color.blue,
+        t = text
+    ));
+}
+
+/// Draws lighting in a terminal with 24-bit ANSI colors, so effects can be checked without a Logitech device,
+/// like on CI or over SSH.
+///
+/// The keyboard is drawn as the 21x6 grid of a `Bitmap`, with each key labelled, and devices without
+/// per-key lighting are drawn as a swatch for each of their zones.
+///
+/// # Example
+/// ```
+/// use lightsync::mock::MockBackend;
+/// use lightsync::{Color, Key, Sdk, TerminalPreview};
+///
+/// let sdk = Sdk::with_backend(MockBackend::new())?;
+/// sdk.set_lighting(Color::BLUE)?;
+/// sdk.set_lighting_for_key(Key::W, Color::RED)?;
+/// print!("{}", TerminalPreview::new().render(&sdk.snapshot()));
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalPreview {
+    labels: bool,
+    zones: bool,
+}
+
+impl TerminalPreview {
+    /// Creates a preview which draws key labels and zone swatches.
+    pub fn new() -> TerminalPreview {
+        TerminalPreview {
+            labels: true,
+            zones: true,
+        }
+    }
+
+    /// Sets whether keys are labelled. Without labels, each key is only two characters wide.
+    pub fn with_labels(mut self, labels: bool) -> TerminalPreview {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets whether swatches are drawn for the zones of mice, headsets and speakers.
+    pub fn with_zones(mut self, zones: bool) -> TerminalPreview {
+        self.zones = zones;
+        self
+    }
+
+    /// Draws the keyboard grid, with a cell's color being `None` if it's transparent.
+    fn render_grid(&self, out: &mut String, color: impl Fn(usize, usize) -> Option<Color>) {
+        let width = if self.labels { 5 } else { 2 };
+        for row in 0..Bitmap::HEIGHT {
+            for col in 0..Bitmap::WIDTH {
+                let key = match Key::at(row, col) {
+                    Some(key) => key,
+                    None => {
+                        out.push_str(&" ".repeat(width));
+                        continue;
+                    }
+                };
+                match color(row, col) {
+                    Some(color) => paint(out, color),
+                    // Transparent cells are drawn faintly on the terminal's background.
+                    None => out.push_str("\x1b[2m"),
+                }
+                let label = if self.labels { label(key) } else { "" };
+                out.push_str(&format!("{:^1$}", label, width));
+                out.push_str(RESET);
+            }
+            out.push('\n');
+        }
+    }
+
+    /// Draws the lighting of every kind of device in `snapshot`, as returned by `Sdk::snapshot()`.
+    pub fn render(&self, snapshot: &Snapshot) -> String {
+        let mut out = String::new();
+        self.render_grid(&mut out, |row, col| Some(snapshot.cell_color(row, col)));
+        if self.zones {
+            for (i, &(device_type, name, zones)) in ZONES.iter().enumerate() {
+                if i > 0 {
+                    out.push_str("  ");
+                }
+                out.push_str(name);
+                for zone in 0..zones {
+                    out.push(' ');
+                    paint(&mut out, snapshot.zone_color(device_type, zone));
+                    out.push_str(&format!(" {} ", zone));
+                    out.push_str(RESET);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Draws `bitmap` on the keyboard grid. Transparent cells are drawn faintly, without a color.
+    pub fn render_bitmap(&self, bitmap: &Bitmap) -> String {
+        let mut out = String::new();
+        self.render_grid(&mut out, |row, col| {
+            if bitmap.cell_alpha(row, col) == 0 {
+                None
+            } else {
+                Some(bitmap.cell(row, col))
+            }
+        });
+        out
+    }
+
+    /// Runs `engine` in real time for `duration`, or forever if it's `None`, redrawing its lighting in place on `out`.
+    ///
+    /// The engine is started if it's stopped. Anything which should be shown under the effect,
+    /// like the static colors of a `Scene`, can be set on `engine.sdk()` first.
+    ///
+    /// # Errors
+    /// Returns any error from writing to `out`, or an error of kind `io::ErrorKind::Other`
+    /// wrapping the `Error` if displaying a frame fails.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use lightsync::effects::RainbowWave;
+    /// use lightsync::mock::MockBackend;
+    /// use lightsync::{HeadlessEngine, Sdk, TerminalPreview};
+    /// use std::io;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let sdk = Sdk::with_backend(MockBackend::new())?;
+    /// let mut engine = HeadlessEngine::new(Arc::new(sdk), 30);
+    /// engine.set_effect(RainbowWave::new());
+    /// TerminalPreview::new().animate(&mut engine, Some(Duration::from_secs(5)), io::stdout())?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn animate<B: LightingBackend>(
+        &self,
+        engine: &mut HeadlessEngine<B>,
+        duration: Option<Duration>,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        let sdk_error = io::Error::other;
+        if engine.state() == EngineState::Stopped {
+            engine.start().map_err(sdk_error)?;
+        }
+
+        let start = Instant::now();
+        let mut last_frame = start;
+        let mut lines = 0;
+        // Hide the cursor while drawing, so it doesn't flicker over the keyboard.
+        write!(out, "\x1b[?25l")?;
+        let result = loop {
+            let now = Instant::now();
+            if let Err(err) = engine.advance(now - last_frame) {
+                break Err(sdk_error(err));
+            }
+            last_frame = now;
+
+            let frame = self.render(&engine.sdk().snapshot());
+            if let Err(err) = redraw(&mut out, lines, &frame) {
+                break Err(err);
+            }
+            lines = frame.lines().count();
+
+            match duration {
+                Some(duration) if now - start >= duration => break Ok(()),
+                _ => thread::sleep(REDRAW_INTERVAL),
+            }
+        };
+        // The cursor is shown again even if drawing failed, as long as the terminal can still be written to.
+        let restored = write!(out, "\x1b[?25h").and_then(|()| out.flush());
+        result.and(restored)
+    }
+}
+
+/// Draws `frame` over the last frame drawn to `out`, which took up `lines` lines.
+fn redraw(out: &mut impl Write, lines: usize, frame: &str) -> io::Result<()> {
+    if lines > 0 {
+        write!(out, "\x1b[{}A", lines)?;
+    }
+    out.write_all(frame.as_bytes())?;
+    out.flush()
+}
+
+impl Default for TerminalPreview {
+    fn default() -> TerminalPreview {
+        TerminalPreview::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+    use crate::Sdk;
+
+    #[test]
+    fn labels() {
+        for &key in &Key::ALL {
+            assert!(label(key).len() <= 5, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn render() {
+        let sdk = Sdk::with_backend(MockBackend::new()).unwrap();
+        sdk.set_lighting_for_key(Key::Esc, Color::WHITE).unwrap();
+        sdk.set_lighting_for_zone(DeviceType::Headset, 1, Color::RED)
+            .unwrap();
+        let preview = TerminalPreview::new().render(&sdk.snapshot());
+        let lines: Vec<_> = preview.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].starts_with("\x1b[48;2;255;255;255m\x1b[38;2;0;0;0m Esc \x1b[0m"));
+        assert!(lines[0].contains("\x1b[48;2;0;0;0m\x1b[38;2;255;255;255m F1  \x1b[0m"));
+        assert!(lines[6].starts_with("Mouse "));
+        assert!(lines[6].contains(
+            "Headset \x1b[48;2;0;0;0m\x1b[38;2;255;255;255m 0 \x1b[0m \x1b[48;2;255;0;0m"
+        ));
+
+        let preview = TerminalPreview::new()
+            .with_labels(false)
+            .with_zones(false)
+            .render(&sdk.snapshot());
+        assert_eq!(preview.lines().count(), 6);
+        assert!(preview.starts_with("\x1b[48;2;255;255;255m\x1b[38;2;0;0;0m  \x1b[0m"));
+    }
+
+    #[test]
+    fn render_bitmap() {
+        let mut bitmap = Bitmap::new();
+        bitmap.set(Key::Space, Color::BLUE);
+        let preview = TerminalPreview::new().render_bitmap(&bitmap);
+        let lines: Vec<_> = preview.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("\x1b[2m Esc \x1b[0m"));
+        // Cells without a key are blank.
+        assert!(lines[0].ends_with(&" ".repeat(25)));
+        assert!(lines[5].contains("\x1b[48;2;0;0;255m\x1b[38;2;255;255;255mSpace\x1b[0m"));
+    }
+
+    #[test]
+    fn animate() {
+        let sdk = Sdk::with_backend(MockBackend::new()).unwrap();
+        let mut engine = HeadlessEngine::new(std::sync::Arc::new(sdk), 30);
+        engine.set_effect(|_: Duration, frame: &mut Bitmap| frame.fill(Color::GREEN));
+        let mut out = Vec::new();
+        TerminalPreview::new()
+            .animate(&mut engine, Some(Duration::from_millis(50)), &mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[?25l"));
+        assert!(out.ends_with("\x1b[?25h"));
+        // Every frame after the first is drawn over the last one.
+        assert!(out.contains("\x1b[7A"));
+        assert!(out.contains("\x1b[48;2;0;255;0m\x1b[38;2;0;0;0m Esc "));
+    }
+
+    /// Fails to write anything longer than a control sequence, like a frame.
+    struct ShortWrites(Vec<u8>);
+
+    impl Write for ShortWrites {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() > 8 {
+                return Err(io::Error::other("too long"));
+            }
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn animate_errors() {
+        let sdk = Sdk::with_backend(MockBackend::new()).unwrap();
+        let mut engine = HeadlessEngine::new(std::sync::Arc::new(sdk), 30);
+        let mut out = ShortWrites(Vec::new());
+        let err = TerminalPreview::new()
+            .animate(&mut engine, None, &mut out)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "too long");
+        // The cursor is shown again after the error.
+        assert_eq!(out.0, b"\x1b[?25l\x1b[?25h");
+    }
+}
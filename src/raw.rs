@@ -3,10 +3,9 @@
 //! You probably shouldn't use these; they're only here because I plan to use them to create Deno bindings.
 
 use super::bindings::*;
-use super::Key;
-use std::ffi::OsString;
+use super::wide::{self, WideBuffer, WideString};
+use super::{Error, Key};
 use std::os::raw::c_int;
-use std::os::windows::ffi::OsStrExt;
 
 /// Makes sure there isn’t already another instance running and then makes
 /// necessary initializations. It saves the current lighting for all connected and supported devices.
@@ -31,26 +30,26 @@ pub fn init() -> bool {
 /// - name: The referred name for this integration to show up as.
 ///
 /// ## Return value
-/// If the function succeeds, it returns true. Otherwise false.
+/// If the function succeeds, it returns `Ok`.
 ///
-/// If it returns false, it means that the connection with Logitech Gaming Software is broken.
-pub fn init_with_name(name: &str) -> bool {
-    let c_str = std::ffi::CString::new(name)
-        .expect(format!("{} cannot be converted to a C string", name).as_str());
-    unsafe { LogiLedInitWithName(c_str.as_ptr()) }
+/// It returns `Error::NulInName` if `name` contains a null byte, and `Error::ConnectionLost` if the
+/// connection with Logitech Gaming Software is broken.
+pub fn init_with_name(name: &str) -> Result<(), Error> {
+    let c_str = std::ffi::CString::new(name).map_err(|_| Error::NulInName)?;
+    check(unsafe { LogiLedInitWithName(c_str.as_ptr()) }, ())
 }
 
 /// Retrieves the version of the SDK installed on the user’s system.
 ///
 /// If it returns None, means that there is no SDK installed on the user system, or the sdk version could not
 /// be retrieved.
-pub fn get_sdk_version() -> Option<super::Color> {
+pub fn get_sdk_version() -> Option<(i32, i32, i32)> {
     let mut major: c_int = 0;
     let mut minor: c_int = 0;
     let mut patch: c_int = 0;
 
     if unsafe { LogiLedGetSdkVersion(&mut major, &mut minor, &mut patch) } {
-        Some((major.into(), minor.into(), patch.into()))
+        Some((major, minor, patch))
     } else {
         None
     }
@@ -108,7 +107,8 @@ pub fn save_current_lighting() -> bool {
 /// define the intensity. For monochrome backlighting device, Logitech Gaming Software will reduce
 /// proportionally the value of the highest color, according to the user hardware brightness setting.
 pub fn set_lighting(color: super::Color) -> bool {
-    unsafe { LogiLedSetLighting(color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLighting(red, green, blue) }
 }
 
 /// Sets lighting on a specific zone for all connected
@@ -121,7 +121,7 @@ pub fn set_lighting(color: super::Color) -> bool {
 ///   - Headset = 0x8,
 ///   - Speaker = 0xe
 /// - zone: the zone ID to set iightng on. For device zone IDs, consult "Features of lighting capable
-/// Logitech Gaming devices")
+///   Logitech Gaming devices")
 /// - red: amount of red. Range is 0 to 100.
 /// - green: amount of green. Range is 0 to 100.
 /// - biuePercentage: amount of biue. Range is 0 to 100.
@@ -138,7 +138,8 @@ pub fn set_lighting_for_target_zone(
     zone: i32,
     color: super::Color
 ) -> bool {
-    unsafe { LogiLedSetLightingForTargetZone(device_type.into(), zone, color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLightingForTargetZone(device_type.into(), zone, red, green, blue) }
 }
 
 /// Restores the last saved lighting. It should be called after a
@@ -155,7 +156,8 @@ pub fn restore_lighting() -> bool {
 }
 
 pub fn flash_lighting(color: super::Color, duration: i32, interval: i32) -> bool {
-    unsafe { LogiLedFlashLighting(color.0, color.1, color.2, duration, interval) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedFlashLighting(red, green, blue, duration, interval) }
 }
 
 /// Saves the current lighting, plays the pulsing effect on the
@@ -165,8 +167,8 @@ pub fn flash_lighting(color: super::Color, duration: i32, interval: i32) -> bool
 /// - green: amount of green. Range is 0 to 100.
 /// - biue: amount of biue. Range is 0 to 100.
 /// - duration : duration of the efect in milliseconds, this parameter can be set to
-/// LOGI_LED_DURATION_INFINITE to make the effect run unti stopped through
-/// LogiLedStopEfects().
+///   LOGI_LED_DURATION_INFINITE to make the effect run unti stopped through
+///   LogiLedStopEfects().
 /// - milliSecondsIntervai : duration of the flashing interval in milliseconds
 /// ## Return value
 /// If the function succeeds, it returns true. Otherwise false.
@@ -174,7 +176,8 @@ pub fn flash_lighting(color: super::Color, duration: i32, interval: i32) -> bool
 /// The function will return false if LogiLedInit() hasn’t been called, if the connection with Logitech Gaming
 /// Software was lost or if another effect is currently running.
 pub fn pulse_lighting(color: super::Color, duration: i32, interval: i32) -> bool {
-    unsafe { LogiLedPulseLighting(color.0, color.1, color.2, duration, interval) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedPulseLighting(red, green, blue, duration, interval) }
 }
 
 /// Stops any of the presets effects (started from LogiLedFlashLighting or LogiLedPulseLighting).
@@ -192,13 +195,13 @@ pub fn stop_effects() -> bool {
 /// bool LogiLedSetLightingFromBitmap(unsigned char bitmap[]);
 /// ## Parameters
 /// - bitmap: a unsigned char array containing the colors to assign to each ket on the er-iightng
-/// device connected. The size required for this bitmap is defned bt LOGI_LED_BITMAP_SIZE.
-/// The array of pixels is organized as a rectangular area, 21x6, representing the keys on the device. Each
-/// color is represented by four consecutive bytes (RGBA).
-/// 32 bit values are stored in 4 consecutive bytes that represent the RGB color values for that pixel.
-/// These values use the same top left to bottom right raster style transform to the flat character array with
-/// the exception that each pixel value is specified using 4 consecutive bytes. The illustration below shows
-/// the data arrangement for these RGB quads.
+///   device connected. The size required for this bitmap is defned bt LOGI_LED_BITMAP_SIZE.
+///   The array of pixels is organized as a rectangular area, 21x6, representing the keys on the device. Each
+///   color is represented by four consecutive bytes (RGBA).
+///   32 bit values are stored in 4 consecutive bytes that represent the RGB color values for that pixel.
+///   These values use the same top left to bottom right raster style transform to the flat character array with
+///   the exception that each pixel value is specified using 4 consecutive bytes. The illustration below shows
+///   the data arrangement for these RGB quads.
 ///
 /// Each of the bytes in the RGB quad specify the intensity of the given color. The value ranges from 0 (the
 /// darkest color value) to 255 (brightest color value).
@@ -223,7 +226,7 @@ pub fn set_lighting_from_bitmap(bitmap: &mut [u8]) -> bool {
 /// LogiLedSetLightingFromKeyName.
 /// ## Parameters
 /// - keys: A preallocated array of LogiLed::KeyNames) to be excluded when calling
-/// LogiLedSetLightingFromKeyName.
+///   LogiLedSetLightingFromKeyName.
 /// - iistCount: the number of items in the iist K’etList
 pub fn exclude_keys_from_bitmap(keys: &[super::Key]) -> bool {
     unsafe {
@@ -252,7 +255,8 @@ pub fn exclude_keys_from_bitmap(keys: &[super::Key]) -> bool {
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
 /// Gaming Software was lost.
 pub fn set_lighting_for_key_with_scan_code(code: i32, color: super::Color) -> bool {
-    unsafe { LogiLedSetLightingForKeyWithScanCode(code, color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLightingForKeyWithScanCode(code, red, green, blue) }
 }
 
 /// Sets the key identified by the hid code
@@ -269,7 +273,8 @@ pub fn set_lighting_for_key_with_scan_code(code: i32, color: super::Color) -> bo
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
 /// Gaming Software was lost.
 pub fn set_lighting_for_key_with_hid_code(code: i32, color: super::Color) -> bool {
-    unsafe { LogiLedSetLightingForKeyWithHidCode(code, color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLightingForKeyWithHidCode(code, red, green, blue) }
 }
 
 /// Sets the key identified by the quartz code
@@ -286,7 +291,8 @@ pub fn set_lighting_for_key_with_hid_code(code: i32, color: super::Color) -> boo
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
 /// Gaming Software was lost.
 pub fn set_lighting_for_key_with_quartz_code(code: i32, color: super::Color) -> bool {
-    unsafe { LogiLedSetLightingForKeyWithQuartzCode(code, color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLightingForKeyWithQuartzCode(code, red, green, blue) }
 }
 
 /// Sets the key identified by the code passed
@@ -302,7 +308,8 @@ pub fn set_lighting_for_key_with_quartz_code(code: i32, color: super::Color) ->
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
 /// Gaming Software was lost.
 pub fn set_lighting_for_key_with_key_name(key: Key, color: super::Color) -> bool {
-    unsafe { LogiLedSetLightingForKeyWithKeyName(key.into(), color.0, color.1, color.2) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedSetLightingForKeyWithKeyName(key.into(), red, green, blue) }
 }
 
 /// Saves the current color on the keycode passed as
@@ -312,7 +319,7 @@ pub fn set_lighting_for_key_with_key_name(key: Key, color: super::Color) -> bool
 /// This function only applies to device of the family LOGI_DEVICETYPE_PERKEY_RGB.
 /// Parameters
 /// - keyName: The key to save the color for. A value from the LogiLed::KeyName enum.
-/// ##Return value
+/// ## Return value
 /// If the function succeeds, it returns true. Otherwise false.
 ///
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
@@ -347,14 +354,15 @@ pub fn restore_lighting_for_key(key: Key) -> bool {
 /// - green : amount of green in the actve coior of the fash efect. eange is 0 to 100.
 /// - blue : amount of biue in the actve coior of the fash efect. eange is 0 to 100.
 /// - msDuraton : duraton in miiiiseconds of the efect on the singie ket. This parameter can be set
-/// to LOGI_LED_DURATION_INFINITE to make the efect run unti stopped through
-/// LogiLedStopEfects() or LogiLedStopEfectsOnKey().
+///   to LOGI_LED_DURATION_INFINITE to make the efect run unti stopped through
+///   LogiLedStopEfects() or LogiLedStopEfectsOnKey().
 /// ## Return value
 /// If the function succeeds, it returns true. Otherwise false.
 /// The function will return false if LogiLedInit() hasn’t been called or if the connection with Logitech
 /// Gaming Software was lost.
 pub fn flash_single_key(key: Key, color: super::Color, duration: i32, interval: i32) -> bool {
-    unsafe { LogiLedFlashSingleKey(key.into(), color.0, color.1, color.2, duration, interval) }
+    let (red, green, blue) = color.to_percent();
+    unsafe { LogiLedFlashSingleKey(key.into(), red, green, blue, duration, interval) }
 }
 
 /// The LogiLedPulseSingleKey() function starts a pulsing effect on the key passed as parameter. The key
@@ -370,7 +378,7 @@ pub fn flash_single_key(key: Key, color: super::Color, duration: i32, interval:
 /// - end_blue: amount of blue in the finish color of the pulse effect. Range is 0 to 100.
 /// - duration: duration of the effect on the singie key.
 /// - infinite : if this is set to true the effect will loop infinitely until stopped with a call to
-/// LogiLedStopEfects() or LogiLedStopEfectsOnKey()
+///   LogiLedStopEfects() or LogiLedStopEfectsOnKey()
 /// ## Return value
 /// If the function succeeds, it returns true. Otherwise false.
 ///
@@ -383,15 +391,17 @@ pub fn pulse_single_key(
     duration: i32,
     infinite: bool,
 ) -> bool {
+    let (start_red, start_green, start_blue) = start.to_percent();
+    let (end_red, end_green, end_blue) = end.to_percent();
     unsafe {
         LogiLedPulseSingleKey(
             key.into(),
-            start.0,
-            start.1,
-            start.2,
-            end.0,
-            end.1,
-            end.2,
+            start_red,
+            start_green,
+            start_blue,
+            end_red,
+            end_green,
+            end_blue,
             duration,
             infinite,
         )
@@ -407,96 +417,164 @@ pub fn shutdown() {
     unsafe { LogiLedShutdown() }
 }
 
-pub fn set_config_option_label(path: &str, label: &str) {
-    let os_path = OsString::from(path);
-    let path_ptr = os_path.encode_wide().collect::<Vec<u16>>().as_ptr();
-    let os_label = OsString::from(label);
-    let label_ptr = os_label.encode_wide().collect::<Vec<u16>>().as_mut_ptr();
-    unsafe {
-        assert!(
-            LogiLedSetConfigOptionLabel(path_ptr, label_ptr),
-            "LogiLedSetConfigOptionLabel failed"
-        );
-    }
+/// Sets the label shown for the config option at `path`.
+///
+/// It returns `Error::NulInName` if `path` or `label` contains a null byte,
+/// and `Error::ConnectionLost` if the call failed.
+pub fn set_config_option_label(path: &str, label: &str) -> Result<(), Error> {
+    let path = WideString::new(path)?;
+    let mut label = WideString::new(label)?;
+    check(
+        unsafe { LogiLedSetConfigOptionLabel(path.as_ptr(), label.as_mut_ptr()) },
+        (),
+    )
 }
 
-pub fn get_config_option_number(path: &str, default: f64) -> f64 {
+/// Gets the number chosen for the config option at `path`, or `default`.
+///
+/// It returns `Error::NulInName` if `path` contains a null byte, and `Error::ConnectionLost` if the call failed.
+pub fn get_config_option_number(path: &str, default: f64) -> Result<f64, Error> {
     let mut value = default;
-    let os_string = OsString::from(path);
-    let path_ptr = os_string.encode_wide().collect::<Vec<u16>>().as_ptr();
-    unsafe {
-        assert!(
-            LogiLedGetConfigOptionNumber(path_ptr, &mut value),
-            "LogiLedGetConfigOptionNumber failed"
-        );
-    }
-    value
+    let path = WideString::new(path)?;
+    check(
+        unsafe { LogiLedGetConfigOptionNumber(path.as_ptr(), &mut value) },
+        value,
+    )
 }
 
-pub fn get_config_option_bool(path: &str, default: bool) -> bool {
+/// Gets the boolean chosen for the config option at `path`, or `default`.
+///
+/// It returns `Error::NulInName` if `path` contains a null byte, and `Error::ConnectionLost` if the call failed.
+pub fn get_config_option_bool(path: &str, default: bool) -> Result<bool, Error> {
     let mut value = default;
-    let os_string = OsString::from(path);
-    let path_ptr = os_string.encode_wide().collect::<Vec<u16>>().as_ptr();
-    unsafe {
-        assert!(
-            LogiLedGetConfigOptionBool(path_ptr, &mut value),
-            "LogiLedGetConfigOptionBool failed"
-        );
-    }
-    value
+    let path = WideString::new(path)?;
+    check(
+        unsafe { LogiLedGetConfigOptionBool(path.as_ptr(), &mut value) },
+        value,
+    )
 }
 
-pub fn get_config_option_color(
-    path: &str,
-    default: super::Color
-) -> super::Color {
-    let mut red = default.0;
-    let mut green = default.1;
-    let mut blue = default.2;
-    let os_string = OsString::from(path);
-    let path_ptr = os_string.encode_wide().collect::<Vec<u16>>().as_ptr();
-    unsafe {
-        assert!(
-            LogiLedGetConfigOptionColor(path_ptr, &mut red, &mut green, &mut blue),
-            "LogiLedGetConfigOptionColor failed"
-        );
-    }
-    (red, green, blue)
+/// Gets the color chosen for the config option at `path`, or `default`.
+///
+/// Unlike the rest of the SDK, config colors are represented with bytes from 0 to 255.
+///
+/// It returns `Error::NulInName` if `path` contains a null byte, and `Error::ConnectionLost` if the call failed.
+pub fn get_config_option_color(path: &str, default: super::Color) -> Result<super::Color, Error> {
+    let mut red = default.red.into();
+    let mut green = default.green.into();
+    let mut blue = default.blue.into();
+    let path = WideString::new(path)?;
+    check(
+        unsafe { LogiLedGetConfigOptionColor(path.as_ptr(), &mut red, &mut green, &mut blue) },
+        super::Color::from_rgb8(byte(red), byte(green), byte(blue)),
+    )
 }
 
-pub fn get_config_option_range(path: &str, default: i32, min: i32, max: i32) -> i32 {
+/// Gets the number between `min` and `max` chosen for the config option at `path`, or `default`.
+///
+/// It returns `Error::NulInName` if `path` contains a null byte, and `Error::ConnectionLost` if the call failed.
+pub fn get_config_option_range(path: &str, default: i32, min: i32, max: i32) -> Result<i32, Error> {
     let mut value = default;
-    let os_string = OsString::from(path);
-    let path_ptr = os_string.encode_wide().collect::<Vec<u16>>().as_ptr();
-    unsafe {
-        assert!(
-            LogiLedGetConfigOptionRange(path_ptr, &mut value, min, max),
-            "LogiLedGetConfigOptionRange failed"
-        );
-    }
-    value
+    let path = WideString::new(path)?;
+    check(
+        unsafe { LogiLedGetConfigOptionRange(path.as_ptr(), &mut value, min, max) },
+        value,
+    )
 }
 
+/// Gets the rectangle chosen for the config option at `path`, or `default`.
+///
+/// It returns `Error::NulInName` if `path` contains a null byte, and `Error::ConnectionLost` if the call failed.
 pub fn get_config_option_rect(
     path: &str,
     default_x: i32,
     default_y: i32,
     default_width: i32,
     default_height: i32,
-) -> (i32, i32, i32, i32) {
+) -> Result<(i32, i32, i32, i32), Error> {
     let mut x = default_x;
     let mut y = default_y;
     let mut width = default_width;
     let mut height = default_height;
-    let os_string = OsString::from(path);
-    let path_ptr = os_string.encode_wide().collect::<Vec<u16>>().as_ptr();
-    unsafe {
-        assert!(
-            LogiLedGetConfigOptionRect(path_ptr, &mut x, &mut y, &mut width, &mut height),
-            "LogiLedGetConfigOptionRect failed"
-        );
-    }
-    (x, y, width, height)
+    let path = WideString::new(path)?;
+    check(
+        unsafe {
+            LogiLedGetConfigOptionRect(path.as_ptr(), &mut x, &mut y, &mut width, &mut height)
+        },
+        (x, y, width, height),
+    )
+}
+
+/// Gets the string chosen for the config option at `path`, or `default`.
+///
+/// It returns `Error::NulInName` if `path` or `default` contains a null byte, and `Error::ConnectionLost` if the
+/// call failed.
+pub fn get_config_option_string(path: &str, default: &str) -> Result<String, Error> {
+    let path = WideString::new(path)?;
+    let mut value = WideBuffer::new(default)?;
+    check(
+        unsafe { LogiLedGetConfigOptionString(path.as_ptr(), value.as_mut_ptr(), value.len()) },
+        (),
+    )?;
+    Ok(value.to_string_lossy())
+}
+
+/// Gets the key chosen for the config option at `path`, or `default`, as the name shown for it by the SDK.
+///
+/// It returns `Error::NulInName` if `path` or `default` contains a null byte, and `Error::ConnectionLost` if the
+/// call failed.
+pub fn get_config_option_key_input(path: &str, default: &str) -> Result<String, Error> {
+    let path = WideString::new(path)?;
+    let mut value = WideBuffer::new(default)?;
+    check(
+        unsafe { LogiLedGetConfigOptionKeyInput(path.as_ptr(), value.as_mut_ptr(), value.len()) },
+        (),
+    )?;
+    Ok(value.to_string_lossy())
 }
 
-// Missing option types: KeyInput and Select, I'm not too sure how they're represented. (and they're undocumented)
+/// Gets which of `values` was chosen for the config option at `path`, or `default`.
+///
+/// The SDK doesn't document this function, so this goes by its signature: the chosen value is written over
+/// `default` in a buffer of `bufferSize` characters along with its length, and the values to choose from are passed
+/// as one buffer of null-terminated strings, ending with an empty string.
+///
+/// It returns `Error::NulInName` if `path`, `default` or any of `values` contains a null byte, and
+/// `Error::ConnectionLost` if the call failed.
+pub fn get_config_option_select(
+    path: &str,
+    default: &str,
+    values: &[&str],
+) -> Result<String, Error> {
+    let path = WideString::new(path)?;
+    let mut value = WideBuffer::new(default)?;
+    let mut value_size = default.encode_utf16().count() as c_int;
+    let values = wide::encode_list(values)?;
+    check(
+        unsafe {
+            LogiLedGetConfigOptionSelect(
+                path.as_ptr(),
+                value.as_mut_ptr(),
+                &mut value_size,
+                values.as_ptr(),
+                value.len(),
+            )
+        },
+        (),
+    )?;
+    Ok(value.to_string_lossy())
+}
+
+/// Clamps a color channel returned by the SDK to a byte.
+fn byte(channel: c_int) -> u8 {
+    channel.clamp(0, 255) as u8
+}
+
+/// Turns the result of an SDK call into `Ok(value)`, or `Error::ConnectionLost` if it failed.
+fn check<T>(succeeded: bool, value: T) -> Result<T, Error> {
+    if succeeded {
+        Ok(value)
+    } else {
+        Err(Error::ConnectionLost)
+    }
+}
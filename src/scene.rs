@@ -0,0 +1,955 @@
+use super::effects::{
+    Breathing, ColorCycle, Direction, FadeTrail, Fire, GameOfLife, GradientSweep, Heatmap,
+    MatrixRain, Palette, RainbowWave, Ripple, SpectrumCycle, Starlight, TypingRipple,
+};
+use super::{
+    Bitmap, BlendMode, Color, DeviceType, Easing, Effect, Error, Key, Layer, LayerStack,
+    LightingBackend, Sdk, Timeline, Track,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The ways in which loading a scene file can fail.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't valid TOML or JSON, or doesn't match the scene format.
+    Parse(String),
+    /// A key name isn't the name of a `Key`.
+    UnknownKey(String),
+    /// A color isn't a valid hex color.
+    InvalidColor(String),
+    /// A number or list is outside the range it's allowed to be in.
+    InvalidValue(String),
+    /// The file's extension isn't `.toml` or `.json`, so its format isn't known.
+    UnknownFormat,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "couldn't read scene: {}", err),
+            SceneError::Parse(message) => write!(f, "invalid scene: {}", message),
+            SceneError::UnknownKey(name) => write!(f, "unknown key name `{}`", name),
+            SceneError::InvalidColor(color) => write!(f, "invalid color `{}`", color),
+            SceneError::InvalidValue(message) => write!(f, "invalid value: {}", message),
+            SceneError::UnknownFormat => {
+                f.write_str("scene files must have a `.toml` or `.json` extension")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> SceneError {
+        SceneError::Io(err)
+    }
+}
+
+/// The scene format, as it's written in files.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SceneFile {
+    color: Option<String>,
+    keys: BTreeMap<String, String>,
+    regions: Vec<RegionFile>,
+    zones: Vec<ZoneFile>,
+    layers: Vec<LayerFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegionFile {
+    keys: Vec<String>,
+    color: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZoneFile {
+    device: DeviceFile,
+    zone: i32,
+    color: String,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DeviceFile {
+    Keyboard,
+    Mouse,
+    Mousemat,
+    Headset,
+    Speaker,
+}
+
+impl From<DeviceFile> for DeviceType {
+    fn from(device: DeviceFile) -> DeviceType {
+        match device {
+            DeviceFile::Keyboard => DeviceType::Keyboard,
+            DeviceFile::Mouse => DeviceType::Mouse,
+            DeviceFile::Mousemat => DeviceType::Mousemat,
+            DeviceFile::Headset => DeviceType::Headset,
+            DeviceFile::Speaker => DeviceType::Speaker,
+        }
+    }
+}
+
+/// The effects which can be used in layers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EffectKind {
+    Solid,
+    RainbowWave,
+    SpectrumCycle,
+    Breathing,
+    Starlight,
+    Ripple,
+    ColorCycle,
+    Fire,
+    MatrixRain,
+    GradientSweep,
+    GameOfLife,
+    TypingRipple,
+    FadeTrail,
+    Heatmap,
+    Timeline,
+}
+
+/// A layer, with the settings for every kind of effect. Settings which don't apply to the layer's effect are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayerFile {
+    effect: EffectKind,
+    #[serde(default)]
+    priority: i32,
+    opacity: Option<f64>,
+    #[serde(default)]
+    blend: BlendFile,
+    mask: Option<Vec<String>>,
+
+    speed: Option<f64>,
+    direction: Option<DirectionFile>,
+    palette: Option<Vec<String>>,
+    seed: Option<u64>,
+    color: Option<String>,
+    density: Option<f64>,
+    fade: Option<f64>,
+    interval: Option<f64>,
+    origin: Option<String>,
+    width: Option<f64>,
+    wavelength: Option<f64>,
+    cooling: Option<f64>,
+    half_life: Option<f64>,
+
+    #[serde(default)]
+    looping: bool,
+    duration: Option<f64>,
+    #[serde(default)]
+    tracks: Vec<TrackFile>,
+}
+
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BlendFile {
+    #[default]
+    Normal,
+    Add,
+    Multiply,
+    Screen,
+    Max,
+}
+
+impl From<BlendFile> for BlendMode {
+    fn from(blend: BlendFile) -> BlendMode {
+        match blend {
+            BlendFile::Normal => BlendMode::Normal,
+            BlendFile::Add => BlendMode::Add,
+            BlendFile::Multiply => BlendMode::Multiply,
+            BlendFile::Screen => BlendMode::Screen,
+            BlendFile::Max => BlendMode::Max,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DirectionFile {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl From<DirectionFile> for Direction {
+    fn from(direction: DirectionFile) -> Direction {
+        match direction {
+            DirectionFile::Left => Direction::Left,
+            DirectionFile::Right => Direction::Right,
+            DirectionFile::Up => Direction::Up,
+            DirectionFile::Down => Direction::Down,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrackFile {
+    /// The keys the track sets, or every key in the bitmap grid if it's left out.
+    keys: Option<Vec<String>>,
+    keyframes: Vec<KeyframeFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeFile {
+    time: f64,
+    color: String,
+    #[serde(default)]
+    easing: EasingFile,
+}
+
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EasingFile {
+    #[default]
+    Linear,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    Bounce,
+    Step,
+    Bezier([f64; 4]),
+}
+
+impl From<EasingFile> for Easing {
+    fn from(easing: EasingFile) -> Easing {
+        match easing {
+            EasingFile::Linear => Easing::Linear,
+            EasingFile::CubicIn => Easing::CubicIn,
+            EasingFile::CubicOut => Easing::CubicOut,
+            EasingFile::CubicInOut => Easing::CubicInOut,
+            EasingFile::SineIn => Easing::SineIn,
+            EasingFile::SineOut => Easing::SineOut,
+            EasingFile::SineInOut => Easing::SineInOut,
+            EasingFile::Bounce => Easing::Bounce,
+            EasingFile::Step => Easing::Step,
+            EasingFile::Bezier([x1, y1, x2, y2]) => Easing::Bezier(x1, y1, x2, y2),
+        }
+    }
+}
+
+fn key(name: &str) -> Result<Key, SceneError> {
+    name.parse()
+        .map_err(|_| SceneError::UnknownKey(name.to_owned()))
+}
+
+fn keys(names: &[String]) -> Result<Vec<Key>, SceneError> {
+    names.iter().map(|name| key(name)).collect()
+}
+
+fn color(hex: &str) -> Result<Color, SceneError> {
+    Color::from_hex(hex).map_err(|_| SceneError::InvalidColor(hex.to_owned()))
+}
+
+fn palette(colors: &[String]) -> Result<Palette, SceneError> {
+    if colors.is_empty() {
+        return Err(SceneError::InvalidValue(
+            "palettes must have at least one color".to_owned(),
+        ));
+    }
+    Ok(Palette::new(
+        colors
+            .iter()
+            .map(|hex| color(hex))
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+fn duration(name: &str, seconds: f64) -> Result<Duration, SceneError> {
+    if seconds.is_nan() || seconds < 0.0 {
+        return Err(SceneError::InvalidValue(format!(
+            "`{}` must be a number of seconds which isn't negative",
+            name
+        )));
+    }
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| SceneError::InvalidValue(format!("`{}` is too long", name)))
+}
+
+/// Checks that `value`, if it's set, is a number for which `valid` returns `true`.
+fn number(
+    name: &str,
+    value: Option<f64>,
+    valid: impl Fn(f64) -> bool,
+    requirement: &str,
+) -> Result<Option<f64>, SceneError> {
+    match value {
+        Some(value) if !value.is_finite() || !valid(value) => Err(SceneError::InvalidValue(
+            format!("`{}` must be {}", name, requirement),
+        )),
+        _ => Ok(value),
+    }
+}
+
+/// Applies the builder method `$method` to `$effect` with each setting which is set.
+macro_rules! configure {
+    ($effect:expr, $($setting:expr => $method:ident),* $(,)?) => {{
+        let effect = $effect;
+        $(
+            let effect = match $setting {
+                Some(value) => effect.$method(value),
+                None => effect,
+            };
+        )*
+        Box::new(effect) as Box<dyn Effect>
+    }};
+}
+
+/// A layer from a scene file, with its settings checked.
+#[derive(Debug, Clone, PartialEq)]
+struct SceneLayer {
+    effect: EffectKind,
+    priority: i32,
+    opacity: Option<f64>,
+    blend_mode: BlendMode,
+    mask: Option<Vec<Key>>,
+
+    speed: Option<f64>,
+    direction: Option<Direction>,
+    palette: Option<Palette>,
+    seed: Option<u64>,
+    color: Option<Color>,
+    density: Option<f64>,
+    fade: Option<Duration>,
+    interval: Option<Duration>,
+    origin: Option<Key>,
+    width: Option<f64>,
+    wavelength: Option<f64>,
+    cooling: Option<f64>,
+    half_life: Option<Duration>,
+    timeline: Timeline,
+}
+
+impl SceneLayer {
+    fn parse(layer: LayerFile) -> Result<SceneLayer, SceneError> {
+        let optional_duration =
+            |name, seconds: Option<f64>| seconds.map(|s| duration(name, s)).transpose();
+
+        let mut timeline = Timeline::new().looping(layer.looping);
+        if let Some(seconds) = layer.duration {
+            timeline = timeline.with_duration(duration("duration", seconds)?);
+        }
+        for track in layer.tracks {
+            let mut parsed = match &track.keys {
+                Some(names) => Track::new(keys(names)?),
+                None => Track::all(),
+            };
+            for keyframe in track.keyframes {
+                parsed = parsed.with_keyframe(
+                    duration("time", keyframe.time)?,
+                    color(&keyframe.color)?,
+                    keyframe.easing.into(),
+                );
+            }
+            timeline = timeline.with_track(parsed);
+        }
+
+        let half_life = optional_duration("half_life", layer.half_life)?;
+        if half_life == Some(Duration::ZERO) {
+            return Err(SceneError::InvalidValue(
+                "`half_life` must be more than 0 seconds".to_owned(),
+            ));
+        }
+
+        Ok(SceneLayer {
+            effect: layer.effect,
+            priority: layer.priority,
+            opacity: number(
+                "opacity",
+                layer.opacity,
+                |opacity| (0.0..=1.0).contains(&opacity),
+                "between 0 and 1",
+            )?,
+            blend_mode: layer.blend.into(),
+            mask: layer.mask.as_deref().map(keys).transpose()?,
+            speed: number("speed", layer.speed, |speed| speed >= 0.0, "at least 0")?,
+            direction: layer.direction.map(Direction::from),
+            palette: layer.palette.as_deref().map(palette).transpose()?,
+            seed: layer.seed,
+            color: layer.color.as_deref().map(color).transpose()?,
+            density: number(
+                "density",
+                layer.density,
+                |density| density >= 0.0,
+                "at least 0",
+            )?,
+            fade: optional_duration("fade", layer.fade)?,
+            interval: optional_duration("interval", layer.interval)?,
+            origin: layer.origin.as_deref().map(key).transpose()?,
+            width: layer.width,
+            wavelength: number(
+                "wavelength",
+                layer.wavelength,
+                |wavelength| wavelength > 0.0,
+                "more than 0",
+            )?,
+            cooling: layer.cooling,
+            half_life,
+            timeline,
+        })
+    }
+
+    /// Creates the layer's effect, starting from its first frame.
+    fn effect(&self) -> Box<dyn Effect> {
+        let palette = self.palette.clone();
+        match self.effect {
+            EffectKind::Solid => {
+                let color = self.color.unwrap_or(Color::WHITE);
+                Box::new(move |_: Duration, frame: &mut Bitmap| frame.fill(color))
+            }
+            EffectKind::RainbowWave => configure!(
+                RainbowWave::new(),
+                self.speed => with_speed,
+                self.direction => with_direction,
+                palette => with_palette,
+                self.wavelength => with_wavelength,
+            ),
+            EffectKind::SpectrumCycle => configure!(
+                SpectrumCycle::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+            ),
+            EffectKind::Breathing => configure!(
+                Breathing::new(self.color.unwrap_or(Color::WHITE)),
+                self.speed => with_speed,
+                palette => with_palette,
+            ),
+            EffectKind::Starlight => configure!(
+                Starlight::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+                self.density => with_density,
+                self.fade => with_fade,
+            ),
+            EffectKind::Ripple => configure!(
+                Ripple::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+                self.origin => with_origin,
+                self.interval => with_interval,
+            ),
+            EffectKind::ColorCycle => configure!(
+                ColorCycle::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.fade.map(|fade| fade.as_secs_f64()) => with_fade,
+            ),
+            EffectKind::Fire => configure!(
+                Fire::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+                self.cooling => with_cooling,
+            ),
+            EffectKind::MatrixRain => configure!(
+                MatrixRain::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+                self.density => with_density,
+            ),
+            EffectKind::GradientSweep => configure!(
+                GradientSweep::new(),
+                self.speed => with_speed,
+                self.direction => with_direction,
+                palette => with_palette,
+                self.width => with_width,
+            ),
+            EffectKind::GameOfLife => configure!(
+                GameOfLife::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+                self.density => with_density,
+            ),
+            EffectKind::TypingRipple => configure!(
+                TypingRipple::new(),
+                self.speed => with_speed,
+                palette => with_palette,
+                self.seed => with_seed,
+            ),
+            EffectKind::FadeTrail => configure!(
+                FadeTrail::new(),
+                palette => with_palette,
+                self.fade => with_fade,
+            ),
+            EffectKind::Heatmap => configure!(
+                Heatmap::new(),
+                palette => with_palette,
+                self.half_life => with_half_life,
+            ),
+            EffectKind::Timeline => Box::new(self.timeline.clone()),
+        }
+    }
+
+    fn layer(&self) -> Layer {
+        let mut layer = Layer::new(self.effect())
+            .with_priority(self.priority)
+            .with_blend_mode(self.blend_mode);
+        if let Some(opacity) = self.opacity {
+            layer.set_opacity(opacity);
+        }
+        if let Some(mask) = &self.mask {
+            layer.set_mask(mask.iter().copied());
+        }
+        layer
+    }
+}
+
+/// Lighting described in a TOML or JSON file, so it can be designed without writing Rust.
+///
+/// A scene has static colors, which are set with `apply()`, and layers of effects, which are run by
+/// passing `effect()` to an `Engine`. Every part of a scene is optional.
+///
+/// Key names are the names of `Key` variants, ignoring case, and colors are hex colors. Times are in seconds.
+///
+/// ```toml
+/// # Sets the lighting of every device.
+/// color = "#000020"
+///
+/// # Sets single keys.
+/// [keys]
+/// Esc = "#ff0000"
+/// Space = "#ffffff"
+///
+/// # Sets groups of keys.
+/// [[regions]]
+/// keys = ["W", "A", "S", "D"]
+/// color = "#ff8800"
+///
+/// # Sets a zone of a device; `device` is `keyboard`, `mouse`, `mousemat`, `headset` or `speaker`.
+/// [[zones]]
+/// device = "mouse"
+/// zone = 1
+/// color = "#00ff00"
+///
+/// # Effects are composited like in a `LayerStack`.
+/// # `effect` is `solid`, `rainbow_wave`, `spectrum_cycle`, `breathing`, `starlight`, `ripple`, `color_cycle`,
+/// # `fire`, `matrix_rain`, `gradient_sweep`, `game_of_life`, `typing_ripple`, `fade_trail`, `heatmap` or `timeline`,
+/// # and the rest of the settings are passed to the effect's builder methods.
+/// [[layers]]
+/// effect = "rainbow_wave"
+/// speed = 0.5
+/// direction = "left"
+/// mask = ["F1", "F2", "F3", "F4"]
+///
+/// [[layers]]
+/// effect = "starlight"
+/// priority = 1
+/// opacity = 0.8
+/// blend = "screen"
+/// palette = ["#ffffff", "#88ccff"]
+/// seed = 42
+///
+/// [[layers]]
+/// effect = "timeline"
+/// priority = 2
+/// looping = true
+///
+/// [[layers.tracks]]
+/// keys = ["Space"]
+/// keyframes = [
+///     { time = 0, color = "#000000" },
+///     { time = 1, color = "#ffffff", easing = "sine_in_out" },
+///     { time = 2, color = "#000000", easing = { bezier = [0.4, 0, 0.2, 1] } },
+/// ]
+/// ```
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Engine, Scene, Sdk};
+/// use std::sync::Arc;
+///
+/// let sdk = Arc::new(Sdk::init()?);
+/// let scene = Scene::load("scene.toml")?;
+/// scene.apply(&sdk)?;
+///
+/// let mut engine = Engine::new(Arc::clone(&sdk), 30);
+/// if let Some(effect) = scene.effect() {
+///     engine.set_effect(effect);
+///     engine.start();
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    color: Option<Color>,
+    keys: Vec<(Key, Color)>,
+    zones: Vec<(DeviceType, i32, Color)>,
+    layers: Vec<SceneLayer>,
+}
+
+impl Scene {
+    /// Loads a scene from a file, whose format is decided by its extension.
+    ///
+    /// # Errors
+    /// Returns `SceneError::UnknownFormat` if the extension isn't `.toml` or `.json`, `SceneError::Io` if the file
+    /// can't be read, and the same errors as `from_toml()` and `from_json()` if it isn't a valid scene.
+    pub fn load(path: impl AsRef<Path>) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => Scene::from_toml,
+            Some("json") => Scene::from_json,
+            _ => return Err(SceneError::UnknownFormat),
+        };
+        parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a scene from TOML.
+    ///
+    /// # Errors
+    /// Returns `SceneError::Parse` if `toml` isn't valid TOML or doesn't match the scene format,
+    /// `SceneError::UnknownKey` if a key name isn't the name of a `Key`, `SceneError::InvalidColor`
+    /// if a color isn't a valid hex color, and `SceneError::InvalidValue` if a time is negative or too long,
+    /// a palette is empty, or a layer setting like `opacity` is out of range.
+    pub fn from_toml(toml: &str) -> Result<Scene, SceneError> {
+        let file = toml::from_str(toml).map_err(|err| SceneError::Parse(err.to_string()))?;
+        Scene::parse(file)
+    }
+
+    /// Parses a scene from JSON, with the same structure as the TOML format.
+    ///
+    /// # Errors
+    /// Returns the same errors as `from_toml()`.
+    pub fn from_json(json: &str) -> Result<Scene, SceneError> {
+        let file = serde_json::from_str(json).map_err(|err| SceneError::Parse(err.to_string()))?;
+        Scene::parse(file)
+    }
+
+    fn parse(file: SceneFile) -> Result<Scene, SceneError> {
+        let mut scene = Scene {
+            color: file.color.as_deref().map(color).transpose()?,
+            ..Scene::default()
+        };
+        for (name, hex) in &file.keys {
+            scene.keys.push((key(name)?, color(hex)?));
+        }
+        for region in &file.regions {
+            let color = color(&region.color)?;
+            scene
+                .keys
+                .extend(keys(&region.keys)?.into_iter().map(|key| (key, color)));
+        }
+        for zone in &file.zones {
+            scene
+                .zones
+                .push((zone.device.into(), zone.zone, color(&zone.color)?));
+        }
+        for layer in file.layers {
+            scene.layers.push(SceneLayer::parse(layer)?);
+        }
+        Ok(scene)
+    }
+
+    /// Returns the color the scene sets every device to, if any.
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Returns the keys the scene sets and their colors, with single keys before regions.
+    pub fn keys(&self) -> &[(Key, Color)] {
+        &self.keys
+    }
+
+    /// Returns the zones the scene sets and their colors.
+    pub fn zones(&self) -> &[(DeviceType, i32, Color)] {
+        &self.zones
+    }
+
+    /// Sets the scene's static colors: first every device, then zones, and then keys.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn apply<B: LightingBackend>(&self, sdk: &Sdk<B>) -> Result<(), Error> {
+        if let Some(color) = self.color {
+            sdk.set_lighting(color)?;
+        }
+        for &(device_type, zone, color) in &self.zones {
+            sdk.set_lighting_for_zone(device_type, zone, color)?;
+        }
+        for &(key, color) in &self.keys {
+            sdk.set_lighting_for_key(key, color)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a stack of the scene's layers, starting from their first frames,
+    /// or returns `None` if it doesn't have any.
+    pub fn effect(&self) -> Option<LayerStack> {
+        if self.layers.is_empty() {
+            return None;
+        }
+        let mut stack = LayerStack::new();
+        for layer in &self.layers {
+            stack.push(layer.layer());
+        }
+        Some(stack)
+    }
+}
+
+/// Reloads a scene file whenever it's changed on disk, by checking its modification time.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Engine, SceneWatcher, Sdk};
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let sdk = Arc::new(Sdk::init()?);
+/// let mut engine = Engine::new(Arc::clone(&sdk), 30);
+/// let mut watcher = SceneWatcher::new("scene.toml");
+/// loop {
+///     match watcher.poll() {
+///         Some(Ok(scene)) => {
+///             engine.stop();
+///             scene.apply(&sdk)?;
+///             if let Some(effect) = scene.effect() {
+///                 engine.set_effect(effect);
+///                 engine.start();
+///             }
+///         }
+///         Some(Err(err)) => eprintln!("{}", err),
+///         None => {}
+///     }
+///     thread::sleep(Duration::from_millis(500));
+/// }
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SceneWatcher {
+    path: PathBuf,
+    /// The modification time of the file when it was last loaded, or `None` if it couldn't be read.
+    modified: Option<SystemTime>,
+    polled: bool,
+}
+
+impl SceneWatcher {
+    /// Creates a watcher for the scene file at `path`. The file is first loaded by `poll()`.
+    pub fn new(path: impl Into<PathBuf>) -> SceneWatcher {
+        SceneWatcher {
+            path: path.into(),
+            modified: None,
+            polled: false,
+        }
+    }
+
+    /// Returns the path of the scene file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the scene if it's the first time this has been called, or the file has changed since it was last loaded.
+    ///
+    /// Returns `None` if the file hasn't changed. If the file can't be read, an error is only returned
+    /// the first time, until it's readable again.
+    pub fn poll(&mut self) -> Option<Result<Scene, SceneError>> {
+        let first = !self.polled;
+        self.polled = true;
+        match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) if self.modified == Some(modified) => None,
+            Ok(modified) => {
+                self.modified = Some(modified);
+                Some(Scene::load(&self.path))
+            }
+            Err(err) => {
+                if self.modified.take().is_some() || first {
+                    Some(Err(err.into()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+    use crate::test_util;
+
+    const SCENE: &str = r##"
+        color = "#000020"
+
+        [keys]
+        esc = "#ff0000"
+        G1 = "#ffffff"
+
+        [[regions]]
+        keys = ["W", "A", "S", "D"]
+        color = "#ff8800"
+
+        [[zones]]
+        device = "mouse"
+        zone = 1
+        color = "#00ff00"
+
+        [[layers]]
+        effect = "solid"
+        color = "#0000ff"
+        mask = ["Space"]
+
+        [[layers]]
+        effect = "timeline"
+        priority = 1
+        blend = "add"
+
+        [[layers.tracks]]
+        keys = ["Space"]
+        keyframes = [
+            { time = 0, color = "#000000" },
+            { time = 1, color = "#ff0000", easing = { bezier = [0.4, 0, 0.2, 1] } },
+        ]
+    "##;
+
+    #[test]
+    fn parse_toml() {
+        let scene = Scene::from_toml(SCENE).unwrap();
+        assert_eq!(scene.color(), Some(Color::from_rgb8(0, 0, 0x20)));
+        assert_eq!(scene.keys().len(), 6);
+        assert!(scene.keys().contains(&(Key::Esc, Color::RED)));
+        assert_eq!(
+            scene.zones(),
+            [(DeviceType::Mouse, 1, Color::from_rgb8(0, 255, 0))]
+        );
+
+        let mut effect = scene.effect().unwrap();
+        assert_eq!(effect.len(), 2);
+        let mut frame = Bitmap::new();
+        effect.render(Duration::from_secs(0), &mut frame);
+        assert_eq!(frame.get(Key::Space), Some(Color::BLUE));
+        effect.render(Duration::from_secs(1), &mut frame);
+        assert_eq!(frame.get(Key::Space), Some(Color::from_rgb8(255, 0, 255)));
+        assert_eq!(frame.alpha(Key::A), Some(0));
+    }
+
+    #[test]
+    fn parse_json() {
+        let json = r##"{
+            "keys": { "W": "#ff0000" },
+            "layers": [{ "effect": "rainbow_wave", "direction": "up", "speed": 2 }]
+        }"##;
+        let scene = Scene::from_json(json).unwrap();
+        assert_eq!(scene.keys(), [(Key::W, Color::RED)]);
+        assert!(scene.effect().is_some());
+        assert_eq!(Scene::from_json("{}").unwrap(), Scene::default());
+        assert!(Scene::from_json("{}").unwrap().effect().is_none());
+    }
+
+    #[test]
+    fn validation() {
+        let error = |toml| Scene::from_toml(toml).unwrap_err().to_string();
+        assert_eq!(error("[keys]\nWq = \"#ff0000\""), "unknown key name `Wq`");
+        assert_eq!(error(r#"color = "red""#), "invalid color `red`");
+        assert_eq!(
+            error("[[layers]]\neffect = \"fire\"\nmask = [\"Nope\"]"),
+            "unknown key name `Nope`"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"starlight\"\npalette = []"),
+            "invalid value: palettes must have at least one color"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"fade_trail\"\nfade = -1"),
+            "invalid value: `fade` must be a number of seconds which isn't negative"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"fade_trail\"\nfade = 1e30"),
+            "invalid value: `fade` is too long"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"solid\"\nopacity = 1.5"),
+            "invalid value: `opacity` must be between 0 and 1"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"starlight\"\ndensity = nan"),
+            "invalid value: `density` must be at least 0"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"rainbow_wave\"\nwavelength = 0"),
+            "invalid value: `wavelength` must be more than 0"
+        );
+        assert_eq!(
+            error("[[layers]]\neffect = \"heatmap\"\nhalf_life = 0"),
+            "invalid value: `half_life` must be more than 0 seconds"
+        );
+        assert!(matches!(
+            Scene::from_toml("[[layers]]\neffect = \"sparkles\""),
+            Err(SceneError::Parse(_))
+        ));
+        assert!(matches!(
+            Scene::from_toml("colour = \"#ffffff\""),
+            Err(SceneError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn apply() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        Scene::from_toml(SCENE).unwrap().apply(&sdk).unwrap();
+        assert_eq!(mock.key_color(Key::Esc), Color::RED);
+        assert_eq!(mock.key_color(Key::G1), Color::WHITE);
+        assert_eq!(mock.key_color(Key::D), Color::from_rgb8(255, 136, 0));
+        assert_eq!(mock.key_color(Key::Space), Color::from_rgb8(0, 0, 0x20));
+        assert_eq!(
+            mock.zone_color(DeviceType::Mouse, 1),
+            Color::from_rgb8(0, 255, 0)
+        );
+    }
+
+    #[test]
+    fn watcher() {
+        let dir = test_util::temp_dir("scene");
+        let path = dir.join("scene.toml");
+        let mut watcher = SceneWatcher::new(&path);
+        assert!(matches!(watcher.poll(), Some(Err(SceneError::Io(_)))));
+        assert!(watcher.poll().is_none());
+
+        fs::write(&path, "color = \"#ff0000\"").unwrap();
+        let scene = watcher.poll().unwrap().unwrap();
+        assert_eq!(scene.color(), Some(Color::RED));
+        assert!(watcher.poll().is_none());
+
+        fs::write(&path, "color = \"#00ff00\"").unwrap();
+        test_util::touch_later(&path);
+        let scene = watcher.poll().unwrap().unwrap();
+        assert_eq!(scene.color(), Some(Color::GREEN));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(watcher.poll(), Some(Err(SceneError::Io(_)))));
+        assert!(watcher.poll().is_none());
+    }
+}
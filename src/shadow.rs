@@ -0,0 +1,508 @@
+//! Tracking of the lighting set through the SDK, which has no way to read it back.
+
+use super::{
+    lighting, Bitmap, Color, DeviceType, Error, Key, LightingBackend, Sdk, TargetDevices,
+    BITMAP_HEIGHT, BITMAP_LAYOUT, BITMAP_WIDTH,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+type Framebuffer = [[Color; BITMAP_WIDTH as usize]; BITMAP_HEIGHT as usize];
+
+/// The keys which don't have a cell in the bitmap.
+const OTHER_KEYS: [Key; 11] = [
+    Key::G1,
+    Key::G2,
+    Key::G3,
+    Key::G4,
+    Key::G5,
+    Key::G6,
+    Key::G7,
+    Key::G8,
+    Key::G9,
+    Key::GLogo,
+    Key::GBadge,
+];
+
+/// The lighting of every kind of device at one point in time, as returned by `Sdk::snapshot()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The colors of per-key devices, laid out like a `Bitmap`.
+    pub(crate) framebuffer: Framebuffer,
+    /// The colors of keys which aren't part of the bitmap, like the G keys.
+    pub(crate) other_keys: HashMap<Key, Color>,
+    /// The color of devices without per-key lighting, for zones which haven't been set separately.
+    pub(crate) color: Color,
+    pub(crate) zones: HashMap<(DeviceType, i32), Color>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Snapshot {
+        Snapshot {
+            framebuffer: [[Color::BLACK; BITMAP_WIDTH as usize]; BITMAP_HEIGHT as usize],
+            other_keys: OTHER_KEYS.iter().map(|&key| (key, Color::BLACK)).collect(),
+            color: Color::BLACK,
+            zones: HashMap::new(),
+        }
+    }
+}
+
+impl From<&Bitmap> for Snapshot {
+    /// Creates a snapshot of per-key devices showing `bitmap`, with transparent cells turned off.
+    fn from(bitmap: &Bitmap) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        snapshot.set_bitmap(lighting::ALL, bitmap, &[]);
+        snapshot
+    }
+}
+
+impl Snapshot {
+    /// Returns the color of `key` on per-key devices.
+    pub fn key_color(&self, key: Key) -> Color {
+        match key.bitmap_position() {
+            Some((row, col)) => self.framebuffer[row][col],
+            None => self.other_keys[&key],
+        }
+    }
+
+    /// Returns the color of the bitmap cell at `row` and `col` on per-key devices.
+    ///
+    /// # Panics
+    /// Panics if `row` or `col` is outside the 21x6 grid.
+    pub fn cell_color(&self, row: usize, col: usize) -> Color {
+        self.framebuffer[row][col]
+    }
+
+    /// Returns the colors of every cell on per-key devices, as an opaque `Bitmap`.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        for (row, colors) in self.framebuffer.iter().enumerate() {
+            for (col, &color) in colors.iter().enumerate() {
+                bitmap.set_cell(row, col, color);
+            }
+        }
+        bitmap
+    }
+
+    /// Returns the color of devices without per-key lighting, in zones which haven't been set separately.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Returns the color of zone `zone` on devices of type `device_type`.
+    pub fn zone_color(&self, device_type: DeviceType, zone: i32) -> Color {
+        self.zones
+            .get(&(device_type, zone))
+            .cloned()
+            .unwrap_or(self.color)
+    }
+
+    /// Sets the color of `key`, if per-key devices are in `target_device`.
+    pub(crate) fn set_key(&mut self, target_device: i32, key: Key, color: Color) {
+        if target_device & lighting::PERKEY_RGB == 0 {
+            return;
+        }
+        match key.bitmap_position() {
+            Some((row, col)) => self.framebuffer[row][col] = color,
+            None => {
+                self.other_keys.insert(key, color);
+            }
+        }
+    }
+
+    /// Sets the color of all the kinds of devices in `target_device`.
+    pub(crate) fn set_lighting(&mut self, target_device: i32, color: Color) {
+        if target_device & lighting::PERKEY_RGB != 0 {
+            self.framebuffer = [[color; BITMAP_WIDTH as usize]; BITMAP_HEIGHT as usize];
+            for key_color in self.other_keys.values_mut() {
+                *key_color = color;
+            }
+        }
+        if target_device & (lighting::RGB | lighting::MONOCHROME) != 0 {
+            self.color = color;
+            self.zones.clear();
+        }
+    }
+
+    pub(crate) fn set_zone(&mut self, device_type: DeviceType, zone: i32, color: Color) {
+        self.zones.insert((device_type, zone), color);
+    }
+
+    /// Copies the opaque cells of `bitmap` which aren't in `excluded_keys`,
+    /// if per-key devices are in `target_device`.
+    pub(crate) fn set_bitmap(
+        &mut self,
+        target_device: i32,
+        bitmap: &Bitmap,
+        excluded_keys: &[Key],
+    ) {
+        if target_device & lighting::PERKEY_RGB == 0 {
+            return;
+        }
+        for ((row, col), color) in bitmap.cells() {
+            let excluded = BITMAP_LAYOUT[row][col].is_some_and(|key| excluded_keys.contains(&key));
+            if bitmap.cell_alpha(row, col) != 0 && !excluded {
+                self.framebuffer[row][col] = color;
+            }
+        }
+    }
+}
+
+/// The state of the SDK as last set through an `Sdk`.
+#[derive(Debug)]
+pub(crate) struct Shadow {
+    pub(crate) target_device: i32,
+    pub(crate) excluded_keys: Vec<Key>,
+    pub(crate) lighting: Snapshot,
+    /// The lighting saved by `save_lighting()`.
+    pub(crate) saved: Snapshot,
+    /// The colors saved by `save_lighting_for_key()`.
+    pub(crate) saved_keys: HashMap<Key, Color>,
+    /// The lighting to restore when each live `LightingGuard` is dropped, along with its ID, from the oldest to the newest.
+    pub(crate) guards: Vec<(u64, Snapshot)>,
+    pub(crate) next_guard: u64,
+}
+
+impl Default for Shadow {
+    fn default() -> Shadow {
+        Shadow {
+            target_device: lighting::ALL,
+            excluded_keys: Vec::new(),
+            lighting: Snapshot::default(),
+            saved: Snapshot::default(),
+            saved_keys: HashMap::new(),
+            guards: Vec::new(),
+            next_guard: 0,
+        }
+    }
+}
+
+impl Shadow {
+    pub(crate) fn set_lighting(&mut self, color: Color) {
+        self.lighting.set_lighting(self.target_device, color);
+    }
+
+    pub(crate) fn set_key(&mut self, key: Key, color: Color) {
+        self.lighting.set_key(self.target_device, key, color);
+    }
+
+    pub(crate) fn set_bitmap(&mut self, bitmap: &Bitmap) {
+        self.lighting
+            .set_bitmap(self.target_device, bitmap, &self.excluded_keys);
+    }
+
+    /// Pushes the current lighting onto the guard stack, and returns the ID of the new guard.
+    pub(crate) fn push_guard(&mut self) -> u64 {
+        let id = self.next_guard;
+        self.next_guard += 1;
+        self.guards.push((id, self.lighting.clone()));
+        id
+    }
+
+    /// Removes the guard `id` from the stack, and returns the lighting to restore now.
+    ///
+    /// If newer guards are still alive, nothing needs to be restored yet: the next newest guard inherits
+    /// the snapshot instead, so that the lighting from before `id` is restored once it's dropped.
+    pub(crate) fn pop_guard(&mut self, id: u64) -> Option<Snapshot> {
+        let index = self.guards.iter().position(|&(guard, _)| guard == id)?;
+        let (_, snapshot) = self.guards.remove(index);
+        match self.guards.get_mut(index) {
+            Some((_, newer)) => {
+                *newer = snapshot;
+                None
+            }
+            None => Some(snapshot),
+        }
+    }
+}
+
+/// Restores the lighting from before it was created when it's dropped.
+///
+/// Guards can be nested, and each one restores exactly the lighting from before it was created.
+/// If a guard is dropped while a newer one is still alive, the newer guard's changes stay in place,
+/// and the older guard's lighting is restored once the newer one is dropped instead.
+///
+/// Errors while restoring the lighting on drop are ignored; call `restore()` to handle them.
+///
+/// Created by `Sdk::temporarily()`.
+#[must_use = "the lighting is restored as soon as the guard is dropped"]
+pub struct LightingGuard<'a, B: LightingBackend> {
+    pub(crate) sdk: &'a Sdk<B>,
+    pub(crate) id: u64,
+}
+
+impl<B: LightingBackend> LightingGuard<'_, B> {
+    /// Restores the lighting from before this guard was created, like dropping it.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn restore(self) -> Result<(), Error> {
+        self.restore_now()
+    }
+
+    fn restore_now(&self) -> Result<(), Error> {
+        let snapshot = self.sdk.shadow().pop_guard(self.id);
+        match snapshot {
+            Some(snapshot) => self.sdk.restore_snapshot(&snapshot),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<B: LightingBackend> Drop for LightingGuard<'_, B> {
+    fn drop(&mut self) {
+        let _ = self.restore_now();
+    }
+}
+
+impl<B: LightingBackend> fmt::Debug for LightingGuard<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LightingGuard")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Targets the devices which were targeted before it was created when it's dropped.
+///
+/// Errors while targeting the previous devices on drop are ignored; call `restore()` to handle them.
+///
+/// Created by `Sdk::targeting()`.
+#[must_use = "the previous devices are targeted again as soon as the guard is dropped"]
+pub struct TargetGuard<'a, B: LightingBackend> {
+    pub(crate) sdk: &'a Sdk<B>,
+    pub(crate) previous: Option<TargetDevices>,
+}
+
+impl<B: LightingBackend> TargetGuard<'_, B> {
+    /// Targets the devices which were targeted before this guard was created, like dropping it.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restore_now()
+    }
+
+    fn restore_now(&mut self) -> Result<(), Error> {
+        match self.previous.take() {
+            Some(previous) => self.sdk.set_target_devices(previous),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<B: LightingBackend> Drop for TargetGuard<'_, B> {
+    fn drop(&mut self) {
+        let _ = self.restore_now();
+    }
+}
+
+impl<B: LightingBackend> fmt::Debug for TargetGuard<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TargetGuard")
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Call, MockBackend};
+
+    fn red() -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        snapshot.set_lighting(lighting::ALL, Color::RED);
+        snapshot
+    }
+
+    #[test]
+    fn targets() {
+        let mut snapshot = Snapshot::default();
+        snapshot.set_lighting(lighting::RGB, Color::RED);
+        snapshot.set_key(lighting::RGB, Key::A, Color::GREEN);
+        assert_eq!(snapshot.key_color(Key::A), Color::BLACK);
+        assert_eq!(snapshot.zone_color(DeviceType::Mouse, 0), Color::RED);
+
+        snapshot.set_key(lighting::PERKEY_RGB, Key::G1, Color::GREEN);
+        assert_eq!(snapshot.key_color(Key::G1), Color::GREEN);
+    }
+
+    #[test]
+    fn guards_in_order() {
+        let mut shadow = Shadow::default();
+        let outer = shadow.push_guard();
+        shadow.lighting = red();
+        let inner = shadow.push_guard();
+        assert_eq!(shadow.pop_guard(inner), Some(red()));
+        assert_eq!(shadow.pop_guard(outer), Some(Snapshot::default()));
+        assert_eq!(shadow.pop_guard(outer), None);
+    }
+
+    #[test]
+    fn guards_out_of_order() {
+        let mut shadow = Shadow::default();
+        let outer = shadow.push_guard();
+        shadow.lighting = red();
+        let inner = shadow.push_guard();
+        assert_eq!(shadow.pop_guard(outer), None);
+        assert_eq!(shadow.pop_guard(inner), Some(Snapshot::default()));
+        assert!(shadow.guards.is_empty());
+    }
+
+    #[test]
+    fn shadow_matches_mock() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting(Color::BLUE).unwrap();
+        sdk.set_target_devices(TargetDevices::RGB).unwrap();
+        sdk.set_lighting(Color::RED).unwrap();
+        sdk.set_lighting_for_key(Key::A, Color::RED).unwrap();
+        sdk.set_lighting_for_zone(DeviceType::Headset, 0, Color::WHITE)
+            .unwrap();
+        sdk.set_target_devices(TargetDevices::PERKEY_RGB).unwrap();
+        sdk.save_lighting().unwrap();
+        sdk.exclude_keys_from_bitmap(&mut [Key::Esc]).unwrap();
+        let mut bitmap = Bitmap::filled(Color::GREEN);
+        bitmap.set_alpha(Key::F1, 0);
+        sdk.set_lighting_from_bitmap(&bitmap).unwrap();
+        sdk.set_lighting_for_code(crate::HidCode(0x04), Color::WHITE)
+            .unwrap();
+        sdk.save_lighting_for_key(Key::A).unwrap();
+        sdk.set_lighting_for_key(Key::G5, Color::RED).unwrap();
+        sdk.set_lighting_for_key(Key::A, Color::RED).unwrap();
+        sdk.restore_lighting_for_key(Key::A).unwrap();
+
+        assert_eq!(sdk.snapshot(), mock.snapshot());
+        assert_eq!(sdk.current_key_color(Key::Esc), Color::BLUE);
+        assert_eq!(sdk.current_key_color(Key::F1), Color::BLUE);
+        assert_eq!(sdk.current_key_color(Key::F2), Color::GREEN);
+        assert_eq!(sdk.current_key_color(Key::A), Color::WHITE);
+        assert_eq!(sdk.current_key_color(Key::G5), Color::RED);
+        assert_eq!(sdk.snapshot().color(), Color::RED);
+        assert_eq!(
+            sdk.snapshot().zone_color(DeviceType::Headset, 0),
+            Color::WHITE
+        );
+        assert_eq!(sdk.target_devices(), TargetDevices::PERKEY_RGB);
+        assert_eq!(sdk.excluded_keys(), [Key::Esc]);
+
+        sdk.restore_lighting().unwrap();
+        assert_eq!(sdk.snapshot(), mock.snapshot());
+        assert_eq!(sdk.current_key_color(Key::F2), Color::BLUE);
+    }
+
+    #[test]
+    fn failed_calls_leave_shadow_alone() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        mock.disconnect();
+        assert!(sdk.set_lighting(Color::RED).is_err());
+        assert_eq!(sdk.snapshot(), Snapshot::default());
+    }
+
+    #[test]
+    fn with_target() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_target_devices(TargetDevices::RGB).unwrap();
+
+        let result = sdk.with_target(TargetDevices::PERKEY_RGB, |sdk| {
+            assert_eq!(mock.target_device(), lighting::PERKEY_RGB);
+            sdk.set_lighting(Color::RED)?;
+            Err::<(), _>(Error::InvalidArgument)
+        });
+        assert_eq!(result, Err(Error::InvalidArgument));
+        assert_eq!(mock.key_color(Key::A), Color::RED);
+        assert_eq!(mock.color(), Color::BLACK);
+        assert_eq!(mock.target_device(), lighting::RGB);
+        assert_eq!(sdk.target_devices(), TargetDevices::RGB);
+    }
+
+    #[test]
+    fn target_guard() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        {
+            let _outer = sdk.targeting(TargetDevices::RGB).unwrap();
+            let inner = sdk.targeting(TargetDevices::MONOCHROME).unwrap();
+            assert_eq!(sdk.target_devices(), TargetDevices::MONOCHROME);
+            inner.restore().unwrap();
+            assert_eq!(sdk.target_devices(), TargetDevices::RGB);
+        }
+        assert_eq!(sdk.target_devices(), TargetDevices::ALL);
+        assert_eq!(mock.target_device(), lighting::ALL);
+    }
+
+    #[test]
+    fn nested_guards() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting(Color::BLUE).unwrap();
+        sdk.set_lighting_for_zone(DeviceType::Mouse, 1, Color::GREEN)
+            .unwrap();
+
+        let outer = sdk.temporarily();
+        sdk.set_lighting(Color::RED).unwrap();
+        sdk.set_lighting_for_key(Key::G1, Color::WHITE).unwrap();
+        let inner = sdk.temporarily();
+        sdk.set_lighting_for_key(Key::A, Color::GREEN).unwrap();
+
+        drop(inner);
+        assert_eq!(mock.key_color(Key::A), Color::RED);
+        assert_eq!(mock.key_color(Key::G1), Color::WHITE);
+        drop(outer);
+        assert_eq!(mock.key_color(Key::A), Color::BLUE);
+        assert_eq!(mock.key_color(Key::G1), Color::BLUE);
+        assert_eq!(mock.color(), Color::BLUE);
+        assert_eq!(mock.zone_color(DeviceType::Mouse, 1), Color::GREEN);
+    }
+
+    #[test]
+    fn guards_dropped_out_of_order() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.set_lighting(Color::BLUE).unwrap();
+
+        let outer = sdk.temporarily();
+        sdk.set_lighting(Color::RED).unwrap();
+        let inner = sdk.temporarily();
+        sdk.set_lighting_for_key(Key::A, Color::GREEN).unwrap();
+
+        // The inner guard's changes are still in place.
+        drop(outer);
+        assert_eq!(mock.key_color(Key::A), Color::GREEN);
+        assert_eq!(mock.key_color(Key::B), Color::RED);
+        drop(inner);
+        assert_eq!(mock.key_color(Key::A), Color::BLUE);
+        assert_eq!(mock.key_color(Key::B), Color::BLUE);
+    }
+
+    #[test]
+    fn restore_keeps_target_and_exclusions() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        sdk.exclude_keys_from_bitmap(&mut [Key::Esc]).unwrap();
+        sdk.set_target_devices(TargetDevices::PERKEY_RGB).unwrap();
+
+        let guard = sdk.temporarily();
+        sdk.set_lighting(Color::RED).unwrap();
+        guard.restore().unwrap();
+
+        assert_eq!(mock.key_color(Key::Esc), Color::BLACK);
+        assert_eq!(mock.key_color(Key::F1), Color::BLACK);
+        assert_eq!(mock.target_device(), lighting::PERKEY_RGB);
+        // Devices without per-key lighting were never changed, so they aren't touched.
+        assert!(!mock.calls().contains(&Call::SetLighting(Color::BLACK)));
+    }
+
+    #[test]
+    fn restore_errors() {
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        let guard = sdk.temporarily();
+        sdk.set_lighting(Color::RED).unwrap();
+        mock.disconnect();
+        assert_eq!(guard.restore(), Err(Error::ConnectionLost));
+    }
+}
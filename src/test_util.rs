@@ -0,0 +1,27 @@
+//! Helpers for tests which read and write files.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Returns a directory for the test `name` in the system's temporary directory, creating it if needed.
+///
+/// The directory is different for each test run, so runs at the same time don't get in each other's way.
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lightsync-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Moves the modification time of the file at `path` 10 seconds past both now and its last modification time,
+/// so that a change is noticed even if the file system's timestamps are coarse.
+pub(crate) fn touch_later(path: &Path) {
+    let modified = fs::metadata(path).unwrap().modified().unwrap();
+    let later = modified.max(SystemTime::now()) + Duration::from_secs(10);
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+}
@@ -0,0 +1,465 @@
+use super::{Bitmap, Color, Effect, Error, Key, LightingBackend, Sdk};
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// How a color changes on the way to a keyframe.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    /// Changes at a constant rate.
+    #[default]
+    Linear,
+    /// Starts slowly and speeds up.
+    CubicIn,
+    /// Starts quickly and slows down.
+    CubicOut,
+    /// Starts and ends slowly.
+    CubicInOut,
+    /// Starts slowly and speeds up, more gently than `CubicIn`.
+    SineIn,
+    /// Starts quickly and slows down, more gently than `CubicOut`.
+    SineOut,
+    /// Starts and ends slowly, more gently than `CubicInOut`.
+    SineInOut,
+    /// Overshoots and bounces back a few times before settling, like a dropped ball.
+    Bounce,
+    /// Holds the previous color, and jumps to the keyframe's color when it's reached.
+    Step,
+    /// A cubic Bézier curve from (0, 0) to (1, 1) with control points (x1, y1) and (x2, y2),
+    /// like CSS's `cubic-bezier()`. `x1` and `x2` are clamped to 0 to 1.
+    Bezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    /// Returns how far between two colors to be at `t` of the way between their keyframes,
+    /// where both are from 0 to 1.
+    ///
+    /// `t` is clamped to 0 to 1. Some curves, like `Bezier` with control points outside of 0 to 1,
+    /// can go past 0 or 1 on the way.
+    pub fn ease(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Easing::Bounce => bounce(t),
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Easing::Bezier(x1, y1, x2, y2) => {
+                bezier(t, x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2)
+            }
+        }
+    }
+}
+
+/// The standard "bounce out" curve, made of four parabolas.
+fn bounce(t: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Returns the y of the cubic Bézier curve at `x`.
+fn bezier(x: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    // One dimension of the curve at parameter `s`, with the ends fixed at 0 and 1.
+    let curve = |s: f64, p1: f64, p2: f64| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+    };
+    // With x1 and x2 from 0 to 1, x only ever increases with `s`, so it can be found by bisection.
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..32 {
+        let mid = (low + high) / 2.0;
+        if curve(mid, x1, x2) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    curve((low + high) / 2.0, y1, y2)
+}
+
+/// A color to reach at a point in a track, and how to get there from the keyframe before.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    pub time: Duration,
+    pub color: Color,
+    pub easing: Easing,
+}
+
+/// Which keys a track sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Region {
+    All,
+    Keys(Vec<Key>),
+}
+
+/// The keyframes for a single key or a region of keys in a `Timeline`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    region: Region,
+    keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Creates an empty track for `keys`.
+    pub fn new(keys: impl IntoIterator<Item = Key>) -> Track {
+        Track {
+            region: Region::Keys(keys.into_iter().collect()),
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Creates an empty track for a single key.
+    pub fn key(key: Key) -> Track {
+        Track::new(Some(key))
+    }
+
+    /// Creates an empty track for every key in the bitmap grid.
+    pub fn all() -> Track {
+        Track {
+            region: Region::All,
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Adds a keyframe reaching `color` at `time`, eased from the keyframe before with `easing`.
+    ///
+    /// Keyframes are kept in order of time. Before the first keyframe, the track doesn't set any color.
+    pub fn with_keyframe(mut self, time: Duration, color: Color, easing: Easing) -> Track {
+        let index = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        self.keyframes.insert(
+            index,
+            Keyframe {
+                time,
+                color,
+                easing,
+            },
+        );
+        self
+    }
+
+    /// Returns the keyframes in the track, in order of time.
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Returns the time of the last keyframe.
+    pub fn duration(&self) -> Duration {
+        self.keyframes
+            .last()
+            .map_or(Duration::from_secs(0), |keyframe| keyframe.time)
+    }
+
+    /// Returns whether the track sets the color of `key`.
+    pub fn contains(&self, key: Key) -> bool {
+        match &self.region {
+            Region::All => key.bitmap_position().is_some(),
+            Region::Keys(keys) => keys.contains(&key),
+        }
+    }
+
+    /// Returns the color at `time`, or `None` if it's before the first keyframe.
+    ///
+    /// After the last keyframe, its color is held.
+    pub fn color_at(&self, time: Duration) -> Option<Color> {
+        let next = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time);
+        let previous = self.keyframes.get(next.checked_sub(1)?)?;
+        let next = match self.keyframes.get(next) {
+            Some(next) => next,
+            None => return Some(previous.color),
+        };
+        let t = (time - previous.time).as_secs_f64() / (next.time - previous.time).as_secs_f64();
+        Some(previous.color.lerp(next.color, next.easing.ease(t)))
+    }
+
+    /// Returns the keys in the track.
+    fn keys(&self) -> Box<dyn Iterator<Item = Key> + '_> {
+        match &self.region {
+            Region::All => Box::new(
+                (0..Bitmap::HEIGHT)
+                    .flat_map(|row| (0..Bitmap::WIDTH).filter_map(move |col| Key::at(row, col))),
+            ),
+            Region::Keys(keys) => Box::new(keys.iter().copied()),
+        }
+    }
+}
+
+/// An animation made of tracks of color keyframes, which either plays once or loops.
+///
+/// A timeline can be run by an `Engine` as an `Effect`, or applied with per-key calls with `apply()`,
+/// which also works for keys without a cell in the bitmap. Where tracks overlap, the last one added wins.
+///
+/// # Example
+/// ```no_run
+/// use lightsync::{Color, Easing, Engine, Key, Sdk, Timeline, Track};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let ms = Duration::from_millis;
+/// let timeline = Timeline::new()
+///     .with_track(
+///         Track::all()
+///             .with_keyframe(ms(0), Color::BLACK, Easing::Linear)
+///             .with_keyframe(ms(2000), Color::BLUE, Easing::SineInOut),
+///     )
+///     .with_track(
+///         Track::new(vec![Key::W, Key::A, Key::S, Key::D])
+///             .with_keyframe(ms(0), Color::WHITE, Easing::Linear)
+///             .with_keyframe(ms(500), Color::RED, Easing::Bounce)
+///             .with_keyframe(ms(2000), Color::WHITE, Easing::Bezier(0.4, 0.0, 0.2, 1.0)),
+///     )
+///     .looping(true);
+///
+/// let mut engine = Engine::new(Arc::new(Sdk::init()?), 30);
+/// engine.set_effect(timeline);
+/// engine.start();
+/// # Ok::<(), lightsync::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    looping: bool,
+    duration: Option<Duration>,
+}
+
+impl Timeline {
+    /// Creates an empty timeline which plays once.
+    pub fn new() -> Timeline {
+        Timeline::default()
+    }
+
+    /// Adds `track` to the timeline, on top of the tracks already added.
+    pub fn with_track(mut self, track: Track) -> Timeline {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Sets whether the timeline starts again from the beginning when it ends.
+    pub fn looping(mut self, looping: bool) -> Timeline {
+        self.looping = looping;
+        self
+    }
+
+    /// Sets how long the timeline lasts, instead of the time of the last keyframe in any track.
+    pub fn with_duration(mut self, duration: Duration) -> Timeline {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Returns the tracks in the timeline.
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// Returns how long the timeline lasts.
+    pub fn duration(&self) -> Duration {
+        self.duration.unwrap_or_else(|| {
+            self.tracks
+                .iter()
+                .map(Track::duration)
+                .max()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Returns whether a timeline which plays once has finished by `time`.
+    pub fn is_finished(&self, time: Duration) -> bool {
+        !self.looping && time >= self.duration()
+    }
+
+    /// Converts the time since the timeline started to the time within it, wrapping around if it loops.
+    fn position(&self, time: Duration) -> Duration {
+        let duration = self.duration();
+        if self.looping && duration > Duration::from_secs(0) {
+            let nanos = time.as_nanos() % duration.as_nanos();
+            Duration::from_nanos(nanos as u64)
+        } else {
+            time
+        }
+    }
+
+    /// Returns the color of `key` at `time`, or `None` if no track sets it yet.
+    pub fn color_at(&self, key: Key, time: Duration) -> Option<Color> {
+        let time = self.position(time);
+        self.tracks
+            .iter()
+            .rev()
+            .filter(|track| track.contains(key))
+            .find_map(|track| track.color_at(time))
+    }
+
+    /// Sets every key the timeline has a color for at `time`, one key at a time.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionLost` if the connection to the SDK has been lost.
+    pub fn apply<B: LightingBackend>(&self, sdk: &Sdk<B>, time: Duration) -> Result<(), Error> {
+        let time = self.position(time);
+        let mut colors: Vec<(Key, Color)> = Vec::new();
+        for track in &self.tracks {
+            if let Some(color) = track.color_at(time) {
+                for key in track.keys() {
+                    match colors.iter_mut().find(|(other, _)| *other == key) {
+                        Some((_, existing)) => *existing = color,
+                        None => colors.push((key, color)),
+                    }
+                }
+            }
+        }
+        for (key, color) in colors {
+            sdk.set_lighting_for_key(key, color)?;
+        }
+        Ok(())
+    }
+}
+
+impl Effect for Timeline {
+    fn render(&mut self, time: Duration, frame: &mut Bitmap) {
+        let time = self.position(time);
+        for track in &self.tracks {
+            if let Some(color) = track.color_at(time) {
+                for key in track.keys() {
+                    frame.set(key, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBackend;
+
+    const EASINGS: [Easing; 10] = [
+        Easing::Linear,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::Bounce,
+        Easing::Step,
+        Easing::Bezier(0.25, 0.1, 0.25, 1.0),
+    ];
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn easing_ends() {
+        for &easing in &EASINGS {
+            assert!(easing.ease(0.0).abs() < 1e-6, "{:?}", easing);
+            assert!((easing.ease(1.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+            assert!((easing.ease(2.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn easing_curves() {
+        assert_eq!(Easing::Linear.ease(0.25), 0.25);
+        assert_eq!(Easing::CubicIn.ease(0.5), 0.125);
+        assert_eq!(Easing::CubicOut.ease(0.5), 0.875);
+        assert_eq!(Easing::CubicInOut.ease(0.5), 0.5);
+        assert!((Easing::SineInOut.ease(0.5) - 0.5).abs() < 1e-9);
+        assert_eq!(Easing::Step.ease(0.99), 0.0);
+        assert!(Easing::Bounce.ease(0.37) > Easing::Bounce.ease(0.5));
+
+        // A Bézier curve with its control points on the diagonal is linear.
+        let linear = Easing::Bezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+        for &t in &[0.1, 0.5, 0.8] {
+            assert!((linear.ease(t) - t).abs() < 1e-6);
+        }
+        assert!(Easing::Bezier(0.0, 2.0, 1.0, 2.0).ease(0.5) > 1.0);
+    }
+
+    #[test]
+    fn tracks() {
+        let track = Track::key(Key::A)
+            .with_keyframe(ms(1000), Color::WHITE, Easing::Linear)
+            .with_keyframe(ms(0), Color::BLACK, Easing::Linear)
+            .with_keyframe(ms(2000), Color::RED, Easing::Step);
+        assert_eq!(track.duration(), ms(2000));
+        assert_eq!(
+            track.color_at(ms(500)),
+            Some(Color::from_rgb8(128, 128, 128))
+        );
+        assert_eq!(track.color_at(ms(1999)), Some(Color::WHITE));
+        assert_eq!(track.color_at(ms(2000)), Some(Color::RED));
+        assert_eq!(track.color_at(ms(9000)), Some(Color::RED));
+
+        let late = Track::key(Key::A).with_keyframe(ms(100), Color::RED, Easing::Linear);
+        assert_eq!(late.color_at(ms(50)), None);
+        assert_eq!(Track::key(Key::A).color_at(ms(0)), None);
+    }
+
+    #[test]
+    fn looping() {
+        let track = Track::all()
+            .with_keyframe(ms(0), Color::BLACK, Easing::Linear)
+            .with_keyframe(ms(1000), Color::WHITE, Easing::Linear);
+        let once = Timeline::new().with_track(track.clone());
+        let looping = once.clone().looping(true).with_duration(ms(2000));
+        assert_eq!(once.color_at(Key::A, ms(2500)), Some(Color::WHITE));
+        assert_eq!(
+            looping.color_at(Key::A, ms(2500)),
+            Some(Color::from_rgb8(128, 128, 128))
+        );
+        assert_eq!(looping.color_at(Key::G1, ms(2500)), None);
+        assert!(once.is_finished(ms(1000)));
+        assert!(!looping.is_finished(ms(1000)));
+    }
+
+    #[test]
+    fn overlapping_tracks() {
+        let mut timeline = Timeline::new()
+            .with_track(Track::all().with_keyframe(ms(0), Color::BLUE, Easing::Linear))
+            .with_track(Track::new(vec![Key::A, Key::G1]).with_keyframe(
+                ms(0),
+                Color::RED,
+                Easing::Linear,
+            ));
+        let mut frame = Bitmap::new();
+        timeline.render(ms(0), &mut frame);
+        assert_eq!(frame.get(Key::A), Some(Color::RED));
+        assert_eq!(frame.get(Key::B), Some(Color::BLUE));
+
+        let mock = MockBackend::new();
+        let sdk = Sdk::with_backend(mock.clone()).unwrap();
+        timeline.apply(&sdk, ms(0)).unwrap();
+        assert_eq!(mock.key_color(Key::A), Color::RED);
+        assert_eq!(mock.key_color(Key::B), Color::BLUE);
+        assert_eq!(mock.key_color(Key::G1), Color::RED);
+    }
+}
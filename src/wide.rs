@@ -0,0 +1,150 @@
+//! Null-terminated UTF-16 strings, which is how the SDK takes and returns the strings for config options.
+
+use super::Error;
+use std::os::raw::c_int;
+
+/// An owned, null-terminated UTF-16 string without any null characters before the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WideString {
+    units: Vec<u16>,
+}
+
+impl WideString {
+    /// Encodes `s` as UTF-16.
+    ///
+    /// Returns `Error::NulInName` if `s` contains a null byte, since the SDK would stop reading there.
+    pub(crate) fn new(s: &str) -> Result<WideString, Error> {
+        if s.contains('\0') {
+            return Err(Error::NulInName);
+        }
+        Ok(WideString {
+            units: s.encode_utf16().chain(Some(0)).collect(),
+        })
+    }
+
+    /// Returns a pointer to the string for the SDK, which is valid for as long as the `WideString` is.
+    pub(crate) fn as_ptr(&self) -> *const u16 {
+        self.units.as_ptr()
+    }
+
+    /// Returns a mutable pointer to the string, for SDK functions which don't take a `const` string.
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u16 {
+        self.units.as_mut_ptr()
+    }
+
+    /// Returns the UTF-16 code units of the string, including the null terminator.
+    pub(crate) fn as_slice_with_nul(&self) -> &[u16] {
+        &self.units
+    }
+}
+
+/// A buffer for the SDK to write a string into, starting out holding a default string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WideBuffer {
+    units: Vec<u16>,
+}
+
+impl WideBuffer {
+    /// The smallest buffer made, in UTF-16 code units.
+    pub(crate) const MIN_LEN: usize = 256;
+
+    /// Makes a buffer holding `default`, with room for at least `MIN_LEN` code units including the null terminator.
+    ///
+    /// Returns `Error::NulInName` if `default` contains a null byte.
+    pub(crate) fn new(default: &str) -> Result<WideBuffer, Error> {
+        let mut units = WideString::new(default)?.units;
+        units.resize(units.len().max(WideBuffer::MIN_LEN), 0);
+        Ok(WideBuffer { units })
+    }
+
+    /// Returns a pointer to the buffer for the SDK to write into.
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u16 {
+        self.units.as_mut_ptr()
+    }
+
+    /// Returns the size of the buffer, in code units.
+    pub(crate) fn len(&self) -> c_int {
+        self.units.len() as c_int
+    }
+
+    /// Decodes the string in the buffer.
+    pub(crate) fn to_string_lossy(&self) -> String {
+        decode(&self.units)
+    }
+}
+
+/// Encodes `values` as a list of null-terminated strings, ending with an empty string.
+///
+/// Returns `Error::NulInName` if any of `values` contains a null byte.
+pub(crate) fn encode_list(values: &[&str]) -> Result<Vec<u16>, Error> {
+    let mut units = Vec::new();
+    for value in values {
+        units.extend_from_slice(WideString::new(value)?.as_slice_with_nul());
+    }
+    units.push(0);
+    Ok(units)
+}
+
+/// Decodes the null-terminated UTF-16 string at the start of `units`, or all of `units` if it isn't null-terminated.
+///
+/// Invalid UTF-16 is replaced with U+FFFD, since the SDK's strings come from the user.
+pub(crate) fn decode(units: &[u16]) -> String {
+    let len = units
+        .iter()
+        .position(|&unit| unit == 0)
+        .unwrap_or(units.len());
+    String::from_utf16_lossy(&units[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_terminated() {
+        let s = WideString::new("Colors/Ωmega 🎨").unwrap();
+        let units = s.as_slice_with_nul();
+        assert_eq!(units.last(), Some(&0));
+        assert_eq!(units.iter().filter(|&&unit| unit == 0).count(), 1);
+        assert_eq!(decode(units), "Colors/Ωmega 🎨");
+        assert_eq!(WideString::new("").unwrap().as_slice_with_nul(), [0]);
+        assert_eq!(WideString::new("a\0b"), Err(Error::NulInName));
+    }
+
+    #[test]
+    fn buffer() {
+        let mut buffer = WideBuffer::new("default").unwrap();
+        assert_eq!(buffer.len(), WideBuffer::MIN_LEN as c_int);
+        assert_eq!(buffer.to_string_lossy(), "default");
+
+        // Simulate the SDK writing a shorter string over the default.
+        let chosen: Vec<u16> = "abc\0".encode_utf16().collect();
+        unsafe {
+            std::ptr::copy_nonoverlapping(chosen.as_ptr(), buffer.as_mut_ptr(), chosen.len());
+        }
+        assert_eq!(buffer.to_string_lossy(), "abc");
+
+        let long = "x".repeat(1000);
+        let buffer = WideBuffer::new(&long).unwrap();
+        assert_eq!(buffer.len(), 1001);
+        assert_eq!(buffer.to_string_lossy(), long);
+        assert_eq!(WideBuffer::new("\0"), Err(Error::NulInName));
+    }
+
+    #[test]
+    fn list() {
+        assert_eq!(
+            encode_list(&["Easy", "Hard"]).unwrap(),
+            "Easy\0Hard\0\0".encode_utf16().collect::<Vec<_>>()
+        );
+        assert_eq!(encode_list(&[]).unwrap(), [0]);
+        assert_eq!(encode_list(&["ok", "n\0"]), Err(Error::NulInName));
+    }
+
+    #[test]
+    fn decoding() {
+        assert_eq!(decode(&[0x48, 0x69]), "Hi");
+        assert_eq!(decode(&[0x48, 0, 0x69]), "H");
+        assert_eq!(decode(&[0xD800, 0x41]), "\u{FFFD}A");
+    }
+}